@@ -0,0 +1,249 @@
+//! Headless terrain preview: renders generator output to PNG images without a GPU.
+//!
+//! Runs the same noise and biome functions as the game over a large area and writes:
+//!   - biomes.png      biome map colored by the biome map colors
+//!   - heightmap.png   terrain height (black = y -64, white = y 256)
+//!   - surface.png     top block color with biome tint and hill shading, sea in blue
+//!   - caves_y<Y>.png  cave noise slice at height Y (red = carved, black = above terrain)
+//!
+//! Usage:
+//!   cargo run --release --example terrain_preview -- [options]
+//!
+//! Options:
+//!   --preset <name>      start from a built-in preset (default, amplified, archipelago, ...)
+//!   --config <file>      start from a preset file (TOML, see src/model/world/presets)
+//!   --seed <n>           world seed (default 0)
+//!   --size <px>          image width and height in pixels (default 512)
+//!   --scale <blocks>     blocks per pixel (default 1)
+//!   --center <x>,<z>     world position at the image center (default 0,0)
+//!   --cave-y <y>         height of the cave slice (default 20)
+//!   --set <field>=<val>  override a TerrainConfig field, may be repeated
+//!   --biome <file>       add a biome definition (TOML, see src/model/world/biomes),
+//!                        replacing the built-in biome of the same name; may be repeated
+//!   --heightmap <file>   read the terrain shape from a grayscale PNG or WebP image
+//!   --biome-map <file>   paint the biomes of a heightmap with their map colors
+//!   --heightmap-set <field>=<val>  override a HeightmapConfig field, may be repeated
+//!   --out <dir>          output directory (default preview/seed-<seed>)
+//!
+//! Options are applied in order, so --set after --preset overrides the preset.
+//!
+//! Example: compare two presets side by side
+//!   cargo run --release --example terrain_preview -- --preset default --out preview/default
+//!   cargo run --release --example terrain_preview -- --preset archipelago --out preview/archipelago
+//!
+//! Example: preview a painted island
+//!   cargo run --release --example terrain_preview -- --heightmap island.png --heightmap-set vertical_scale=96
+
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use image::{Rgb, RgbImage};
+use woxel::model::world::biome::BiomeBlender;
+use woxel::model::world::heightmap::{HeightmapConfig, HeightmapGenerator};
+use woxel::model::world::terrain::{TerrainConfig, VoxelDensityGenerator, SEA_LEVEL};
+use woxel::model::world::{BiomeRegistry, Block};
+
+struct Options {
+    config: TerrainConfig,
+    size: u32,
+    scale: i32,
+    center: (i32, i32),
+    cave_y: f32,
+    out: Option<PathBuf>,
+    heightmap: Option<PathBuf>,
+    biome_map: Option<PathBuf>,
+    heightmap_config: HeightmapConfig,
+    biomes: BiomeRegistry,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        config: TerrainConfig::default(),
+        size: 512,
+        scale: 1,
+        center: (0, 0),
+        cave_y: 20.0,
+        out: None,
+        heightmap: None,
+        biome_map: None,
+        heightmap_config: HeightmapConfig::default(),
+        biomes: (*BiomeRegistry::builtin()).clone(),
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--preset" => {
+                let name = value()?;
+                options.config = TerrainConfig::preset(&name).ok_or(format!("unknown preset '{name}'"))?;
+            }
+            "--config" => {
+                let path = value()?;
+                let text = std::fs::read_to_string(&path).map_err(|e| format!("cannot read {path}: {e}"))?;
+                options.config = TerrainConfig::from_toml(&text).map_err(|e| format!("{path}: {e}"))?;
+            }
+            "--seed" => options.config.set_field("seed", &value()?)?,
+            "--size" => options.size = value()?.parse().map_err(|_| "invalid --size")?,
+            "--scale" => options.scale = value()?.parse::<i32>().map_err(|_| "invalid --scale")?.max(1),
+            "--cave-y" => options.cave_y = value()?.parse().map_err(|_| "invalid --cave-y")?,
+            "--out" => options.out = Some(PathBuf::from(value()?)),
+            "--center" => {
+                let v = value()?;
+                let (x, z) = v.split_once(',').ok_or("--center expects <x>,<z>")?;
+                options.center = (
+                    x.trim().parse().map_err(|_| "invalid --center")?,
+                    z.trim().parse().map_err(|_| "invalid --center")?,
+                );
+            }
+            "--set" => {
+                let v = value()?;
+                let (field, val) = v.split_once('=').ok_or("--set expects <field>=<value>")?;
+                options.config.set_field(field.trim(), val)?;
+            }
+            "--biome" => {
+                let path = value()?;
+                let text = std::fs::read_to_string(&path).map_err(|e| format!("cannot read {path}: {e}"))?;
+                options.biomes = options.biomes.clone().with_toml(&text).map_err(|e| format!("{path}: {e}"))?;
+            }
+            "--heightmap" => options.heightmap = Some(PathBuf::from(value()?)),
+            "--biome-map" => options.biome_map = Some(PathBuf::from(value()?)),
+            "--heightmap-set" => {
+                let v = value()?;
+                let (field, val) = v.split_once('=').ok_or("--heightmap-set expects <field>=<value>")?;
+                options.heightmap_config.set_field(field.trim(), val)?;
+            }
+            "--help" | "-h" => {
+                println!("see the header of examples/terrain_preview.rs for usage");
+                std::process::exit(0);
+            }
+            _ => return Err(format!("unknown argument '{arg}'")),
+        }
+    }
+    Ok(options)
+}
+
+/// Map a value from [min, max] to a gray level
+fn gray(value: f32, min: f32, max: f32) -> u8 {
+    (((value - min) / (max - min)).clamp(0.0, 1.0) * 255.0) as u8
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let biomes = Rc::new(options.biomes);
+    let noise = VoxelDensityGenerator::with_biomes(options.config, biomes.clone());
+    let heightmap = options.heightmap.as_ref().map(|path| {
+        let terrain = VoxelDensityGenerator::with_biomes(options.config, biomes.clone());
+        HeightmapGenerator::open(path, options.biome_map.as_deref(), options.heightmap_config, terrain)
+            .unwrap_or_else(|e| {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            })
+    });
+    let generator = heightmap.as_ref().map_or(&noise, |heightmap| &heightmap.terrain);
+    let size = options.size;
+    let scale = options.scale;
+    let extent = size as i32 * scale;
+    let min_x = options.center.0 - extent / 2;
+    let min_z = options.center.1 - extent / 2;
+
+    println!(
+        "sampling {}x{} blocks around ({}, {}) with seed {}",
+        extent, extent, options.center.0, options.center.1, options.config.seed
+    );
+    let blender = BiomeBlender::new(generator, min_x, min_z, extent);
+
+    // sample all columns once, images are derived from these
+    let mut columns = Vec::with_capacity((size * size) as usize);
+    for pz in 0..size as i32 {
+        for px in 0..size as i32 {
+            let wx = (min_x + px * scale) as f32;
+            let wz = (min_z + pz * scale) as f32;
+            columns.push(match &heightmap {
+                Some(heightmap) => heightmap.sample_column(wx, wz),
+                None => generator.sample_column(&blender, wx, wz),
+            });
+        }
+    }
+    let column = |px: u32, pz: u32| &columns[(px + pz * size) as usize];
+
+    let mut biomes = RgbImage::new(size, size);
+    let mut heightmap = RgbImage::new(size, size);
+    let mut surface = RgbImage::new(size, size);
+    let mut caves = RgbImage::new(size, size);
+
+    for pz in 0..size {
+        for px in 0..size {
+            let c = column(px, pz);
+            biomes.put_pixel(px, pz, Rgb(generator.biomes.get(c.biome).map_color));
+
+            let h = gray(c.height as f32, -64.0, 256.0);
+            heightmap.put_pixel(px, pz, Rgb([h, h, h]));
+
+            // surface color with simple hill shading from the western neighbor
+            let west = column(px.saturating_sub(1), pz).height;
+            let shade = (1.0 + (c.height - west) as f32 * 0.08 / scale as f32).clamp(0.6, 1.4);
+            let mut color = if c.surface_block.is_tinted(2) {
+                c.tint.apply(c.surface_block.color(2))
+            } else {
+                c.surface_block.color(2)
+            };
+            // under water: mix sea floor and water color, darker with depth
+            if c.height <= SEA_LEVEL {
+                let depth = (SEA_LEVEL + 1 - c.height) as f32;
+                let water = Block::Water.color(2);
+                let w = (0.5 + depth / 40.0).min(1.0);
+                for i in 0..3 {
+                    color[i] = color[i] * (1.0 - w) + water[i] * w;
+                }
+            }
+            let channel = |v: f32| ((v * shade).clamp(0.0, 1.0) * 255.0) as u8;
+            surface.put_pixel(px, pz, Rgb([channel(color[0]), channel(color[1]), channel(color[2])]));
+
+            // cave slice: carved band in red, solid rock by noise value, air above terrain black
+            let wx = (min_x + px as i32 * scale) as f32;
+            let wz = (min_z + pz as i32 * scale) as f32;
+            let pixel = if options.cave_y >= c.height as f32 {
+                [0, 0, 0]
+            } else {
+                let noise = generator.cave_noise(wx, options.cave_y, wz);
+                if noise > generator.config.cave_noise_min && noise < generator.config.cave_noise_max {
+                    [220, 40, 40]
+                } else {
+                    let g = gray(noise, -1.0, 1.0);
+                    [g, g, g]
+                }
+            };
+            caves.put_pixel(px, pz, Rgb(pixel));
+        }
+    }
+
+    let out = options
+        .out
+        .unwrap_or_else(|| PathBuf::from(format!("preview/seed-{}", options.config.seed)));
+    if let Err(e) = std::fs::create_dir_all(&out) {
+        eprintln!("error: cannot create {}: {e}", out.display());
+        std::process::exit(1);
+    }
+
+    let images = [
+        ("biomes.png".to_string(), &biomes),
+        ("heightmap.png".to_string(), &heightmap),
+        ("surface.png".to_string(), &surface),
+        (format!("caves_y{}.png", options.cave_y), &caves),
+    ];
+    for (name, image) in images {
+        let path = out.join(name);
+        match image.save(&path) {
+            Ok(()) => println!("wrote {}", path.display()),
+            Err(e) => eprintln!("error: cannot write {}: {e}", path.display()),
+        }
+    }
+}
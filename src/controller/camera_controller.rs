@@ -1,5 +1,15 @@
 use glam::Vec3;
 use crate::model::Camera;
+use crate::model::WorldClock;
+
+/// Full health of the player
+pub const MAX_HEALTH: f32 = 20.0;
+
+/// Health regained per second outside of damaging blocks
+const HEALTH_REGEN: f32 = 1.0;
+
+/// Where the player starts, and comes back to when out of health
+const SPAWN: Vec3 = Vec3::new(8.0, 80.0, 8.0);
 
 /// Player/Game state - position, velocity, orientation
 pub struct GameState {
@@ -9,18 +19,43 @@ pub struct GameState {
     pub player_pitch: f32,
     pub player_active: bool,
     pub camera_follows_player: bool,
+    /// Time of day, shared by all dimensions
+    pub clock: WorldClock,
+    /// 0..=MAX_HEALTH, lost in lava
+    pub health: f32,
 }
 
 impl GameState {
     pub fn new() -> Self {
         Self {
-            player_pos: Vec3::new(8.0, 80.0, 8.0),
+            player_pos: SPAWN,
             player_vel: Vec3::ZERO,
             player_yaw: 0.0,
             player_pitch: 0.0,
             player_active: false,
             camera_follows_player: true,
+            clock: WorldClock::default(),
+            health: MAX_HEALTH,
+        }
+    }
+
+    /// Take the damage of the blocks the player is in (Block::damage_per_second), or
+    /// heal slowly; returns whether the player is out of health and has to respawn
+    pub fn update_health(&mut self, damage_per_second: f32, dt: f32) -> bool {
+        if damage_per_second > 0.0 {
+            self.health -= damage_per_second * dt;
+        } else {
+            self.health = (self.health + HEALTH_REGEN * dt).min(MAX_HEALTH);
         }
+        self.health <= 0.0
+    }
+
+    /// Back at the spawn point with full health, the spawn point is in the
+    /// home dimension (see Dimensions::go_home)
+    pub fn respawn(&mut self) {
+        self.health = MAX_HEALTH;
+        self.player_pos = SPAWN;
+        self.player_vel = Vec3::ZERO;
     }
 
     pub fn toggle_camera_follow(&mut self) {
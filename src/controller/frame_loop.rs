@@ -8,7 +8,7 @@ use crate::model::Camera;
 use super::camera_controller::{CameraController, GameState};
 use super::physics::PhysicsSystem;
 use super::input::InputState;
-use crate::model::Scene;
+use crate::model::{Block, Daylight, Dimensions, Fog, Scene};
 use crate::view::RenderState;
 use crate::utils::WorldCoord;
 use crate::ui;
@@ -22,6 +22,8 @@ pub struct FrameLoopContext {
     pub lighting_buf_data: Rc<RefCell<LightingUniform>>,
     pub depth_view_cell: Rc<RefCell<TextureView>>,
     pub core: Rc<RefCell<Scene>>,
+    /// Inactive dimensions, `core` is the scene of the active one
+    pub dimensions: Dimensions,
     pub input_state: Rc<RefCell<InputState>>,
     pub game_state: Rc<RefCell<GameState>>,
     pub camera_controller: CameraController,
@@ -46,9 +48,30 @@ pub struct LightingUniform {
     pub sun_dir: [f32; 3],
     pub sun_intensity: f32,
     pub ambient: f32,
-    pub _pad1: f32,
-    pub _pad2: f32,
-    pub _pad3: f32,
+    /// FogMode as 0 linear, 1 exp, 2 exp²
+    pub fog_mode: u32,
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub fog_color: [f32; 3],
+    pub fog_density: f32,
+    /// Camera position, fog is by distance from it
+    pub eye: [f32; 3],
+    /// Light left where no sky light reaches (DimensionSettings::cave_light)
+    pub cave_light: f32,
+    /// Seconds since start, animates lava
+    pub seconds: f32,
+    pub _pad: [f32; 3],
+}
+
+impl LightingUniform {
+    pub fn set_fog(&mut self, fog: &Fog, eye: glam::Vec3) {
+        self.fog_mode = fog.mode as u32;
+        self.fog_start = fog.start;
+        self.fog_end = fog.end;
+        self.fog_color = fog.color;
+        self.fog_density = fog.density;
+        self.eye = eye.to_array();
+    }
 }
 
 #[repr(C)]
@@ -117,11 +140,48 @@ impl FrameLoopContext {
             game.player_pos = pos;
             game.player_vel = vel;
 
+            // Lava burns the feet and the body
+            let damage = {
+                let core = self.core.borrow();
+                [0.0, 1.0]
+                    .iter()
+                    .filter_map(|dy| core.get_block(&WorldCoord(pos.x.floor() as isize, (pos.y + dy).floor() as isize, pos.z.floor() as isize)))
+                    .map(|block| block.damage_per_second())
+                    .fold(0.0, f32::max)
+            };
+            if game.update_health(damage, dt) {
+                // out of health: start over at the spawn point back home
+                self.dimensions.go_home(&mut self.core.borrow_mut());
+                game.respawn();
+            }
+
             // Update camera to match player after physics
             self.camera_controller
                 .sync_camera_from_player(&mut self.cam.borrow_mut(), game.player_pos);
         }
 
+        // Step through portals into another dimension
+        if let Some(arrival) = self.dimensions.update(&mut self.core.borrow_mut(), game.player_pos) {
+            game.player_pos = arrival;
+            game.player_vel = glam::Vec3::ZERO;
+            self.camera_controller
+                .sync_camera_from_player(&mut self.cam.borrow_mut(), arrival);
+        }
+
+        // Start over in the world picked in the New World window
+        let new_world = self.input_state.borrow_mut().new_world.take();
+        if let Some(config) = new_world {
+            self.dimensions.new_world(&mut self.core.borrow_mut(), config);
+            game.respawn();
+            self.camera_controller
+                .sync_camera_from_player(&mut self.cam.borrow_mut(), game.player_pos);
+        }
+
+        // Keep block edits across reloads
+        if let Err(e) = self.dimensions.save_edits(&mut self.core.borrow_mut()) {
+            log_1(&format!("cannot save block edits: {e}").into());
+        }
+
         // Update chunks based on player position
         let p_pos = game.player_pos;
         drop(game); // Release game_state borrow
@@ -129,6 +189,7 @@ impl FrameLoopContext {
         self.core.borrow_mut().update(
             &WorldCoord(p_pos.x as isize, p_pos.y as isize, p_pos.z as isize),
             device,
+            queue,
             100
         );
 
@@ -140,18 +201,55 @@ impl FrameLoopContext {
             self.cam.borrow().view_proj().to_cols_array_2d();
         queue.write_buffer(&self.cam_buf, 0, bytemuck::bytes_of(&*self.cam_buf_data.borrow()));
 
-        // Update sun position relative to player
-        let player_eye = self.cam.borrow().eye;
-        let sun_offset = glam::Vec3::new(50.0, 100.0, 50.0);
-        let sun_pos = player_eye + sun_offset;
-        let sun_dir = (sun_pos - player_eye).normalize();
-        self.lighting_buf_data.borrow_mut().sun_dir = [sun_dir.x, sun_dir.y, sun_dir.z];
+        // Cull chunks outside the view or hidden behind rock
+        self.core.borrow_mut().update_visibility(&self.cam.borrow());
+
+        // Advance the time of day: sun or moon light, sky color
+        let daylight = {
+            let mut game = self.game_state.borrow_mut();
+            game.clock.advance(dt);
+            game.clock.daylight(&self.core.borrow().dimension)
+        };
+        // Fog up to the view distance, or of the water the camera is in
+        let (fog, underwater) = {
+            let core = self.core.borrow();
+            let eye = self.cam.borrow().eye;
+            let underwater = core.get_block(&WorldCoord(eye.x.floor() as isize, eye.y.floor() as isize, eye.z.floor() as isize))
+                .is_some_and(|block| matches!(block, Block::Water | Block::LakeWater));
+            (Fog::for_view(self.input_state.borrow().fog, core.view_distance(), &daylight, underwater), underwater)
+        };
+        {
+            let light_dir = daylight.light_dir;
+            let mut lighting = self.lighting_buf_data.borrow_mut();
+            lighting.sun_dir = [light_dir.x, light_dir.y, light_dir.z];
+            lighting.sun_intensity = daylight.sun_intensity;
+            lighting.ambient = daylight.ambient;
+            lighting.set_fog(&fog, self.cam.borrow().eye);
+            lighting.cave_light = self.core.borrow().dimension.cave_light;
+            lighting.seconds = (now / 1000.0) as f32;
+        }
         queue.write_buffer(&self.lighting_buf, 0, bytemuck::bytes_of(&*self.lighting_buf_data.borrow()));
+        render_state.sky_color = daylight.sky_color;
+        {
+            let core = self.core.borrow();
+            let clouds = core.dimension.day_cycle.then_some(core.bounds().cloud_height as f32);
+            if underwater {
+                // the water hides the sky too: a plain gradient of the fog color
+                let water = Daylight { sky_color: fog.color, fog_color: fog.color, ..daylight };
+                render_state.sky.update(queue, &self.cam.borrow(), &water, (now / 1000.0) as f32, None);
+            } else {
+                render_state.sky.update(queue, &self.cam.borrow(), &daylight, (now / 1000.0) as f32, clouds);
+            }
+        }
+
+        // Fit the shadow cascades to the camera
+        let shadow_settings = self.input_state.borrow().shadows;
+        render_state.shadows.update(device, queue, &self.cam.borrow(), daylight.light_dir, shadow_settings);
 
         // Raycast to find block under crosshair
         let raycast_result = self.cam.borrow().raycast(8.0, |x, y, z| {
             match self.core.borrow().get_block(&WorldCoord(x as isize, y as isize, z as isize)) {
-                Some(b) => b.is_solid(),
+                Some(b) => b.is_solid() || b.is_portal(),
                 None => false,
             }
         });
@@ -177,7 +275,8 @@ impl FrameLoopContext {
                     &WorldCoord(bx as isize, by as isize, bz as isize),
                     crate::model::Block::Empty,
                     true,
-                    device
+                    device,
+                    queue
                 ) {
                     log_1(&"removed block".into());
                     // Successfully removed block, reload chunk
@@ -196,7 +295,8 @@ impl FrameLoopContext {
                     &WorldCoord(placement_x as isize, placement_y as isize, placement_z as isize),
                     input.selected_block,
                     true,
-                    device
+                    device,
+                    queue
                 ) {
                     log_1(&format!("set block to {:?}", input.selected_block).into());
                     // Successfully placed block
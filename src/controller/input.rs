@@ -1,6 +1,8 @@
 /// Platform-agnostic input handling system
 use std::collections::HashSet;
-use crate::model::Block;
+use crate::model::{Block, FogMode};
+use crate::model::world::terrain::TerrainConfig;
+use crate::view::ShadowSettings;
 
 /// Platform-independent input events
 #[derive(Debug, Clone)]
@@ -46,6 +48,12 @@ pub struct InputState {
     pub selected_block: Block,
     pub wireframe_mode: bool,
     pub show_chunk_borders: bool,
+    /// Shadow quality from the settings window, read by the renderer every frame
+    pub shadows: ShadowSettings,
+    /// Distance fog falloff from the settings window
+    pub fog: FogMode,
+    /// World to start from the New World window, taken by the frame loop
+    pub new_world: Option<TerrainConfig>,
     pub mouse_pos: (f32, f32),
     pub left_click: bool,
     pub right_click: bool,
@@ -60,6 +68,9 @@ impl InputState {
             selected_block: Block::Grass,
             wireframe_mode: false,
             show_chunk_borders: false,
+            shadows: ShadowSettings::default(),
+            fog: FogMode::default(),
+            new_world: None,
             mouse_pos: (0.0, 0.0),
             left_click: false,
             right_click: false,
@@ -139,7 +150,8 @@ impl InputState {
         let blocks = [
             Block::Grass, Block::Dirt, Block::Stone, Block::Sand, Block::Gravel,
             Block::Cobblestone, Block::Bedrock, Block::OakLeaves, Block::Wood,
-            Block::Water, Block::Cloud,
+            Block::Water, Block::Cloud, Block::Portal, Block::SkyPortal,
+            Block::Torch, Block::Lantern, Block::Glowstone, Block::Lava,
         ];
         let current_idx = blocks.iter().position(|&b| b == self.selected_block).unwrap_or(0);
         let next_idx = if forward {
@@ -257,6 +269,12 @@ impl InputProcessor {
             "9" => Some(Block::Wood),
             "0" => Some(Block::Water),
             "-" | "_" => Some(Block::Cloud),
+            "=" => Some(Block::Portal),
+            "]" => Some(Block::SkyPortal),
+            "t" | "T" => Some(Block::Torch),
+            "y" | "Y" => Some(Block::Lantern),
+            "u" | "U" => Some(Block::Glowstone),
+            "i" | "I" => Some(Block::Lava),
             _ => None,
         }
     }
@@ -32,13 +32,14 @@ impl PhysicsSystem {
 
         // Apply velocity
         let new_pos = *pos + *vel * dt;
+        let bounds = world.bounds();
 
         // Check for ground (block below)
         let below_block = world
             .get_block(&WorldCoord(
-                new_pos.x as isize,
-                (new_pos.y - 1.2).max(0.0) as isize,
-                new_pos.z as isize,
+                new_pos.x.floor() as isize,
+                (new_pos.y - 1.2).max(bounds.min_y as f32).floor() as isize,
+                new_pos.z.floor() as isize,
             ))
             .unwrap();
         let on_ground = below_block.is_solid();
@@ -59,30 +60,71 @@ impl PhysicsSystem {
         }
 
         // Horizontal collision (simple axis-aligned)
-        let check_block = |x: isize, y: isize, z: isize| {
+        let check_block = |x: f32, y: f32, z: f32| {
             world
-                .get_block(&WorldCoord(x, y, z))
+                .get_block(&WorldCoord(x.floor() as isize, y.floor() as isize, z.floor() as isize))
                 .unwrap()
                 .is_solid()
         };
 
         let x_next = new_pos.x;
-        if !check_block(x_next as isize, new_pos.y as isize, pos.z as isize)
-            && !check_block(x_next as isize, (new_pos.y + 1.5) as isize, pos.z as isize)
+        if !check_block(x_next, new_pos.y, pos.z)
+            && !check_block(x_next, new_pos.y + 1.5, pos.z)
         {
             pos.x = x_next;
         }
 
         let z_next = new_pos.z;
-        if !check_block(pos.x as isize, new_pos.y as isize, z_next as isize)
-            && !check_block(pos.x as isize, (new_pos.y + 1.5) as isize, z_next as isize)
+        if !check_block(pos.x, new_pos.y, z_next)
+            && !check_block(pos.x, new_pos.y + 1.5, z_next)
         {
             pos.z = z_next;
         }
 
         // Clamp to world bounds
-        pos.y = pos.y.max(0.1).min(254.0);
+        pos.y = pos.y.max(bounds.min_y as f32 + 0.1).min(bounds.max_y as f32 - 2.0);
         pos.x = pos.x.max(-50.0).min(250.0);
         pos.z = pos.z.max(-50.0).min(250.0);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::world::terrain::TerrainConfig;
+    use crate::model::world::{Block, Chunk, VoxelDensityGenerator, WorldGenerator, CHUNK_SIZE};
+    use crate::model::DimensionSettings;
+    use crate::utils::{BlockCoord, ChunkCoord};
+
+    #[test]
+    fn test_player_lands_below_zero() {
+        // stone up to y = -5 in the chunks around the origin: the floor and
+        // the player are at negative x, y and z
+        let generator = WorldGenerator::Noise(VoxelDensityGenerator::with_config(TerrainConfig::default()));
+        let mut scene = Scene::parked([4, 4, 4], generator, DimensionSettings::overworld());
+        let top = -4;
+        for (cx, cy, cz) in [-1, 0].into_iter().flat_map(|x| [(x, -1), (x, 0)]).flat_map(|(x, y)| [(x, y, -1), (x, y, 0)]) {
+            let coord = ChunkCoord(cx, cy, cz);
+            let mut chunk = Chunk::new_empty();
+            for (x, y, z) in (0..CHUNK_SIZE).flat_map(|x| (0..CHUNK_SIZE).flat_map(move |y| (0..CHUNK_SIZE).map(move |z| (x, y, z)))) {
+                if cy * CHUNK_SIZE + y < top {
+                    chunk.set_block(&BlockCoord(x as usize, y as usize, z as usize), Block::Stone, true);
+                }
+            }
+            scene.insert_chunk(&coord, chunk);
+        }
+
+        let physics = PhysicsSystem::new();
+        let (mut pos, mut vel) = (Vec3::new(-2.5, -1.0, -2.5), Vec3::ZERO);
+        let mut lowest = pos.y;
+        for _ in 0..180 {
+            physics.update(&mut pos, &mut vel, &HashSet::new(), &scene, 1.0 / 60.0);
+            lowest = lowest.min(pos.y);
+        }
+
+        // standing on the floor: never sunk into it and not falling any more
+        assert!(lowest > top as f32 + 1.0, "sank to {lowest}");
+        assert!(pos.y < top as f32 + 2.0, "still at {}", pos.y);
+        assert_eq!((pos.x, pos.z), (-2.5, -2.5));
+    }
+}
@@ -1,6 +1,7 @@
 // Re-export all public modules so they can be used from main.rs
 pub mod logging;
 pub mod utils;
+pub mod storage;
 pub mod ui;
 
 // MVC Architecture
@@ -18,9 +19,21 @@ use glam::Vec3;
 
 use controller::{GameState, CameraController, CameraUniform, LightingUniform, TransformUniform, InputState, FrameLoopContext, PhysicsSystem, InputProcessor};
 use model::{Camera, Scene};
+#[cfg(target_arch = "wasm32")]
+use model::{DimensionSettings, Dimensions};
+#[cfg(target_arch = "wasm32")]
+use model::world::heightmap::{HeightmapConfig, HeightmapGenerator};
+#[cfg(target_arch = "wasm32")]
+use model::world::terrain::{TerrainConfig, VoxelDensityGenerator};
+#[cfg(target_arch = "wasm32")]
+use model::world::{BiomeRegistry, WorldGenerator};
+#[cfg(target_arch = "wasm32")]
+use model::world::underworld::UnderworldGenerator;
 use view::render;
 #[cfg(target_arch = "wasm32")]
 use view::GpuContext;
+#[cfg(target_arch = "wasm32")]
+use view::{ShadowResources, ShadowSettings, SkyResources};
 
 
 #[cfg(target_arch = "wasm32")]
@@ -74,9 +87,16 @@ async fn setup_app(
         sun_dir: [0.5, 1.0, 0.5],
         sun_intensity: 0.3,
         ambient: 0.7,
-        _pad1: 0.0,
-        _pad2: 0.0,
-        _pad3: 0.0,
+        // exp fog of density 0: no fog until the first frame sets it
+        fog_mode: 1,
+        fog_start: 0.0,
+        fog_end: 0.0,
+        fog_color: [1.0, 1.0, 1.0],
+        fog_density: 0.0,
+        eye: [0.0; 3],
+        cave_light: 0.05,
+        seconds: 0.0,
+        _pad: [0.0; 3],
     }));
     let lighting_buf = camera_resources.lighting_buffer;
     gpu.queue.as_ref().write_buffer(&lighting_buf, 0, bytemuck::bytes_of(&*lighting_buf_data.borrow()));
@@ -86,10 +106,15 @@ async fn setup_app(
     let (depth_tex, depth_view) = render::create_depth_texture(gpu.device.as_ref(), width, height);
     let depth_view_cell: Rc<RefCell<wgpu::TextureView>> = Rc::new(RefCell::new(depth_view));
 
+    // Shadow maps (quality from the settings window, off by default on WebGL)
+    let shadow_settings = ShadowSettings::for_device(gpu.webgl);
+    let shadows = ShadowResources::new(gpu.device.as_ref(), &camera_resources.palette_buffer, shadow_settings);
+
     // Create chunk pipelines
-    let pipes = render::create_chunk_pipelines(gpu.device.as_ref(), gpu.format, &cam_bgl, depth_format);
+    let pipes = render::create_chunk_pipelines(gpu.device.as_ref(), gpu.format, &cam_bgl, &shadows.bind_group_layout, depth_format);
     let render_pipeline = pipes.pipeline;
     let wireframe_pipeline = pipes.wireframe_pipeline;
+    let horizon_pipeline = pipes.horizon_pipeline;
     let wireframe_available = wireframe_pipeline.is_some();
 
     // Outline resources
@@ -108,10 +133,28 @@ async fn setup_app(
     }));
 
     // World and game state
-    let core = Rc::new(RefCell::new(Scene::new([128, 64, 128], gpu.device.as_ref())));
+    let generator = world_generator_from_url(window).await;
+    let core = Rc::new(RefCell::new(Scene::with_generator([128, 64, 128], gpu.device.as_ref(), generator)));
+    // the other dimensions share the seed of the overworld and load only once entered
+    let seed = core.borrow().terrain_config().seed;
+    let sky_config = TerrainConfig { seed, ..TerrainConfig::preset("sky_world").unwrap_or_default() };
+    let mut dimensions = Dimensions::new(vec![
+        Scene::parked(
+            [128, 64, 128],
+            WorldGenerator::Underworld(UnderworldGenerator::new(seed)),
+            DimensionSettings::underworld(),
+        ),
+        Scene::parked(
+            [128, 64, 128],
+            WorldGenerator::Noise(VoxelDensityGenerator::with_config(sky_config)),
+            DimensionSettings::sky_world(),
+        ),
+    ]);
+    dimensions.load_edits(&mut core.borrow_mut());
     let raycast_target: Rc<RefCell<Option<(i32, i32, i32)>>> = Rc::new(RefCell::new(None));
     let game_state = Rc::new(RefCell::new(GameState::new()));
     let input_state = Rc::new(RefCell::new(InputState::new()));
+    input_state.borrow_mut().shadows = shadow_settings;
     let egui_events: Rc<RefCell<Vec<egui::Event>>> = Rc::new(RefCell::new(Vec::new()));
 
     // egui setup
@@ -138,11 +181,16 @@ async fn setup_app(
         height,
         pipeline: render_pipeline,
         wireframe_pipeline: wireframe_pipeline.clone(),
+        horizon_pipeline,
         outline_pipeline,
         outline_mesh,
         show_outline: false,
         chunk_border_mesh,
         show_chunk_borders: false,
+        chunk_draws: render::ChunkDrawList::new(gpu.device.as_ref(), gpu.indirect_draw),
+        sky: SkyResources::new(gpu.device.as_ref(), gpu.format, depth_format),
+        shadows,
+        shadow_draws: render::ChunkDrawList::new(gpu.device.as_ref(), gpu.indirect_draw),
         player_pos: Vec3::new(8.0, 80.0, 8.0),
         camera_yaw: 0.0,
         camera_pitch: 0.0,
@@ -155,6 +203,7 @@ async fn setup_app(
         egui_full_output: None,
         egui_dpr: 1.0,
         wireframe_mode: false,
+        sky_color: DimensionSettings::overworld().sky_color,
     };
 
     // Setup frame loop
@@ -166,6 +215,7 @@ async fn setup_app(
         lighting_buf_data,
         depth_view_cell,
         core,
+        dimensions,
         input_state,
         game_state,
         camera_controller: CameraController::new(),
@@ -192,7 +242,7 @@ async fn setup_app(
                 gpu.device.as_ref(),
                 gpu.queue.as_ref(),
                 &gpu.surface,
-                &core_borrow.active,
+                &core_borrow,
                 &dv,
                 &cam_bg,
                 &outline_bg,
@@ -426,6 +476,94 @@ fn init_canvas(width: u32, height: u32) -> Result<(Window, Document, HtmlCanvasE
     Ok((window, document, canvas_el))
 }
 
+/// World generator selected by the page URL, e.g. `?preset=archipelago&seed=42&caves=true`
+///
+/// `preset` picks a built-in preset, every other parameter overrides a single
+/// TerrainConfig or HeightmapConfig field. `heightmap` (and optionally `biome_map`)
+/// loads a PNG/WebP image relative to the page, e.g.
+/// `?heightmap=maps/island.png&biome_map=maps/island_biomes.png&vertical_scale=160`.
+/// Falls back to the noise generator if the images can't be loaded. `biomes` is a
+/// comma separated list of biome files (see src/model/world/biomes) added to the
+/// built-in biomes, e.g. `?biomes=mods/volcano.toml,mods/desert.toml`.
+#[cfg(target_arch = "wasm32")]
+async fn world_generator_from_url(window: &Window) -> WorldGenerator {
+    let search = window.location().search().unwrap_or_default();
+    let params: Vec<(&str, &str)> = search
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.split_once('=').unwrap_or((p, "")))
+        .collect();
+    let param = |name: &str| params.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+
+    // preset first, so single fields can override it regardless of their order
+    let mut config = match param("preset") {
+        Some(name) => TerrainConfig::preset(name).unwrap_or_else(|| {
+            tracing::warn!("unknown terrain preset '{name}', using default");
+            TerrainConfig::default()
+        }),
+        None => TerrainConfig::default(),
+    };
+    let mut heightmap_config = HeightmapConfig::default();
+    for (key, value) in params.iter().filter(|(key, _)| !matches!(*key, "preset" | "heightmap" | "biome_map" | "biomes")) {
+        if let Err(e) = config.set_field(key, value) {
+            if heightmap_config.set_field(key, value).is_err() {
+                tracing::warn!("ignoring url parameter: {e}");
+            }
+        }
+    }
+
+    let mut biomes = (*BiomeRegistry::builtin()).clone();
+    for url in param("biomes").unwrap_or_default().split(',').filter(|url| !url.is_empty()) {
+        let loaded = fetch_bytes(window, url)
+            .await
+            .and_then(|bytes| String::from_utf8(bytes).map_err(|e| format!("{url}: {e}")))
+            .and_then(|text| biomes.clone().with_toml(&text).map_err(|e| format!("{url}: {e}")));
+        match loaded {
+            Ok(registry) => biomes = registry,
+            Err(e) => tracing::warn!("ignoring biome file: {e}"),
+        }
+    }
+    let biomes = Rc::new(biomes);
+
+    let Some(heightmap_url) = param("heightmap") else {
+        return WorldGenerator::Noise(VoxelDensityGenerator::with_biomes(config, biomes));
+    };
+    let heightmap = async {
+        let heightmap = fetch_bytes(window, heightmap_url).await?;
+        let biome_map = match param("biome_map") {
+            Some(url) => Some(fetch_bytes(window, url).await?),
+            None => None,
+        };
+        let terrain = VoxelDensityGenerator::with_biomes(config, biomes.clone());
+        HeightmapGenerator::from_memory(&heightmap, biome_map.as_deref(), heightmap_config, terrain)
+    };
+    match heightmap.await {
+        Ok(generator) => WorldGenerator::Heightmap(generator),
+        Err(e) => {
+            tracing::warn!("can't load heightmap, using noise terrain: {e}");
+            WorldGenerator::Noise(VoxelDensityGenerator::with_biomes(config, biomes))
+        }
+    }
+}
+
+/// Download a file relative to the page
+#[cfg(target_arch = "wasm32")]
+async fn fetch_bytes(window: &Window, url: &str) -> Result<Vec<u8>, String> {
+    use wasm_bindgen_futures::JsFuture;
+
+    let response: web_sys::Response = JsFuture::from(window.fetch_with_str(url))
+        .await
+        .and_then(|r| r.dyn_into())
+        .map_err(|e| format!("{url}: {e:?}"))?;
+    if !response.ok() {
+        return Err(format!("{url}: HTTP {}", response.status()));
+    }
+    let buffer = response.array_buffer().map_err(|e| format!("{url}: {e:?}"))?;
+    let buffer = JsFuture::from(buffer).await.map_err(|e| format!("{url}: {e:?}"))?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
 #[cfg(target_arch = "wasm32")]
 fn js_error<E: Into<String>>(msg: E) -> JsValue {
     JsValue::from_str(&msg.into())
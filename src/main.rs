@@ -46,6 +46,7 @@ struct App {
     depth_view: wgpu::TextureView,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
+    shadows: view::ShadowResources,
     lighting_buffer: wgpu::Buffer,
     
     // egui
@@ -119,14 +120,24 @@ impl App {
             sun_dir: [0.5, -1.0, 0.3],
             sun_intensity: 1.0,
             ambient: 0.35,
-            _pad1: 0.0,
-            _pad2: 0.0,
-            _pad3: 0.0,
+            // exp fog of density 0: no fog until the first frame sets it
+            fog_mode: 1,
+            fog_start: 0.0,
+            fog_end: 0.0,
+            fog_color: [1.0, 1.0, 1.0],
+            fog_density: 0.0,
+            eye: [0.0; 3],
+            cave_light: 0.05,
+            seconds: 0.0,
+            _pad: [0.0; 3],
         };
         queue.write_buffer(&lighting_buffer, 0, bytemuck::bytes_of(&lighting_buf_data));
         
+        // Shadow maps (no shadow passes in this renderer yet)
+        let shadows = view::ShadowResources::new(&device, &camera_resources.palette_buffer, view::ShadowSettings { enabled: false, ..Default::default() });
+
         // Create chunk pipelines
-        let pipes = render::create_chunk_pipelines(&device, config.format, &camera_bgl, depth_format);
+        let pipes = render::create_chunk_pipelines(&device, config.format, &camera_bgl, &shadows.bind_group_layout, depth_format);
         let pipeline = pipes.pipeline;
         let wireframe_pipeline = pipes.wireframe_pipeline;
         
@@ -181,6 +192,7 @@ impl App {
             depth_view,
             camera_buffer,
             camera_bind_group,
+            shadows,
             lighting_buffer,
             egui_renderer,
             egui_state,
@@ -361,7 +373,8 @@ impl App {
             self.camera.eye.y as isize,
             self.camera.eye.z as isize,
         );
-        self.core.update(&camera_coord, &self.device, 500);
+        self.core.update(&camera_coord, &self.device, &self.queue, 500);
+        self.core.update_visibility(&self.camera);
         
         // Update camera buffer
         let view_proj = self.camera.view_proj();
@@ -445,7 +458,7 @@ impl App {
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                     view: &self.depth_view,
                     depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
+                        load: wgpu::LoadOp::Clear(0.0), // reversed depth (Camera::view_proj)
                         store: wgpu::StoreOp::Store,
                     }),
                     stencil_ops: None,
@@ -466,6 +479,7 @@ impl App {
             }
             
             render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            render_pass.set_bind_group(1, &self.shadows.bind_group, &[]);
             
             // Render visible chunks
             for x_chunks in &self.core.active {
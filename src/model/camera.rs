@@ -21,7 +21,7 @@ impl Camera {
             fov_y: 60f32.to_radians(),
             aspect: width as f32 / height as f32,
             z_near: 0.1,
-            z_far: 1000.0,
+            z_far: 20000.0, // far enough for the horizon rings (horizon.rs), see view_proj
         }
     }
 
@@ -35,9 +35,14 @@ impl Camera {
 
     pub fn set_aspect(&mut self, width: u32, height: u32) { self.aspect = width as f32 / height as f32; }
 
+    /// View projection with reversed depth: z_near maps to 1, z_far to 0
+    ///
+    /// With a float depth buffer this keeps the precision nearly constant over the
+    /// whole range, so the distant horizon rings don't z-fight with z_far / z_near = 200000.
+    /// Depth is cleared to 0 and compared with Greater (see render.rs).
     pub fn view_proj(&self) -> Mat4 {
         let view = Mat4::look_at_rh(self.eye, self.target(), self.up);
-        let proj = Mat4::perspective_rh(self.fov_y, self.aspect, self.z_near, self.z_far);
+        let proj = Mat4::perspective_rh(self.fov_y, self.aspect, self.z_far, self.z_near);
         proj * view
     }
 
@@ -116,17 +121,24 @@ impl Camera {
         ]
     }
 
+    /// Frustum planes of this camera (see `frustum_planes`)
+    pub fn frustum(&self) -> [[f32; 4]; 6] {
+        Self::frustum_planes(self.eye, self.yaw, self.pitch, self.aspect, self.fov_y, self.z_near, self.z_far)
+    }
+
     // Test if AABB (chunk bounding box) intersects frustum
     pub fn is_chunk_in_frustum(eye: Vec3, yaw: f32, pitch: f32, aspect: f32, fov_y: f32, z_near: f32, z_far: f32, cx: i32, cy: i32, cz: i32, chunk_size: f32) -> bool {
         let planes = Self::frustum_planes(eye, yaw, pitch, aspect, fov_y, z_near, z_far);
-        let min_x = cx as f32 * chunk_size;
-        let min_y = cy as f32 * chunk_size;
-        let min_z = cz as f32 * chunk_size;
-        let max_x = min_x + chunk_size;
-        let max_y = min_y + chunk_size;
-        let max_z = min_z + chunk_size;
+        let min = Vec3::new(cx as f32, cy as f32, cz as f32) * chunk_size;
+        Self::is_box_in_frustum(&planes, min, min + Vec3::splat(chunk_size))
+    }
+
+    // Test if AABB intersects frustum given by its planes
+    pub fn is_box_in_frustum(planes: &[[f32; 4]; 6], min: Vec3, max: Vec3) -> bool {
+        let (min_x, min_y, min_z) = (min.x, min.y, min.z);
+        let (max_x, max_y, max_z) = (max.x, max.y, max.z);
 
-        for plane in &planes {
+        for plane in planes {
             let [a, b, c, d] = plane;
             // Test all 8 corners, if all are outside this plane, cull the chunk
             let mut all_outside = true;
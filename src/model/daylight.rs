@@ -0,0 +1,172 @@
+use glam::Vec3;
+
+use super::dimension::DimensionSettings;
+
+// ============================================================================
+// DAYLIGHT: world clock, sun and moon, and the colors of the sky
+// ============================================================================
+//
+// The world clock runs through a day of `day_length` seconds, the same in all
+// dimensions. Time of day 0.0 is midnight, 0.25 sunrise, 0.5 noon and 0.75
+// sunset:
+//   → the sun turns around the world on a circle through east, zenith and
+//     west (tilted a little so noon shadows aren't straight down), the moon
+//     is opposite it
+//   → the sun lights the world while it is up, the much weaker moon at night;
+//     the lighting uniform gets whichever of the two is up, and both fade out
+//     towards the horizon so the switch (and the shadows) don't jump
+//   → sky and fog colors go from night through dawn to the dimension's day
+//     sky and back through dusk; ambient light dims at night
+//
+// Dimensions without a day cycle (under a rock roof) keep their fixed
+// lighting and sky around the clock.
+// ============================================================================
+
+/// Seconds per day unless changed in the time window
+pub const DEFAULT_DAY_LENGTH: f32 = 600.0;
+
+/// Sky colors away from full day
+const NIGHT_SKY: [f32; 3] = [0.02, 0.03, 0.08];
+const DAWN_SKY: [f32; 3] = [0.92, 0.6, 0.55];
+const DUSK_SKY: [f32; 3] = [0.95, 0.5, 0.3];
+
+/// Moonlight relative to sunlight
+const MOON_INTENSITY: f32 = 0.15;
+
+/// Ambient light at night relative to the day
+const NIGHT_AMBIENT: f32 = 0.35;
+
+pub struct WorldClock {
+    /// Time of day, 0.0..1.0 from midnight
+    pub time: f32,
+    /// Real seconds per day
+    pub day_length: f32,
+    pub paused: bool,
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        // start in the morning
+        Self { time: 0.3, day_length: DEFAULT_DAY_LENGTH, paused: false }
+    }
+}
+
+/// Lighting and sky at a time of day in a dimension
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Daylight {
+    pub sun_dir: Vec3,
+    pub moon_dir: Vec3,
+    /// Direction of the light that shines (sun by day, moon by night)
+    pub light_dir: Vec3,
+    /// 0 at night, 1 by day
+    pub day: f32,
+    pub sun_intensity: f32,
+    pub ambient: f32,
+    pub sky_color: [f32; 3],
+    pub fog_color: [f32; 3],
+}
+
+impl WorldClock {
+    pub fn advance(&mut self, dt: f32) {
+        if !self.paused && self.day_length > 0.0 {
+            self.time = (self.time + dt / self.day_length).rem_euclid(1.0);
+        }
+    }
+
+    /// Time of day as "hh:mm"
+    pub fn clock_text(&self) -> String {
+        let minutes = (self.time * 24.0 * 60.0).round() as u32;
+        format!("{:02}:{:02}", minutes / 60 % 24, minutes % 60)
+    }
+
+    pub fn sun_dir(&self) -> Vec3 {
+        let angle = (self.time - 0.25) * std::f32::consts::TAU;
+        Vec3::new(angle.cos(), angle.sin(), 0.3).normalize()
+    }
+
+    pub fn daylight(&self, dimension: &DimensionSettings) -> Daylight {
+        let sun_dir = self.sun_dir();
+        let moon_dir = -sun_dir;
+        if !dimension.day_cycle {
+            return Daylight {
+                sun_dir,
+                moon_dir,
+                light_dir: Vec3::new(0.5, 1.0, 0.5).normalize(),
+                day: 1.0,
+                sun_intensity: dimension.sun_intensity,
+                ambient: dimension.ambient,
+                sky_color: dimension.sky_color,
+                fog_color: dimension.sky_color,
+            };
+        }
+
+        // 0 at night, 1 by day, blending while the sun is near the horizon
+        let day = smoothstep(-0.1, 0.2, sun_dir.y);
+        // strongest with the sun at the horizon
+        let twilight = 1.0 - smoothstep(0.0, 0.3, sun_dir.y.abs());
+        let twilight_sky = if self.time < 0.5 { DAWN_SKY } else { DUSK_SKY };
+
+        let sky_color = mix(mix(NIGHT_SKY, dimension.sky_color, day), twilight_sky, twilight * 0.6);
+        // the horizon is hazier than the sky above
+        let fog_color = mix(sky_color, [1.0, 1.0, 1.0], 0.25 * day);
+
+        // only one of the two is above the horizon
+        let light = smoothstep(0.0, 0.2, sun_dir.y) + MOON_INTENSITY * smoothstep(0.0, 0.2, moon_dir.y);
+
+        Daylight {
+            sun_dir,
+            moon_dir,
+            light_dir: if sun_dir.y >= 0.0 { sun_dir } else { moon_dir },
+            day,
+            sun_intensity: dimension.sun_intensity * light,
+            ambient: dimension.ambient * (NIGHT_AMBIENT + (1.0 - NIGHT_AMBIENT) * day),
+            sky_color,
+            fog_color,
+        }
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_night_cycle() {
+        let overworld = DimensionSettings::overworld();
+        let at = |time: f32| WorldClock { time, ..WorldClock::default() }.daylight(&overworld);
+
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        let noon = at(0.5);
+        assert!(noon.sun_dir.y > 0.9 && noon.light_dir == noon.sun_dir);
+        assert!(close(noon.sun_intensity, overworld.sun_intensity) && close(noon.ambient, overworld.ambient));
+        assert!((0..3).all(|c| close(noon.sky_color[c], overworld.sky_color[c])));
+
+        let midnight = at(0.0);
+        assert!(midnight.moon_dir.y > 0.9 && midnight.light_dir == midnight.moon_dir);
+        assert!(midnight.sun_intensity < noon.sun_intensity * 0.2 && midnight.ambient < noon.ambient * 0.5);
+        assert_eq!(midnight.sky_color, NIGHT_SKY);
+        assert!(at(0.25).sky_color[0] > noon.sky_color[0], "dawn is red");
+        assert!(at(0.25).sun_intensity < 1e-4 && at(0.75).sun_intensity < 1e-4, "no light at the switch");
+
+        // colors change smoothly around the clock, including across midnight
+        for i in 0..1000 {
+            let (a, b) = (at(i as f32 / 1000.0), at((i + 1) as f32 / 1000.0));
+            assert!((0..3).all(|c| (a.sky_color[c] - b.sky_color[c]).abs() < 0.05), "jump at {i}");
+        }
+
+        // the clock wraps, and the underworld has no day
+        let mut clock = WorldClock { time: 0.9, day_length: 100.0, paused: false };
+        clock.advance(20.0);
+        assert!(close(clock.time, 0.1) && clock.clock_text() == "02:24");
+        assert_eq!(clock.daylight(&DimensionSettings::underworld()).sky_color, DimensionSettings::underworld().sky_color);
+    }
+}
@@ -0,0 +1,264 @@
+use glam::Vec3;
+
+use crate::model::world::terrain::{TerrainConfig, SEA_LEVEL};
+use crate::model::world::{Block, VoxelDensityGenerator, WorldGenerator};
+use crate::utils::WorldCoord;
+
+use super::scene::Scene;
+
+// ============================================================================
+// DIMENSIONS: separate worlds linked by portals
+// ============================================================================
+//
+// Every dimension is its own Scene (generator, block edits, far terrain) plus
+// the lighting and sky it is shown with:
+//   → only the active dimension is updated and rendered; on leaving, its chunks
+//     and GPU meshes are evicted and only its block edits are kept, the one
+//     mesh arena moves on to the dimension entered
+//   → the block edits of every dimension are saved to storage (storage.rs) as
+//     they change and read back at startup
+//   → dimension 0 is home: a portal block there leads to the dimension with that
+//     portal block, any portal elsewhere leads back home
+//   → x and z are scaled by the ratio of the dimension scales (one underworld
+//     block is 8 home blocks), y follows the terrain at the arrival column
+//   → the arrival spot gets a small platform with a portal back next to it,
+//     queued as block edits since its chunks are not loaded yet
+//
+// Travel happens when the player steps into a portal, not while standing in one,
+// so arriving next to the portal back does not bounce the player.
+// ============================================================================
+
+/// Lighting, sky and portal link of a dimension
+#[derive(Clone, Debug, PartialEq)]
+pub struct DimensionSettings {
+    pub name: &'static str,
+    /// Home dimension blocks per block of this dimension along x and z
+    pub scale: f32,
+    /// Portal block leading here from the home dimension (None for home itself)
+    pub portal: Option<Block>,
+    /// Sky behind the terrain (at noon with a day cycle)
+    pub sky_color: [f32; 3],
+    pub sun_intensity: f32,
+    pub ambient: f32,
+    /// Show far terrain beyond the loaded chunks (pointless under a roof)
+    pub horizon: bool,
+    /// Lighting and sky follow the time of day (see daylight.rs)
+    pub day_cycle: bool,
+    /// Share of the light left where no sky light reaches (see light.rs)
+    pub cave_light: f32,
+}
+
+impl DimensionSettings {
+    pub fn overworld() -> Self {
+        Self {
+            name: "overworld",
+            scale: 1.0,
+            portal: None,
+            sky_color: [0.5, 0.8, 1.0],
+            sun_intensity: 0.3,
+            ambient: 0.7,
+            horizon: true,
+            day_cycle: true,
+            cave_light: 0.05,
+        }
+    }
+
+    /// Basalt caverns, see world/underworld.rs
+    pub fn underworld() -> Self {
+        Self {
+            name: "underworld",
+            scale: 8.0,
+            portal: Some(Block::Portal),
+            sky_color: [0.2, 0.05, 0.04],
+            sun_intensity: 0.05,
+            ambient: 0.65,
+            horizon: false,
+            day_cycle: false,
+            cave_light: 0.4,
+        }
+    }
+
+    /// Floating islands over the void (the sky_world terrain preset)
+    pub fn sky_world() -> Self {
+        Self {
+            name: "sky world",
+            scale: 1.0,
+            portal: Some(Block::SkyPortal),
+            sky_color: [0.62, 0.85, 1.0],
+            sun_intensity: 0.45,
+            ambient: 0.65,
+            horizon: true,
+            day_cycle: true,
+            cave_light: 0.05,
+        }
+    }
+}
+
+/// The inactive dimensions and the index of the one being played
+///
+/// The active dimension's scene lives outside (shared with rendering and UI);
+/// travelling swaps it with the parked scene of the target.
+pub struct Dimensions {
+    /// Scenes by dimension index, None at the active one
+    parked: Vec<Option<Scene>>,
+    active: usize,
+    /// Whether the player stood in a portal at the last update
+    in_portal: bool,
+}
+
+impl Dimensions {
+    /// Dimension 0 (home) is active, `others` are the remaining dimensions in order
+    pub fn new(others: Vec<Scene>) -> Self {
+        let parked = std::iter::once(None).chain(others.into_iter().map(Some)).collect();
+        Self { parked, active: 0, in_portal: false }
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Dimension a portal block leads to from the active one
+    fn target(&self, portal: Block) -> Option<usize> {
+        if self.active != 0 {
+            return Some(0);
+        }
+        self.parked
+            .iter()
+            .position(|scene| scene.as_ref().is_some_and(|scene| scene.dimension.portal == Some(portal)))
+    }
+
+    /// Send the player through a portal they just stepped into
+    ///
+    /// `scene` is the active scene, `player` the feet position. Returns the
+    /// arrival position if the player changed dimension.
+    pub fn update(&mut self, scene: &mut Scene, player: Vec3) -> Option<Vec3> {
+        let portal = [0.0, 1.0]
+            .iter()
+            .filter_map(|dy| scene.get_block(&WorldCoord(player.x.floor() as isize, (player.y + dy).floor() as isize, player.z.floor() as isize)))
+            .find(|block| block.is_portal());
+        let entering = portal.is_some() && !self.in_portal;
+        self.in_portal = portal.is_some();
+
+        let target = self.target(portal.filter(|_| entering)?)?;
+        Some(self.travel(scene, target, player))
+    }
+
+    /// Start over in a new world: home gets noise terrain from `config` (keeping
+    /// its biome definitions), the other dimensions take its seed, all block
+    /// edits are dropped and home becomes the active dimension
+    pub fn new_world(&mut self, scene: &mut Scene, config: TerrainConfig) {
+        self.go_home(scene);
+        let biomes = scene.generator().terrain().biomes.clone();
+        scene.set_generator(WorldGenerator::Noise(VoxelDensityGenerator::with_biomes(config, biomes)));
+        for other in self.parked.iter_mut().flatten() {
+            let other_config = TerrainConfig { seed: config.seed, ..*other.terrain_config() };
+            other.set_terrain_config(other_config);
+        }
+    }
+
+    /// Read the saved block edits of every dimension (at startup)
+    pub fn load_edits(&mut self, scene: &mut Scene) {
+        scene.load_edits();
+        self.parked.iter_mut().flatten().for_each(Scene::load_edits);
+    }
+
+    /// Save the block edits of every dimension that changed since the last save
+    pub fn save_edits(&mut self, scene: &mut Scene) -> Result<(), String> {
+        scene.save_edits()?;
+        self.parked.iter_mut().flatten().try_for_each(Scene::save_edits)
+    }
+
+    /// Make home the active dimension (e.g. to respawn there), no arrival platform is built
+    pub fn go_home(&mut self, scene: &mut Scene) {
+        if self.active != 0 {
+            let mut home = self.parked[0].take().expect("home is parked while away");
+            scene.leave(&mut home);
+            std::mem::swap(scene, &mut home);
+            self.parked[self.active] = Some(home);
+            self.active = 0;
+        }
+        self.in_portal = false;
+    }
+
+    /// Move the player to another dimension, returns the arrival feet position
+    pub fn travel(&mut self, scene: &mut Scene, target: usize, player: Vec3) -> Vec3 {
+        let mut next = self.parked[target].take().expect("travel target must be an inactive dimension");
+
+        let factor = scene.dimension.scale / next.dimension.scale;
+        let (x, z) = ((player.x * factor).floor() as isize, (player.z * factor).floor() as isize);
+        let y = arrival_height(&next, x, z);
+
+        // the portal back home is the one of the dimension we come from
+        let back = if target == 0 { scene.dimension.portal } else { next.dimension.portal };
+        build_arrival(&mut next, WorldCoord(x, y, z), back.unwrap_or(Block::Portal));
+
+        scene.leave(&mut next);
+        std::mem::swap(scene, &mut next);
+        self.parked[self.active] = Some(next);
+        self.active = target;
+        self.in_portal = true;
+
+        Vec3::new(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5)
+    }
+}
+
+/// First free world y of the arrival column, above any water or lava
+///
+/// Columns without ground inside the world (the void of the sky world) arrive
+/// halfway up.
+fn arrival_height(scene: &Scene, x: isize, z: isize) -> isize {
+    let bounds = scene.bounds();
+    let height = scene.generator().coarse_column(x as f32, z as f32).height;
+    if height <= bounds.min_y {
+        return (bounds.min_y + bounds.max_y) / 2;
+    }
+    height.max(SEA_LEVEL + 1).clamp(bounds.min_y + 1, bounds.max_y - 4)
+}
+
+/// Platform of 3x3 blackstone with room to stand and a two block portal next to it
+fn build_arrival(scene: &mut Scene, feet: WorldCoord, portal: Block) {
+    let WorldCoord(x, y, z) = feet;
+    for dz in -1..=1 {
+        for dx in -1..=1 {
+            scene.queue_block(&WorldCoord(x + dx, y - 1, z + dz), Block::BlackStone);
+            for dy in 0..3 {
+                scene.queue_block(&WorldCoord(x + dx, y + dy, z + dz), Block::Empty);
+            }
+        }
+    }
+    scene.queue_block(&WorldCoord(x + 2, y - 1, z), Block::BlackStone);
+    scene.queue_block(&WorldCoord(x + 2, y, z), portal);
+    scene.queue_block(&WorldCoord(x + 2, y + 1, z), portal);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::world::underworld::UnderworldGenerator;
+    use crate::model::world::Chunk;
+    use crate::utils::{BlockCoord, ChunkCoord};
+
+    #[test]
+    fn test_portal_at_negative_coordinates() {
+        let home = WorldGenerator::Noise(VoxelDensityGenerator::with_config(TerrainConfig::default()));
+        let mut scene = Scene::parked([4, 4, 4], home, DimensionSettings::overworld());
+        let underworld = WorldGenerator::Underworld(UnderworldGenerator::new(0));
+        let mut dimensions = Dimensions::new(vec![Scene::parked([4, 4, 4], underworld, DimensionSettings::underworld())]);
+
+        // a portal block at (-3, -10, -3), in chunk (-1, -1, -1) at block (13, 6, 13)
+        let mut chunk = Chunk::new_empty();
+        chunk.set_block(&BlockCoord(13, 6, 13), Block::Portal, true);
+        scene.insert_chunk(&ChunkCoord(-1, -1, -1), chunk);
+
+        // next to it nothing happens, stepping into it leads to the underworld
+        assert_eq!(dimensions.update(&mut scene, Vec3::new(-3.5, -9.5, -2.5)), None);
+        assert!(dimensions.update(&mut scene, Vec3::new(-2.5, -9.5, -2.5)).is_some());
+        assert_eq!(dimensions.active(), 1);
+        assert_eq!(scene.dimension, DimensionSettings::underworld());
+
+        // respawning takes the player back home
+        dimensions.go_home(&mut scene);
+        assert_eq!(dimensions.active(), 0);
+        assert_eq!(scene.dimension, DimensionSettings::overworld());
+    }
+}
@@ -0,0 +1,118 @@
+use super::daylight::Daylight;
+
+// ============================================================================
+// FOG: distance fog that hides where the terrain ends
+// ============================================================================
+//
+// Chunk fragments are blended towards the fog color by their distance from
+// the camera (chunk.wgsl), so terrain fades into the sky instead of ending at
+// a hard edge:
+//   → the fog ends at the scene's view distance: the edge of the loaded chunk
+//     window, or of the far terrain where a dimension has one
+//   → linear fog starts at LINEAR_START of that distance, exp and exp² fog
+//     start at the camera and are tuned to leave END_VISIBILITY at the end
+//   → the fog color is the horizon color of the sky (Daylight::fog_color), so
+//     it follows the time of day
+//   → with the camera in water a short dense blue fog replaces it
+// ============================================================================
+
+/// Fraction of the view distance where linear fog starts
+const LINEAR_START: f32 = 0.6;
+
+/// Part of a surface still visible at the view distance in the exp modes (one
+/// step of an 8 bit color)
+const END_VISIBILITY: f32 = 1.0 / 255.0;
+
+/// Blocks of water the camera sees through
+const UNDERWATER_DISTANCE: f32 = 24.0;
+const UNDERWATER_COLOR: [f32; 3] = [0.05, 0.2, 0.45];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FogMode {
+    #[default]
+    Linear,
+    Exp,
+    Exp2,
+}
+
+impl FogMode {
+    pub const ALL: [FogMode; 3] = [FogMode::Linear, FogMode::Exp, FogMode::Exp2];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FogMode::Linear => "Linear",
+            FogMode::Exp => "Exp",
+            FogMode::Exp2 => "Exp²",
+        }
+    }
+}
+
+/// Fog of one frame, written to the lighting uniform
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fog {
+    pub mode: FogMode,
+    /// Distance where linear fog starts
+    pub start: f32,
+    /// Distance where the fog hides everything
+    pub end: f32,
+    /// Density of the exp modes
+    pub density: f32,
+    pub color: [f32; 3],
+}
+
+impl Fog {
+    /// Fog ending at `distance`
+    pub fn new(mode: FogMode, distance: f32, color: [f32; 3]) -> Self {
+        let end = distance.max(1.0);
+        // exp: e^(-d * density), exp²: e^(-(d * density)²), both END_VISIBILITY at the end
+        let density = match mode {
+            FogMode::Exp2 => (-END_VISIBILITY.ln()).sqrt() / end,
+            _ => -END_VISIBILITY.ln() / end,
+        };
+        Self { mode, start: end * LINEAR_START, end, density, color }
+    }
+
+    /// Fog of the sky above the view distance, or of the water around the camera
+    pub fn for_view(mode: FogMode, distance: f32, daylight: &Daylight, underwater: bool) -> Self {
+        if !underwater {
+            return Self::new(mode, distance, daylight.fog_color);
+        }
+        // darker at night like everything else
+        let brightness = 0.3 + 0.7 * daylight.day;
+        Self::new(FogMode::Exp2, UNDERWATER_DISTANCE, UNDERWATER_COLOR.map(|c| c * brightness))
+    }
+
+    /// Fraction of the fog color in a fragment at a distance (as in chunk.wgsl)
+    pub fn amount(&self, distance: f32) -> f32 {
+        let amount = match self.mode {
+            FogMode::Linear => (distance - self.start) / (self.end - self.start),
+            FogMode::Exp => 1.0 - (-distance * self.density).exp(),
+            FogMode::Exp2 => 1.0 - (-(distance * self.density).powi(2)).exp(),
+        };
+        amount.clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DimensionSettings, WorldClock};
+
+    #[test]
+    fn test_fog_hides_the_view_distance() {
+        let daylight = WorldClock::default().daylight(&DimensionSettings::overworld());
+        for mode in FogMode::ALL {
+            let fog = Fog::for_view(mode, 1000.0, &daylight, false);
+            assert_eq!(fog.amount(0.0), 0.0, "{mode:?}");
+            assert!(fog.amount(1000.0) > 0.99, "{mode:?} leaves the edge visible");
+            // more fog further away
+            assert!((1..100).all(|i| fog.amount(i as f32 * 10.0) >= fog.amount((i - 1) as f32 * 10.0)));
+        }
+        assert_eq!(Fog::for_view(FogMode::Linear, 1000.0, &daylight, false).amount(500.0), 0.0);
+
+        // water is much denser and blue, whatever the mode
+        let water = Fog::for_view(FogMode::Linear, 1000.0, &daylight, true);
+        assert!(water.amount(UNDERWATER_DISTANCE) > 0.99);
+        assert!(water.color[2] > water.color[0]);
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::model::world::terrain::SEA_LEVEL;
+use crate::model::world::{Block, WorldGenerator};
+use crate::utils::{Mesh, MeshBuffer, Vertex};
+
+// ============================================================================
+// HORIZON: far terrain beyond the loaded chunk window
+// ============================================================================
+//
+// Square rings of low-poly terrain tiles around the player, sampled directly
+// from the generator's height and biome functions (no chunks):
+//   → ring 0 starts at the edge of the loaded chunk window, every further ring
+//     starts where the previous one ends and samples 4x coarser
+//   → a tile is TILE_SAMPLES x TILE_SAMPLES height samples, one colored vertex
+//     per sample (surface block with biome tint, water at sea level)
+//   → tiles are built nearest first, a few per update, and dropped once they
+//     leave their ring
+//   → cells touching a column whose top lies below the world floor are left
+//     out (the void under the islands of the sky world)
+//
+// Rings overlap the loaded chunks and each other at their borders. Every ring
+// is lowered a bit more than the one inside it, so the more detailed terrain
+// always covers the coarser one.
+// ============================================================================
+
+/// Blocks between two height samples of each ring, innermost first
+const RING_SPACINGS: [i32; 3] = [4, 16, 64];
+
+/// Width of each ring in its own tiles (256 blocks, 2 km, 10 km)
+const RING_WIDTHS: [i32; 3] = [4, 8, 10];
+
+/// Height samples along a tile edge (the mesh has TILE_SAMPLES + 1 vertices per row)
+const TILE_SAMPLES: i32 = 16;
+
+/// One tile of a ring, x and z count tiles of the ring's size from the world origin
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TileKey {
+    pub ring: usize,
+    pub x: i32,
+    pub z: i32,
+}
+
+impl TileKey {
+    /// Edge length in blocks
+    pub fn size(&self) -> i32 {
+        RING_SPACINGS[self.ring] * TILE_SAMPLES
+    }
+
+    /// World (x, z) of the tile corner with the lowest coordinates
+    pub fn origin(&self) -> (i32, i32) {
+        (self.x * self.size(), self.z * self.size())
+    }
+}
+
+pub struct Horizon {
+    /// Half width in blocks of the square around the player covered by loaded chunks
+    inner_radius: i32,
+    /// Tiles to show for the last player position, nearest first
+    wanted: Vec<TileKey>,
+    /// Player column the wanted tiles were computed for
+    center: Option<(i32, i32)>,
+    tiles: HashMap<TileKey, MeshBuffer>,
+}
+
+impl Horizon {
+    pub fn new(inner_radius: i32) -> Self {
+        Self { inner_radius, wanted: Vec::new(), center: None, tiles: HashMap::new() }
+    }
+
+    /// Drop all tiles so they are rebuilt (e.g. after the terrain changed)
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Half width in blocks of the square covered by the outermost ring
+    pub fn outer_radius(&self) -> i32 {
+        (0..RING_SPACINGS.len()).fold(self.inner_radius, |inner, ring| ring_outer(ring, inner))
+    }
+
+    /// Meshes of all built tiles
+    pub fn meshes(&self) -> impl Iterator<Item = &MeshBuffer> {
+        self.tiles.values()
+    }
+
+    /// Follow the player: forget tiles outside the rings and build up to `max_tiles` new ones
+    pub fn update(&mut self, player: (i32, i32), generator: &WorldGenerator, device: &wgpu::Device, max_tiles: usize) {
+        // the rings only change when the player crosses a ring 0 sample
+        let spacing = RING_SPACINGS[0];
+        let center = (player.0.div_euclid(spacing) * spacing, player.1.div_euclid(spacing) * spacing);
+        if self.center != Some(center) {
+            self.center = Some(center);
+            self.wanted = wanted_tiles(center, self.inner_radius);
+            let wanted: HashSet<_> = self.wanted.iter().collect();
+            self.tiles.retain(|key, _| wanted.contains(key));
+        }
+
+        let missing: Vec<TileKey> = self.wanted.iter().filter(|key| !self.tiles.contains_key(key)).take(max_tiles).copied().collect();
+        for key in missing {
+            self.tiles.insert(key, tile_mesh(generator, key).upload(device));
+        }
+    }
+}
+
+/// Half width of the square covered by a ring starting at `inner`
+fn ring_outer(ring: usize, inner: i32) -> i32 {
+    let size = RING_SPACINGS[ring] * TILE_SAMPLES;
+    (inner + size - 1).div_euclid(size) * size + RING_WIDTHS[ring] * size
+}
+
+/// All tiles of the rings around a player column, nearest first
+///
+/// A ring covers the tiles overlapping its outer square that are not entirely
+/// inside its inner square (the loaded chunks or the finer ring).
+pub fn wanted_tiles(player: (i32, i32), inner_radius: i32) -> Vec<TileKey> {
+    let mut tiles = Vec::new();
+    let mut inner = inner_radius;
+    for (ring, spacing) in RING_SPACINGS.iter().enumerate() {
+        let size = spacing * TILE_SAMPLES;
+        let outer = ring_outer(ring, inner);
+
+        let (first_x, last_x) = ((player.0 - outer).div_euclid(size), (player.0 + outer - 1).div_euclid(size));
+        let (first_z, last_z) = ((player.1 - outer).div_euclid(size), (player.1 + outer - 1).div_euclid(size));
+        for z in first_z..=last_z {
+            for x in first_x..=last_x {
+                let key = TileKey { ring, x, z };
+                let (ox, oz) = key.origin();
+                let inside = |o: i32, p: i32| o >= p - inner && o + size <= p + inner;
+                if !(inside(ox, player.0) && inside(oz, player.1)) {
+                    tiles.push(key);
+                }
+            }
+        }
+        inner = outer;
+    }
+
+    let distance = |key: &TileKey| {
+        let (ox, oz) = key.origin();
+        let half = key.size() / 2;
+        let (dx, dz) = ((ox + half - player.0) as i64, (oz + half - player.1) as i64);
+        dx * dx + dz * dz
+    };
+    tiles.sort_by_key(distance);
+    tiles
+}
+
+/// Low-poly terrain mesh of a tile in world coordinates
+pub fn tile_mesh(generator: &WorldGenerator, key: TileKey) -> Mesh {
+    let spacing = RING_SPACINGS[key.ring];
+    let (ox, oz) = key.origin();
+    // coarser rings lie lower, so finer terrain (and the chunks) covers them where they overlap
+    let sink = spacing as f32 * 0.5 + 2.0;
+    let min_y = generator.bounds().min_y;
+
+    // one sample more on every side for the normals at the tile border
+    let n = TILE_SAMPLES as usize + 3;
+    let mut heights = vec![0.0; n * n];
+    let mut colors = vec![[0.0; 4]; n * n];
+    let mut void = vec![false; n * n];
+    for iz in 0..n {
+        for ix in 0..n {
+            let wx = ox + (ix as i32 - 1) * spacing;
+            let wz = oz + (iz as i32 - 1) * spacing;
+            let column = generator.coarse_column(wx as f32, wz as f32);
+
+            // sea water fills everything up to SEA_LEVEL
+            let (top, color) = if column.height <= SEA_LEVEL {
+                (SEA_LEVEL + 1, Block::Water.color(2))
+            } else if column.surface_block.is_tinted(2) {
+                (column.height, column.tint.apply(column.surface_block.color(2)))
+            } else {
+                (column.height, column.surface_block.color(2))
+            };
+            heights[ix + iz * n] = top as f32 - sink;
+            colors[ix + iz * n] = color;
+            void[ix + iz * n] = top <= min_y;
+        }
+    }
+
+    let mut vertices = Vec::new();
+    for iz in 1..n - 1 {
+        for ix in 1..n - 1 {
+            let h = |x: usize, z: usize| heights[x + z * n];
+            let normal = glam::Vec3::new(
+                h(ix - 1, iz) - h(ix + 1, iz),
+                2.0 * spacing as f32,
+                h(ix, iz - 1) - h(ix, iz + 1),
+            ).normalize();
+            vertices.push(Vertex {
+                pos: [(ox + (ix as i32 - 1) * spacing) as f32, h(ix, iz), (oz + (iz as i32 - 1) * spacing) as f32],
+                normal: normal.to_array(),
+                color: colors[ix + iz * n],
+                uv: [0.0, 0.0],
+            });
+        }
+    }
+
+    // two counter-clockwise (seen from above) triangles per cell
+    let row = TILE_SAMPLES as u32 + 1;
+    let mut indices = Vec::new();
+    for z in 0..TILE_SAMPLES as u32 {
+        for x in 0..TILE_SAMPLES as u32 {
+            let corners = [(x, z), (x + 1, z), (x, z + 1), (x + 1, z + 1)];
+            if corners.iter().any(|&(cx, cz)| void[cx as usize + 1 + (cz as usize + 1) * n]) {
+                continue;
+            }
+            let a = x + z * row;
+            let (b, c, d) = (a + row, a + 1, a + row + 1);
+            indices.extend_from_slice(&[a, b, c, c, b, d]);
+        }
+    }
+
+    Mesh { vertices, indices }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::world::terrain::TerrainConfig;
+    use crate::model::world::VoxelDensityGenerator;
+
+    #[test]
+    fn test_horizon_rings_and_tile_borders() {
+        let player = (100, -40);
+        let tiles = wanted_tiles(player, 512);
+
+        // no tile lies inside the loaded window, the last ring reaches kilometers
+        for key in &tiles {
+            let (ox, oz) = key.origin();
+            let inside = ox >= player.0 - 512 && ox + key.size() <= player.0 + 512 &&
+                         oz >= player.1 - 512 && oz + key.size() <= player.1 + 512;
+            assert!(!inside, "{key:?} is covered by chunks");
+        }
+        let far = tiles.iter().map(|key| (key.origin().0 - player.0).abs()).max().unwrap();
+        assert!(far > 8000, "horizon ends at {far} blocks");
+        assert!(tiles.iter().any(|key| key.ring == 0) && tiles.iter().any(|key| key.ring == 2));
+
+        // neighboring tiles share their border vertices (no cracks)
+        let generator = WorldGenerator::Noise(VoxelDensityGenerator::with_config(TerrainConfig::default()));
+        let left = tile_mesh(&generator, TileKey { ring: 0, x: 3, z: 2 });
+        let right = tile_mesh(&generator, TileKey { ring: 0, x: 4, z: 2 });
+        let row = TILE_SAMPLES as usize + 1;
+        assert_eq!(left.vertices.len(), row * row);
+        for z in 0..row {
+            assert_eq!(left.vertices[row - 1 + z * row].pos, right.vertices[z * row].pos);
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::VecDeque;
+
+use crate::model::visibility::FACE_OFFSETS;
+use crate::model::world::{Block, CHUNK_SIZE};
+use crate::utils::{ChunkCoord, WorldCoord};
+
+// ============================================================================
+// LIGHT: sky light and block light flooding through the blocks
+// ============================================================================
+//
+// Every block cell of a lit chunk stores two light levels 0..=MAX_LIGHT:
+//   → sky light: MAX_LIGHT under the open sky, falling straight down through
+//     air without getting weaker, and losing a level per block sideways,
+//     upwards and through see-through blocks (water, ice, portals)
+//   → block light: the level a glowing block emits (Block::light_emission),
+//     losing a level per block in every direction, tinted with the
+//     Block::light_color of the emitter it came from (where the light of two
+//     emitters meets, the brighter one wins)
+//   → opaque blocks hold no light and stop it, only emitters keep their own
+//
+// Changes flood out with the usual two breadth-first queues (LightQueues):
+// the remove queue darkens every cell that took its light from a cell that
+// got darker, and hands the cells at its border that are lit from elsewhere to
+// the add queue, which spreads light out until no neighbor gets any brighter.
+// Both cross chunk borders freely.
+//
+// Only chunks near the player are lit (the scene lights them when they enter
+// LOD 0), the rest stop light like unloaded chunks. A chunk lit before the
+// chunk above it starts out dark, the sky light falls in once the chunk above
+// is lit. Air chunks count as open sky until they are lit themselves, and take
+// it back where they turn out to be shaded.
+// ============================================================================
+
+/// Brightest light level
+pub const MAX_LIGHT: u8 = 15;
+
+/// Index of -Y in FACE_OFFSETS
+const DOWN: usize = 3;
+
+/// Sky light (bits 0..4), block light (bits 4..8) and the id of the block the
+/// block light comes from (bits 8..16) of a block cell
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Light(pub u16);
+
+impl Light {
+    pub const DARK: Light = Light(0);
+    /// Open sky, no block light
+    pub const SKY: Light = Light(MAX_LIGHT as u16);
+
+    /// Block light without a source (white)
+    pub fn new(sky: u8, block: u8) -> Self {
+        Light(sky as u16 | (block as u16) << 4)
+    }
+
+    /// Block light of the emitter `source`
+    pub fn emitted(sky: u8, block: u8, source: Block) -> Self {
+        Light(Light::new(sky, block).0 | (source as u16) << 8)
+    }
+
+    pub fn sky(self) -> u8 {
+        (self.0 & 15) as u8
+    }
+
+    pub fn block(self) -> u8 {
+        (self.0 >> 4 & 15) as u8
+    }
+
+    /// Emitter the block light comes from
+    pub fn source(self) -> Block {
+        Block::from_u8((self.0 >> 8) as u8)
+    }
+
+    /// The light of a cell holding `block` with nothing shining in: its own emission
+    fn own(sky: u8, block: Block) -> Self {
+        match block.light_emission() {
+            0 => Light::new(sky, 0),
+            emission => Light::emitted(sky, emission, block),
+        }
+    }
+
+    fn get(self, channel: Channel) -> u8 {
+        match channel {
+            Channel::Sky => self.sky(),
+            Channel::Block => self.block(),
+        }
+    }
+
+    /// `level` on one channel; block light takes the source of `from`
+    fn with(self, channel: Channel, level: u8, from: Light) -> Self {
+        match channel {
+            Channel::Sky => Light::emitted(level, self.block(), self.source()),
+            Channel::Block => Light::emitted(self.sky(), level, from.source()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Channel {
+    Sky,
+    Block,
+}
+
+const CHANNELS: [Channel; 2] = [Channel::Sky, Channel::Block];
+
+/// The blocks and light the propagation runs through (the scene's chunks)
+pub trait LightMap {
+    /// Block and light of a cell, None outside the lit chunks
+    fn cell(&self, coord: &WorldCoord) -> Option<(Block, Light)>;
+
+    fn set_light(&mut self, coord: &WorldCoord, light: Light);
+}
+
+/// Level a cell of `level` gives its neighbor `into` in direction `face`
+fn spread(channel: Channel, level: u8, face: usize, into: Block) -> u8 {
+    if level == 0 || into.is_opaque() {
+        return 0;
+    }
+    // sunlight falls through air without getting weaker
+    if channel == Channel::Sky && level == MAX_LIGHT && face == DOWN && into.is_empty() {
+        MAX_LIGHT
+    } else {
+        level - 1
+    }
+}
+
+fn neighbor(coord: &WorldCoord, face: usize) -> WorldCoord {
+    let (dx, dy, dz) = FACE_OFFSETS[face];
+    WorldCoord(coord.0 + dx, coord.1 + dy, coord.2 + dz)
+}
+
+/// Cells waiting to spread light out, or to take back the light they gave
+#[derive(Default)]
+struct LightQueues {
+    add: VecDeque<(WorldCoord, Channel)>,
+    /// Cells already darkened, with the level they had
+    remove: VecDeque<(WorldCoord, Channel, u8)>,
+}
+
+impl LightQueues {
+    fn propagate(&mut self, map: &mut impl LightMap) {
+        while let Some((coord, channel, level)) = self.remove.pop_front() {
+            for face in 0..FACE_OFFSETS.len() {
+                let next = neighbor(&coord, face);
+                let Some((block, light)) = map.cell(&next) else { continue };
+                let current = light.get(channel);
+                if current == 0 {
+                    continue;
+                }
+                if current <= spread(channel, level, face, block) {
+                    // lit by the darkened cell: darken it too (emitters keep their own light)
+                    let own = match channel {
+                        Channel::Sky => light.with(channel, 0, light),
+                        Channel::Block => Light::own(light.sky(), block),
+                    };
+                    map.set_light(&next, own);
+                    self.remove.push_back((next, channel, current));
+                    if own.get(channel) > 0 {
+                        self.add.push_back((next, channel));
+                    }
+                } else {
+                    // lit from elsewhere: it lights the darkened cells again
+                    self.add.push_back((next, channel));
+                }
+            }
+        }
+
+        while let Some((coord, channel)) = self.add.pop_front() {
+            let Some((_, light)) = map.cell(&coord) else { continue };
+            let level = light.get(channel);
+            for face in 0..FACE_OFFSETS.len() {
+                let next = neighbor(&coord, face);
+                let Some((block, next_light)) = map.cell(&next) else { continue };
+                let level = spread(channel, level, face, block);
+                if level > next_light.get(channel) {
+                    map.set_light(&next, next_light.with(channel, level, light));
+                    self.add.push_back((next, channel));
+                }
+            }
+        }
+    }
+}
+
+/// Relight around a cell whose block just changed
+pub fn block_changed(map: &mut impl LightMap, coord: &WorldCoord) {
+    let Some((block, light)) = map.cell(coord) else { return };
+    let mut queues = LightQueues::default();
+    for channel in CHANNELS {
+        let level = light.get(channel);
+        if level > 0 {
+            queues.remove.push_back((*coord, channel, level));
+        }
+        // the neighbors light the cell again wherever the new block lets them
+        for face in 0..FACE_OFFSETS.len() {
+            queues.add.push_back((neighbor(coord, face), channel));
+        }
+    }
+    map.set_light(coord, Light::own(0, block));
+    if block.light_emission() > 0 {
+        queues.add.push_back((*coord, Channel::Block));
+    }
+    queues.propagate(map);
+}
+
+/// Light a chunk whose cells just became part of the map
+///
+/// Sky light falls down the columns from the lit chunk above (none if it is
+/// not lit yet), emitters shine, and the lit neighbors spread their light in;
+/// then the light spreads out into the neighbors, and the chunk below drops
+/// the open sky it saw through this chunk where this chunk covers it.
+pub fn light_chunk(map: &mut impl LightMap, chunk: &ChunkCoord) {
+    let WorldCoord(x0, y0, z0) = chunk.to_world_coord();
+    let size = CHUNK_SIZE;
+    let mut queues = LightQueues::default();
+
+    for z in z0..z0 + size {
+        for x in x0..x0 + size {
+            let mut sky = map.cell(&WorldCoord(x, y0 + size, z)).map_or(0, |(_, light)| light.sky());
+            for y in (y0..y0 + size).rev() {
+                let coord = WorldCoord(x, y, z);
+                let Some((block, _)) = map.cell(&coord) else { continue };
+                sky = spread(Channel::Sky, sky, DOWN, block);
+                map.set_light(&coord, Light::own(sky, block));
+                if sky > 0 {
+                    queues.add.push_back((coord, Channel::Sky));
+                }
+                if block.light_emission() > 0 {
+                    queues.add.push_back((coord, Channel::Block));
+                }
+            }
+
+            // below: open sky it saw while this was an unlit air chunk, that this
+            // column does not let through
+            let (bottom, below) = (WorldCoord(x, y0, z), WorldCoord(x, y0 - 1, z));
+            if let (Some((_, light)), Some((block, below_light))) = (map.cell(&bottom), map.cell(&below)) {
+                if below_light.sky() == MAX_LIGHT && spread(Channel::Sky, light.sky(), DOWN, block) < MAX_LIGHT {
+                    map.set_light(&below, below_light.with(Channel::Sky, 0, below_light));
+                    queues.remove.push_back((below, Channel::Sky, MAX_LIGHT));
+                }
+            }
+        }
+    }
+
+    // the lit neighbors shine in through all six faces
+    for (face, &(dx, dy, dz)) in FACE_OFFSETS.iter().enumerate() {
+        for a in 0..size {
+            for b in 0..size {
+                let (x, y, z) = match face / 2 {
+                    0 => (if dx > 0 { size } else { -1 }, a, b),
+                    1 => (a, if dy > 0 { size } else { -1 }, b),
+                    _ => (a, b, if dz > 0 { size } else { -1 }),
+                };
+                let coord = WorldCoord(x0 + x, y0 + y, z0 + z);
+                if map.cell(&coord).is_some() {
+                    queues.add.extend(CHANNELS.map(|channel| (coord, channel)));
+                }
+            }
+        }
+    }
+    queues.propagate(map);
+}
+
+/// Light of a chunk and the cells around it, gathered for meshing
+pub struct LightVolume(Vec<Light>);
+
+impl LightVolume {
+    const SIDE: isize = CHUNK_SIZE + 2;
+
+    /// Cells outside the lit chunks count as open sky
+    pub fn gather(map: &impl LightMap, chunk: &ChunkCoord) -> Self {
+        let WorldCoord(x0, y0, z0) = chunk.to_world_coord();
+        let mut cells = Vec::with_capacity(Self::SIDE.pow(3) as usize);
+        for z in -1..=CHUNK_SIZE {
+            for y in -1..=CHUNK_SIZE {
+                for x in -1..=CHUNK_SIZE {
+                    let light = map.cell(&WorldCoord(x0 + x, y0 + y, z0 + z)).map_or(Light::SKY, |(_, light)| light);
+                    cells.push(light);
+                }
+            }
+        }
+        Self(cells)
+    }
+
+    /// Light at a chunk-local cell, -1..=CHUNK_SIZE along every axis
+    pub fn get(&self, x: isize, y: isize, z: isize) -> Light {
+        self.0[((x + 1) + (y + 1) * Self::SIDE + (z + 1) * Self::SIDE * Self::SIDE) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Some chunks under open air (like the scene's air chunks), everything else unloaded
+    struct TestMap {
+        cells: HashMap<(isize, isize, isize), (Block, Light)>,
+        /// World y from which up all cells are open air
+        air_from: isize,
+    }
+
+    impl TestMap {
+        fn new(chunks: &[ChunkCoord], block: impl Fn(isize, isize, isize) -> Block) -> Self {
+            let mut cells = HashMap::new();
+            for chunk in chunks {
+                let WorldCoord(x0, y0, z0) = chunk.to_world_coord();
+                for z in z0..z0 + CHUNK_SIZE {
+                    for y in y0..y0 + CHUNK_SIZE {
+                        for x in x0..x0 + CHUNK_SIZE {
+                            cells.insert((x, y, z), (block(x, y, z), Light::DARK));
+                        }
+                    }
+                }
+            }
+            let air_from = chunks.iter().map(|chunk| chunk.1 + 1).max().unwrap_or(0) * CHUNK_SIZE;
+            Self { cells, air_from }
+        }
+
+        fn light(&self, x: isize, y: isize, z: isize) -> Light {
+            self.cells[&(x, y, z)].1
+        }
+
+        fn set_block(&mut self, x: isize, y: isize, z: isize, block: Block) {
+            self.cells.get_mut(&(x, y, z)).unwrap().0 = block;
+            block_changed(self, &WorldCoord(x, y, z));
+        }
+    }
+
+    impl LightMap for TestMap {
+        fn cell(&self, coord: &WorldCoord) -> Option<(Block, Light)> {
+            if coord.1 >= self.air_from {
+                return Some((Block::Empty, Light::SKY));
+            }
+            self.cells.get(&(coord.0, coord.1, coord.2)).copied()
+        }
+
+        fn set_light(&mut self, coord: &WorldCoord, light: Light) {
+            if let Some(cell) = self.cells.get_mut(&(coord.0, coord.1, coord.2)) {
+                cell.1 = light;
+            }
+        }
+    }
+
+    #[test]
+    fn test_light_floods_and_retreats() {
+        // stone below y = 8 with a roof at y = 12 over x < 8 (a cave mouth open to +x)
+        let chunks = [ChunkCoord(0, 0, 0), ChunkCoord(1, 0, 0)];
+        let mut map = TestMap::new(&chunks, |x, y, _| if y < 8 || (y == 12 && x < 8) { Block::Stone } else { Block::Empty });
+        light_chunk(&mut map, &ChunkCoord(0, 0, 0));
+        light_chunk(&mut map, &ChunkCoord(1, 0, 0));
+
+        // full sky in the open, fading under the roof with the distance to its edge
+        assert_eq!(map.light(20, 8, 5).sky(), MAX_LIGHT);
+        assert_eq!(map.light(7, 8, 5).sky(), MAX_LIGHT - 1);
+        assert_eq!(map.light(2, 8, 5).sky(), MAX_LIGHT - 6);
+        assert_eq!(map.light(2, 4, 5), Light::DARK, "rock holds no light");
+
+        // closing the mouth darkens the cave, opening it again brings the light back
+        for z in 0..CHUNK_SIZE {
+            for y in 8..12 {
+                map.set_block(8, y, z, Block::Stone);
+            }
+        }
+        assert_eq!(map.light(2, 8, 5).sky(), 0);
+        map.set_block(8, 8, 5, Block::Empty);
+        assert_eq!(map.light(2, 8, 5).sky(), MAX_LIGHT - 7);
+
+        // block light spreads across the chunk border and goes out with its source
+        map.set_block(14, 10, 5, Block::Magma);
+        let level = Block::Magma.light_emission();
+        assert_eq!(map.light(14, 10, 5).block(), level);
+        assert_eq!(map.light(17, 10, 5).block(), level - 3);
+
+        // a brighter emitter takes over the cells it lights best, with its color
+        map.set_block(20, 10, 5, Block::Torch);
+        let torch = Block::Torch.light_emission();
+        assert_eq!(map.light(17, 10, 5), Light::emitted(MAX_LIGHT, torch - 3, Block::Torch));
+        map.set_block(20, 10, 5, Block::Empty);
+        assert_eq!(map.light(17, 10, 5), Light::emitted(MAX_LIGHT, level - 3, Block::Magma));
+
+        map.set_block(14, 10, 5, Block::Empty);
+        assert!((0..2 * CHUNK_SIZE).all(|x| map.light(x, 10, 5).block() == 0));
+    }
+
+    #[test]
+    fn test_chunk_lit_before_the_one_above() {
+        // stone below y = 4, a roof at y = 20 over x < 8 in the chunk above
+        let chunks = [ChunkCoord(0, 0, 0), ChunkCoord(0, 1, 0)];
+        let mut map = TestMap::new(&chunks, |x, y, _| if y < 4 || (y == 20 && x < 8) { Block::Stone } else { Block::Empty });
+
+        // the chunk above is not lit yet: no sky light comes in
+        light_chunk(&mut map, &ChunkCoord(0, 0, 0));
+        assert_eq!(map.light(12, 8, 5).sky(), 0);
+
+        // once it is, the sky falls through, except under the roof
+        light_chunk(&mut map, &ChunkCoord(0, 1, 0));
+        assert_eq!(map.light(12, 4, 5).sky(), MAX_LIGHT);
+        assert_eq!(map.light(7, 8, 5).sky(), MAX_LIGHT - 1);
+        assert_eq!(map.light(2, 8, 5).sky(), MAX_LIGHT - 6);
+    }
+}
@@ -0,0 +1,252 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::utils::{ChunkCoord, ChunkMesh, ChunkVertex, WorldCoord};
+
+// ============================================================================
+// MESH ARENA: shared GPU buffers for all chunk meshes of the active scene
+// ============================================================================
+//
+// Instead of two buffers per chunk mesh, every chunk mesh lives in one big
+// vertex buffer and one big index buffer:
+//   → both buffers are split up by a first-fit free list, a chunk mesh takes
+//     one range of each and gives them back when it is dropped (remeshed,
+//     unloaded or evicted)
+//   → meshes are written with queue.write_buffer, no buffers are created
+//   → indices are stored relative to the start of the vertex buffer, so draws
+//     need no base vertex (WebGL has none)
+//   → when a mesh does not fit, both buffers grow to twice their size and the
+//     old contents are copied over on the GPU
+//
+// The renderer draws all chunks from these two buffers, with one indirect
+// draw where the device supports it (see view/render.rs). There is one arena
+// for all dimensions, it moves to the scene of the dimension being entered.
+// ============================================================================
+
+/// Vertices the arena starts with (12 MB)
+const INITIAL_VERTICES: u32 = 1 << 20;
+
+/// Indices the arena starts with (6 MB, 6 indices per 4 vertices)
+const INITIAL_INDICES: u32 = 3 << 19;
+
+/// First-fit allocator of element ranges inside a buffer
+#[derive(Debug)]
+pub struct FreeList {
+    /// Free ranges sorted by start, neighbors are always merged
+    free: Vec<Range<u32>>,
+    capacity: u32,
+}
+
+impl FreeList {
+    pub fn new(capacity: u32) -> Self {
+        Self { free: std::iter::once(0..capacity).collect(), capacity }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Elements currently handed out
+    pub fn used(&self) -> u32 {
+        self.capacity - self.free.iter().map(|range| range.len() as u32).sum::<u32>()
+    }
+
+    /// Take the first free range of at least `len` elements
+    pub fn alloc(&mut self, len: u32) -> Option<Range<u32>> {
+        let i = self.free.iter().position(|range| range.len() as u32 >= len)?;
+        let start = self.free[i].start;
+        self.free[i].start += len;
+        if self.free[i].is_empty() {
+            self.free.remove(i);
+        }
+        Some(start..start + len)
+    }
+
+    /// Give back a range returned by `alloc`
+    pub fn free(&mut self, range: Range<u32>) {
+        if range.is_empty() {
+            return;
+        }
+        let i = self.free.partition_point(|free| free.start < range.start);
+        let merge_prev = i > 0 && self.free[i - 1].end == range.start;
+        let merge_next = i < self.free.len() && self.free[i].start == range.end;
+        match (merge_prev, merge_next) {
+            (true, true) => {
+                self.free[i - 1].end = self.free[i].end;
+                self.free.remove(i);
+            }
+            (true, false) => self.free[i - 1].end = range.end,
+            (false, true) => self.free[i].start = range.start,
+            (false, false) => self.free.insert(i, range),
+        }
+    }
+
+    /// Add free space at the end
+    pub fn grow(&mut self, capacity: u32) {
+        let (old, new) = (self.capacity, capacity);
+        self.capacity = capacity;
+        self.free(old..new);
+    }
+}
+
+struct Allocators {
+    vertices: FreeList,
+    indices: FreeList,
+}
+
+/// A chunk mesh placed in the arena, its ranges are freed when it is dropped
+pub struct ArenaMesh {
+    /// Range of the index buffer to draw
+    pub indices: Range<u32>,
+    /// World position of the corner of the chunk the mesh is drawn at
+    pub origin: [i32; 3],
+    vertices: Range<u32>,
+    /// None for meshes without geometry, they belong to no arena
+    allocators: Option<Rc<RefCell<Allocators>>>,
+}
+
+impl ArenaMesh {
+    /// A mesh without geometry (takes no space, nothing to write)
+    pub fn empty() -> Self {
+        Self { indices: 0..0, origin: [0; 3], vertices: 0..0, allocators: None }
+    }
+
+    pub fn index_count(&self) -> u32 {
+        self.indices.len() as u32
+    }
+}
+
+impl Drop for ArenaMesh {
+    fn drop(&mut self) {
+        let Some(allocators) = &self.allocators else { return };
+        let mut allocators = allocators.borrow_mut();
+        allocators.vertices.free(self.vertices.clone());
+        allocators.indices.free(self.indices.clone());
+    }
+}
+
+pub struct MeshArena {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    allocators: Rc<RefCell<Allocators>>,
+}
+
+impl MeshArena {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let (vertex_buffer, index_buffer) = create_buffers(device, INITIAL_VERTICES, INITIAL_INDICES);
+        let allocators = Allocators { vertices: FreeList::new(INITIAL_VERTICES), indices: FreeList::new(INITIAL_INDICES) };
+        Self { vertex_buffer, index_buffer, allocators: Rc::new(RefCell::new(allocators)) }
+    }
+
+    /// (used, capacity) in bytes of the vertex and index buffers together
+    pub fn usage(&self) -> (u64, u64) {
+        let allocators = self.allocators.borrow();
+        let bytes = |vertices: u32, indices: u32| {
+            vertices as u64 * std::mem::size_of::<ChunkVertex>() as u64 + indices as u64 * 4
+        };
+        (
+            bytes(allocators.vertices.used(), allocators.indices.used()),
+            bytes(allocators.vertices.capacity(), allocators.indices.capacity()),
+        )
+    }
+
+    /// Copy the mesh of a chunk into the arena
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, mesh: &ChunkMesh, coord: &ChunkCoord) -> ArenaMesh {
+        let WorldCoord(x, y, z) = coord.to_world_coord();
+        let origin = [x as i32, y as i32, z as i32];
+        if mesh.is_empty() {
+            return ArenaMesh { indices: 0..0, origin, vertices: 0..0, allocators: None };
+        }
+
+        let (vertex_count, index_count) = (mesh.vertices.len() as u32, mesh.indices.len() as u32);
+        let (vertices, indices) = loop {
+            let mut allocators = self.allocators.borrow_mut();
+            let vertices = allocators.vertices.alloc(vertex_count);
+            let indices = allocators.indices.alloc(index_count);
+            match (vertices, indices) {
+                (Some(vertices), Some(indices)) => break (vertices, indices),
+                (vertices, indices) => {
+                    // give back what did fit and retry with bigger buffers
+                    vertices.into_iter().for_each(|range| allocators.vertices.free(range));
+                    indices.into_iter().for_each(|range| allocators.indices.free(range));
+                    drop(allocators);
+                    self.grow(device, queue);
+                }
+            }
+        };
+
+        // indices point at the vertices' place in the shared buffer
+        let rebased: Vec<u32> = mesh.indices.iter().map(|i| i + vertices.start).collect();
+        let vertex_size = std::mem::size_of::<ChunkVertex>() as u64;
+        queue.write_buffer(&self.vertex_buffer, vertices.start as u64 * vertex_size, bytemuck::cast_slice(&mesh.vertices));
+        queue.write_buffer(&self.index_buffer, indices.start as u64 * 4, bytemuck::cast_slice(&rebased));
+
+        ArenaMesh { indices, origin, vertices, allocators: Some(self.allocators.clone()) }
+    }
+
+    /// Double both buffers, keeping their contents
+    fn grow(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut allocators = self.allocators.borrow_mut();
+        let (old_vertices, old_indices) = (allocators.vertices.capacity(), allocators.indices.capacity());
+        let (vertices, indices) = (old_vertices * 2, old_indices * 2);
+        let vertex_size = std::mem::size_of::<ChunkVertex>() as u64;
+        assert!(
+            vertices as u64 * vertex_size <= device.limits().max_buffer_size,
+            "chunk meshes exceed the largest GPU buffer"
+        );
+
+        let (vertex_buffer, index_buffer) = create_buffers(device, vertices, indices);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("mesh_arena_grow") });
+        encoder.copy_buffer_to_buffer(&self.vertex_buffer, 0, &vertex_buffer, 0, old_vertices as u64 * vertex_size);
+        encoder.copy_buffer_to_buffer(&self.index_buffer, 0, &index_buffer, 0, old_indices as u64 * 4);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        self.vertex_buffer = vertex_buffer;
+        self.index_buffer = index_buffer;
+        allocators.vertices.grow(vertices);
+        allocators.indices.grow(indices);
+    }
+}
+
+fn create_buffers(device: &wgpu::Device, vertices: u32, indices: u32) -> (wgpu::Buffer, wgpu::Buffer) {
+    let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Mesh Arena Vertex Buffer"),
+        size: vertices as u64 * std::mem::size_of::<ChunkVertex>() as u64,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Mesh Arena Index Buffer"),
+        size: indices as u64 * 4,
+        usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    (vertex_buffer, index_buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_list_reuses_and_merges_ranges() {
+        let mut list = FreeList::new(100);
+        let a = list.alloc(30).unwrap();
+        let b = list.alloc(30).unwrap();
+        let c = list.alloc(30).unwrap();
+        assert_eq!((a.clone(), b.clone(), c.clone()), (0..30, 30..60, 60..90));
+        assert!(list.alloc(20).is_none());
+
+        // freed neighbors merge back into one range that fits a bigger mesh
+        list.free(a);
+        list.free(c);
+        assert!(list.alloc(45).is_none());
+        list.free(b);
+        assert_eq!(list.used(), 0);
+        assert_eq!(list.alloc(100), Some(0..100));
+
+        list.grow(150);
+        assert_eq!(list.alloc(50), Some(100..150));
+    }
+}
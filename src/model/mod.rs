@@ -2,7 +2,19 @@
 pub mod world;
 pub mod camera;
 pub mod scene;
+pub mod horizon;
+pub mod dimension;
+pub mod daylight;
+pub mod fog;
+pub mod light;
+pub mod mesh_arena;
+pub mod visibility;
 
 pub use world::{Block, Chunk, CHUNK_SIZE};
 pub use camera::Camera;
 pub use scene::Scene;
+pub use horizon::Horizon;
+pub use dimension::{DimensionSettings, Dimensions};
+pub use daylight::{Daylight, WorldClock};
+pub use fog::{Fog, FogMode};
+pub use mesh_arena::MeshArena;
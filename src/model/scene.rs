@@ -1,22 +1,45 @@
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
-use crate::{model::world::{Block, Chunk}, utils::{ChunkCoord, Mesh, MeshBuffer, WorldCoord}};
+use crate::{model::world::{Block, Chunk, CHUNK_SIZE}, storage, utils::{BlockCoord, ChunkCoord, WorldCoord}};
 
-use super::world::VoxelDensityGenerator;
+use super::camera::Camera;
+use super::dimension::DimensionSettings;
+use super::horizon::Horizon;
+use super::light::{self, Light, LightMap, LightVolume};
+use super::world::terrain::TerrainConfig;
+use super::world::{VoxelDensityGenerator, WorldBounds, WorldGenerator};
+use super::mesh_arena::{ArenaMesh, MeshArena};
+use super::visibility::{flood_visible, CullStats, FaceConnections};
 
 
 
-fn select_lod(distance_to_player: usize) -> LOD {
-    if distance_to_player < 200 {
-        0  // Full resolution
-    } else if distance_to_player < 40 {
-        1  // 1/2 resolution
-    } else if distance_to_player < 50 {
-        2  // 1/4 resolution
-    } else if distance_to_player < 60 {
-        3  // 1/8 resolution
-    } else {
-        4  // 1/16 resolution
+/// Outer chunk distance of each LOD ring: LOD n is used below LOD_RINGS[n], the
+/// coarsest LOD beyond the last ring
+const LOD_RINGS: [usize; 4] = [8, 16, 32, 48];
+
+/// Chunks a chunk has to move past the edge of its ring before it changes LOD,
+/// so chunks on a ring edge don't flap between two meshes as the player moves
+const LOD_HYSTERESIS: usize = 2;
+
+/// World position of the corner of a chunk, as stored in its ArenaMesh
+fn origin_of(coord: &ChunkCoord) -> [i32; 3] {
+    let WorldCoord(x, y, z) = coord.to_world_coord();
+    [x as i32, y as i32, z as i32]
+}
+
+/// LOD for a chunk at a distance (in chunks) that is currently shown at `current`
+fn select_lod(distance_to_player: usize, current: Option<LOD>) -> LOD {
+    let ring = |distance: usize| LOD_RINGS.iter().position(|&outer| distance < outer).unwrap_or(LOD_RINGS.len()) as LOD;
+    let target = ring(distance_to_player);
+    match current {
+        Some(lod) if lod != target => {
+            let inner = if lod == 0 { 0 } else { LOD_RINGS[lod as usize - 1] };
+            let outer = LOD_RINGS.get(lod as usize).copied().unwrap_or(usize::MAX);
+            let keep = distance_to_player + LOD_HYSTERESIS >= inner && distance_to_player < outer.saturating_add(LOD_HYSTERESIS);
+            if keep { lod } else { target }
+        }
+        _ => target,
     }
 }
 
@@ -47,8 +70,11 @@ fn generate_qube_offset_in_spherical_order(active_size: [usize; 3]) -> Vec<((isi
 
 type LOD = u8;
 
-/// Active entry: (Chunk, (LOD, MeshBuffer))
-type ActiveEntry = (Chunk, (LOD, MeshBuffer));
+/// Far terrain tiles built per update, each one samples the generator a few hundred times
+const HORIZON_TILES_PER_UPDATE: usize = 2;
+
+/// Active entry: (Chunk, (LOD, ArenaMesh))
+type ActiveEntry = (Chunk, (LOD, ArenaMesh));
 
 
 pub struct Scene {
@@ -58,7 +84,7 @@ pub struct Scene {
     /// 
     /// Some((Chunk, None)) = chunk loaded/generated but not meshed
     /// 
-    /// Some((Chunk, Some((LOD, MeshBuffer)))) = chunk loaded and meshed
+    /// Some((Chunk, Some((LOD, ArenaMesh)))) = chunk loaded and meshed
     pub active: Vec<Option<Rc<ActiveEntry>>>,
 
     /// Number of chunks along each axis in the active chunk grid
@@ -67,11 +93,60 @@ pub struct Scene {
     sphere_offsets: Vec<((isize, isize, isize), usize)>,
 
     empty_entry: Rc<ActiveEntry>,
-    density_generator: VoxelDensityGenerator,
+    generator: WorldGenerator,
+
+    /// GPU buffers holding the meshes of all active chunks, None while the
+    /// dimension is parked (the arena moves on with the player, see `leave`)
+    arena: Option<MeshArena>,
+
+    /// Per active slot: reached by the cave culling search last frame (see visibility.rs)
+    visible: Vec<bool>,
+    pub cull_stats: CullStats,
+
+    /// Low-poly terrain beyond the active chunk window
+    pub horizon: Horizon,
+
+    /// Lighting, sky and portal link of the dimension this scene is
+    pub dimension: DimensionSettings,
+
+    /// Block edits per chunk, in order, applied again whenever the chunk is generated
+    ///
+    /// Saved per dimension and seed (see `save_edits`) and read back at startup.
+    edits: HashMap<ChunkCoord, Vec<(BlockCoord, Block)>>,
+    /// The edits changed since they were last saved or loaded
+    unsaved_edits: bool,
+
+    /// Chunks whose light, or the light around them, changed since they were meshed
+    light_dirty: HashSet<ChunkCoord>,
 }
 
 impl Scene {
     pub fn new(active_size: [usize; 3], device: &wgpu::Device) -> Self {
+        Self::with_terrain_config(active_size, device, TerrainConfig::default())
+    }
+
+    /// Create a scene whose terrain is generated with the given config (e.g. a preset)
+    pub fn with_terrain_config(active_size: [usize; 3], device: &wgpu::Device, terrain_config: TerrainConfig) -> Self {
+        Self::with_generator(active_size, device, WorldGenerator::Noise(VoxelDensityGenerator::with_config(terrain_config)))
+    }
+
+    /// Create a scene with any world generator (e.g. a heightmap world)
+    pub fn with_generator(active_size: [usize; 3], device: &wgpu::Device, generator: WorldGenerator) -> Self {
+        Self::with_dimension(active_size, device, generator, DimensionSettings::overworld())
+    }
+
+    /// Create the scene of a dimension (see dimension.rs)
+    pub fn with_dimension(active_size: [usize; 3], device: &wgpu::Device, generator: WorldGenerator, dimension: DimensionSettings) -> Self {
+        Self::build(active_size, Some(MeshArena::new(device)), generator, dimension)
+    }
+
+    /// Create the scene of a dimension the player is not in, it takes over the
+    /// mesh arena of the active scene when entered (see `leave`)
+    pub fn parked(active_size: [usize; 3], generator: WorldGenerator, dimension: DimensionSettings) -> Self {
+        Self::build(active_size, None, generator, dimension)
+    }
+
+    fn build(active_size: [usize; 3], arena: Option<MeshArena>, generator: WorldGenerator, dimension: DimensionSettings) -> Self {
         // ensure chunk_distance is a power of two for modulo indexing
         // assert!(chunk_distance.is_power_of_two(), "chunk_distance must be a power of two");
         
@@ -81,17 +156,154 @@ impl Scene {
             active.push(None);
         }
 
+        // the horizon starts where the chunk window ends
+        let window_radius = (active_size[0].min(active_size[2]) / 2) as i32 * CHUNK_SIZE as i32;
+
         Self {
+            visible: vec![true; active.len()],
+            cull_stats: CullStats::default(),
             active_size: active_size,
             active: active,
             previous_player_chunk_coord: ChunkCoord(0, 0, 0),
 
-            empty_entry: Rc::new((Chunk::new_empty(), (0, Mesh::empty().upload(device)))),
+            empty_entry: Rc::new((Chunk::new_empty(), (0, ArenaMesh::empty()))),
             sphere_offsets: generate_qube_offset_in_spherical_order(active_size),
-            density_generator: VoxelDensityGenerator::new(),
+            generator,
+            arena,
+            horizon: Horizon::new(window_radius),
+            dimension,
+            edits: HashMap::new(),
+            unsaved_edits: false,
+            light_dirty: HashSet::new(),
+        }
+    }
+
+    pub fn generator(&self) -> &WorldGenerator {
+        &self.generator
+    }
+
+    pub fn terrain_config(&self) -> &TerrainConfig {
+        self.generator.terrain_config()
+    }
+
+    /// Vertical extent of the world: no chunks are loaded and no blocks edited outside
+    pub fn bounds(&self) -> WorldBounds {
+        self.generator.bounds()
+    }
+
+    /// Distance from the player up to which terrain is shown: the edge of the
+    /// loaded chunk window (a chunk short of it, chunks load around the
+    /// player's chunk), or of the far terrain if the dimension has one
+    pub fn view_distance(&self) -> f32 {
+        if self.dimension.horizon {
+            return self.horizon.outer_radius() as f32;
+        }
+        let half = self.active_size[0].min(self.active_size[2]) / 2;
+        (half.saturating_sub(1) as isize * CHUNK_SIZE) as f32
+    }
+
+    /// Replace the terrain config and drop all loaded chunks so they regenerate
+    ///
+    /// Block edits are lost.
+    pub fn set_terrain_config(&mut self, terrain_config: TerrainConfig) {
+        self.generator.set_terrain_config(terrain_config);
+        self.edits.clear();
+        self.unsaved_edits = true;
+        self.evict();
+    }
+
+    /// Replace the world generator and drop all loaded chunks and block edits
+    pub fn set_generator(&mut self, generator: WorldGenerator) {
+        self.generator = generator;
+        self.edits.clear();
+        self.unsaved_edits = true;
+        self.evict();
+    }
+
+    /// Drop all loaded chunks, their GPU meshes and the far terrain (e.g. when
+    /// leaving the dimension), block edits are kept
+    pub fn evict(&mut self) {
+        for entry in self.active.iter_mut() {
+            *entry = None;
         }
+        self.light_dirty.clear();
+        self.horizon.clear();
+    }
+
+    /// Leave this dimension for the one of `next`: evict, then hand the mesh
+    /// arena (now empty) over to `next`
+    pub fn leave(&mut self, next: &mut Scene) {
+        self.evict();
+        next.arena = self.arena.take();
     }
 
+    /// GPU buffers of the loaded chunk meshes
+    pub fn arena(&self) -> &MeshArena {
+        self.arena.as_ref().expect("only the active scene has a mesh arena")
+    }
+
+    fn record_edit(&mut self, world_coord: &WorldCoord, block: Block) {
+        let block_coord = world_coord.to_block_coord();
+        let edits = self.edits.entry(world_coord.to_chunk_coord()).or_default();
+        edits.retain(|(coord, _)| *coord != block_coord);
+        edits.push((block_coord, block));
+        self.unsaved_edits = true;
+    }
+
+    /// Edit a block of a chunk that is not loaded, it is applied once the chunk generates
+    pub fn queue_block(&mut self, world_coord: &WorldCoord, block: Block) {
+        if self.bounds().contains_y(world_coord.1) {
+            self.record_edit(world_coord, block);
+        }
+    }
+
+
+    /// Storage key of the block edits, one per dimension and seed
+    fn edits_key(&self) -> String {
+        format!("woxel-edits-{}-{}", self.dimension.name.replace(' ', "-"), self.terrain_config().seed)
+    }
+
+    /// Block edits as text, one `x y z block` line per edit (world coordinates, Block id)
+    pub fn edits_to_text(&self) -> String {
+        let mut text = String::new();
+        for (chunk_coord, edits) in &self.edits {
+            let WorldCoord(x0, y0, z0) = chunk_coord.to_world_coord();
+            for (BlockCoord(x, y, z), block) in edits {
+                let (x, y, z) = (x0 + *x as isize, y0 + *y as isize, z0 + *z as isize);
+                text.push_str(&format!("{x} {y} {z} {}\n", *block as u8));
+            }
+        }
+        text
+    }
+
+    /// Add the block edits of a text from `edits_to_text`, lines that do not parse are skipped
+    pub fn load_edits_text(&mut self, text: &str) {
+        for line in text.lines() {
+            let values: Vec<isize> = line.split_whitespace().filter_map(|value| value.parse().ok()).collect();
+            if let [x, y, z, block] = values[..] {
+                if (0..Block::COUNT as isize).contains(&block) {
+                    self.queue_block(&WorldCoord(x, y, z), Block::from_u8(block as u8));
+                }
+            }
+        }
+    }
+
+    /// Read the block edits saved for this dimension (at startup, before chunks load)
+    pub fn load_edits(&mut self) {
+        if let Some(text) = storage::load(&self.edits_key()) {
+            self.load_edits_text(&text);
+        }
+        self.unsaved_edits = false;
+    }
+
+    /// Save the block edits if they changed since the last save
+    pub fn save_edits(&mut self) -> Result<(), String> {
+        if !self.unsaved_edits {
+            return Ok(());
+        }
+        self.unsaved_edits = false;
+        storage::save(&self.edits_key(), &self.edits_to_text())
+    }
 
     fn active_idx(&self, coord: &ChunkCoord) -> usize {
         coord.0.rem_euclid(self.active_size[0] as isize) as usize + 
@@ -103,13 +315,91 @@ impl Scene {
         self.active[self.active_idx(coord)].as_deref()
     }
 
-    fn get_active_mut(&mut self, coord: &ChunkCoord) -> Option<&mut ActiveEntry> {
-        let active_idx = self.active_idx(coord);
-        if let Some(entry) = &mut self.active[active_idx] {
-            Rc::get_mut(entry)
+    /// Meshes of the active chunks that passed culling in the last `update_visibility`
+    pub fn visible_meshes(&self) -> impl Iterator<Item = &ArenaMesh> {
+        self.active.iter().zip(&self.visible)
+            .filter(|(_, visible)| **visible)
+            .filter_map(|(entry, _)| entry.as_deref())
+            .map(|(_, (_, mesh))| mesh)
+    }
+
+    /// Meshes of the active chunks within `radius` of a point (e.g. shadow casters)
+    pub fn meshes_near(&self, center: glam::Vec3, radius: f32) -> impl Iterator<Item = &ArenaMesh> {
+        let half = CHUNK_SIZE as f32 / 2.0;
+        // chunk centers up to half a chunk diagonal outside the radius still reach into it
+        let reach = radius + half * 3f32.sqrt();
+        self.active.iter()
+            .filter_map(|entry| entry.as_deref())
+            .map(|(_, (_, mesh))| mesh)
+            .filter(move |mesh| {
+                let chunk_center = glam::Vec3::new(mesh.origin[0] as f32, mesh.origin[1] as f32, mesh.origin[2] as f32) + half;
+                mesh.index_count() > 0 && chunk_center.distance_squared(center) <= reach * reach
+            })
+    }
+
+    /// Mark the chunks the camera can see: inside the view frustum and not
+    /// hidden behind opaque chunks (see visibility.rs)
+    pub fn update_visibility(&mut self, camera: &Camera) {
+        let planes = camera.frustum();
+        let size = CHUNK_SIZE as f32;
+        let in_frustum = |coord: &ChunkCoord| {
+            let min = glam::Vec3::new(coord.0 as f32, coord.1 as f32, coord.2 as f32) * size;
+            Camera::is_box_in_frustum(&planes, min, min + glam::Vec3::splat(size))
+        };
+
+        // only slots that certainly hold the chunk at their coordinate (no wrap-around)
+        let center = self.previous_player_chunk_coord;
+        let half = self.active_size.map(|n| n as isize / 2);
+        let in_window = |coord: &ChunkCoord| {
+            (coord.0 - center.0).abs() < half[0] && (coord.1 - center.1).abs() < half[1] && (coord.2 - center.2).abs() < half[2]
+        };
+
+        let eye = (camera.eye / size).floor();
+        let camera_chunk = ChunkCoord(eye.x as isize, eye.y as isize, eye.z as isize);
+
+        let mut visible = std::mem::take(&mut self.visible);
+        if in_window(&camera_chunk) {
+            visible.fill(false);
+            let connections = |coord: &ChunkCoord| match self.get_active(coord) {
+                Some((chunk, (_, mesh))) if mesh.index_count() == 0 || mesh.origin == origin_of(coord) => chunk.face_connections(),
+                _ => FaceConnections::ALL, // not loaded (yet): don't cull what's behind
+            };
+            let in_view = |coord: &ChunkCoord| in_window(coord) && in_frustum(coord);
+            flood_visible(camera_chunk, connections, in_view, |coord| {
+                !std::mem::replace(&mut visible[self.active_idx(coord)], true)
+            });
         } else {
-            None
+            // camera outside the loaded window (e.g. free flight): frustum culling only
+            visible.fill(true);
         }
+
+        // count what happened to every meshed chunk
+        let mut stats = CullStats::default();
+        for (entry, visible) in self.active.iter().zip(visible.iter_mut()) {
+            let Some((_, (_, mesh))) = entry.as_deref() else { continue };
+            if mesh.index_count() == 0 {
+                continue;
+            }
+            let [x, y, z] = mesh.origin;
+            let coord = ChunkCoord(x as isize / CHUNK_SIZE, y as isize / CHUNK_SIZE, z as isize / CHUNK_SIZE);
+            if !in_frustum(&coord) {
+                *visible = false;
+                stats.outside_frustum += 1;
+            } else if *visible {
+                stats.drawn += 1;
+            } else {
+                stats.occluded += 1;
+            }
+        }
+        self.visible = visible;
+        self.cull_stats = stats;
+    }
+
+    /// Put a chunk into its slot, unlit and without a mesh (tests have no GPU to load chunks with)
+    #[cfg(test)]
+    pub(crate) fn insert_chunk(&mut self, coord: &ChunkCoord, chunk: Chunk) {
+        let active_idx = self.active_idx(coord);
+        self.active[active_idx] = Some(Rc::new((chunk, (0, ArenaMesh::empty()))));
     }
 
     fn unset_active(&mut self, coord: &ChunkCoord) {
@@ -130,31 +420,79 @@ impl Scene {
         }
     }
     
-    pub fn set_block(&mut self, world_coord: &WorldCoord, block: Block, overwrite: bool, device: &wgpu::Device) -> bool {
+    pub fn set_block(&mut self, world_coord: &WorldCoord, block: Block, overwrite: bool, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        // Build limits
+        if !self.bounds().contains_y(world_coord.1) {
+            return false;
+        }
+
         // Find which chunk contains this block
         let chunk_coord = world_coord.to_chunk_coord();
 
-        if let Some((active_chunk, (active_lod, active_mesh_buffer))) = self.get_active_mut(&chunk_coord) {
+        // index the entry directly, the arena is borrowed alongside it
+        let active_idx = self.active_idx(&chunk_coord);
+        let changed = if let Some((active_chunk, _)) = self.active[active_idx].as_mut().and_then(Rc::get_mut) {
+            active_chunk.set_block(&world_coord.to_block_coord(), block, overwrite)
+        } else {
+            false
+        };
 
-            let block_coord = world_coord.to_block_coord();
+        if changed {
+            // relight and upload the new meshes to GPU (the old ones free their arena space)
+            light::block_changed(self, world_coord);
+            self.remesh_with_light(&chunk_coord, device, queue);
 
-            if active_chunk.set_block(&block_coord, block, overwrite) {
-                
-                // upload new mesh to GPU
-                let mut new_mesh = active_chunk.get_mesh(*active_lod);
-                new_mesh.offset_vertices_by(&chunk_coord);
-                *active_mesh_buffer = new_mesh.upload(device);
+            // keep the edit for when the chunk is generated again
+            self.record_edit(world_coord, block);
+        }
+        changed
+    }
 
-                true
-            } else {
-                false
+    /// Light a chunk that entered LOD 0 (see light.rs)
+    fn light_chunk(&mut self, coord: &ChunkCoord) {
+        let active_idx = self.active_idx(coord);
+        if let Some((chunk, _)) = self.active[active_idx].as_mut().and_then(Rc::get_mut) {
+            chunk.fill_light(Light::DARK);
+        }
+        light::light_chunk(self, coord);
+    }
+
+    /// Mesh a loaded chunk again at its LOD and upload it, with its light if it is lit
+    fn remesh(&mut self, coord: &ChunkCoord, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let light = match self.get_active(coord) {
+            Some((chunk, _)) if chunk.is_lit() => Some(LightVolume::gather(self, coord)),
+            _ => None,
+        };
+        let active_idx = self.active_idx(coord);
+        let arena = self.arena.as_mut().expect("only the active scene meshes chunks");
+        if let Some((chunk, (lod, mesh))) = self.active[active_idx].as_mut().and_then(Rc::get_mut) {
+            if !chunk.is_empty() {
+                *mesh = arena.upload(device, queue, &chunk.get_mesh(*lod, light.as_ref()), coord);
             }
-        } else {
-            false
         }
     }
 
-    pub fn update(&mut self, player: &WorldCoord, device: &wgpu::Device, compute_budget: usize) {
+    /// Mesh the lit chunks whose light changed again, returns how many
+    fn remesh_light_dirty(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> usize {
+        let dirty: Vec<ChunkCoord> = self.light_dirty.drain().collect();
+        let mut count = 0;
+        for coord in &dirty {
+            if self.get_active(coord).is_some_and(|(chunk, _)| chunk.is_lit() && !chunk.is_empty()) {
+                self.remesh(coord, device, queue);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Mesh a chunk again along with the lit chunks whose light changed, returns how many
+    fn remesh_with_light(&mut self, coord: &ChunkCoord, device: &wgpu::Device, queue: &wgpu::Queue) -> usize {
+        self.light_dirty.remove(coord);
+        self.remesh(coord, device, queue);
+        1 + self.remesh_light_dirty(device, queue)
+    }
+
+    pub fn update(&mut self, player: &WorldCoord, device: &wgpu::Device, queue: &wgpu::Queue, compute_budget: usize) {
 
         let mut used_compute_budget = 0;
 
@@ -163,12 +501,11 @@ impl Scene {
 
         // copy offsets to allow mutable borrow of self in the loop
         let sphere_offsets = self.sphere_offsets.clone();
+        let bounds = self.bounds();
         
         // iterate in order of distance from player
         for ((offset_x, offset_y, offset_z), distance) in sphere_offsets {
 
-            let required_lod = select_lod(distance);
-
             let chunk_coord = ChunkCoord(
                 player.to_chunk_coord().0 + offset_x,
                 player.to_chunk_coord().1 + offset_y,
@@ -176,6 +513,16 @@ impl Scene {
             ); 
             let active_idx = self.active_idx(&chunk_coord);
 
+            let current_lod = self.get_active(&chunk_coord).map(|(_, (lod, _))| *lod);
+            let required_lod = select_lod(distance, current_lod);
+
+            // chunks below the floor or above the build limit stay air without generating them
+            if !bounds.contains_chunk(&chunk_coord) {
+                if self.active[active_idx].is_none() {
+                    self.active[active_idx] = Some(self.empty_entry.clone());
+                }
+                continue;
+            }
 
             // this manual check is needed because Rc::get_mut() will return None for shared references (like our empty chunk)
             if self.active[active_idx].is_some() && Rc::ptr_eq(self.active[active_idx].as_ref().unwrap(), &self.empty_entry) {
@@ -184,47 +531,62 @@ impl Scene {
             }
 
 
-            if let Some((active_chunk, (active_lod, active_mesh_buffer))) = self.get_active_mut(&chunk_coord){
+            if let Some((active_chunk, (active_lod, _))) = self.active[active_idx].as_mut().and_then(Rc::get_mut) {
                 // chunk is present -> check if LOD needs to be updated
                 if !active_chunk.is_empty() && *active_lod != required_lod {
                     // println!("Updating LOD for Chunk {:?} from {} to {}", chunk_coord, *active_lod, required_lod);
 
-                    let mut new_mesh = active_chunk.get_mesh(required_lod);
-                    new_mesh.offset_vertices_by(&chunk_coord);
-                    used_compute_budget += 1;
-
-                    (*active_lod, *active_mesh_buffer) = (required_lod, new_mesh.upload(device));
+                    // only chunks at LOD 0 are lit
+                    *active_lod = required_lod;
+                    if required_lod == 0 {
+                        self.light_chunk(&chunk_coord);
+                    } else {
+                        active_chunk.clear_light();
+                    }
+                    used_compute_budget += self.remesh_with_light(&chunk_coord, device, queue);
                 }
 
             } else {
                 // log_1(&format!("self.active at {:?} is None", chunk_coord).into());
                 // chunk is missing -> generate and mesh it
-                let mut new_chunk = Chunk::new_polulated(&self.density_generator, &chunk_coord);
+                let mut new_chunk = Chunk::new_polulated(&self.generator, &chunk_coord);
                 // let mut new_chunk = Chunk::new_flat(&chunk_coord, Block::Grass);
+                for (block_coord, block) in self.edits.get(&chunk_coord).into_iter().flatten() {
+                    new_chunk.set_block(block_coord, *block, true);
+                }
 
                 // now check whether the new chunk is empty
                 // if empty, use air chunk instance (safes memory and GPU resources)
                 // else compute mesh and upload to gpu
                 let active_idx = self.active_idx(&chunk_coord);
 
-                self.active[active_idx] = if new_chunk.is_empty() {
+                if new_chunk.is_empty() {
                     // log_1(&format!("Re-Using air chunk at {:?}", chunk_coord).into());
                     // instead of generating a new empty chunk, reuse the precomputed empty chunk
-                    Some(self.empty_entry.clone())
+                    self.active[active_idx] = Some(self.empty_entry.clone());
+                    // it still lets the sky into lit chunks around it, or is shaded itself
+                    if required_lod == 0 {
+                        self.light_chunk(&chunk_coord);
+                        used_compute_budget += 1 + self.remesh_light_dirty(device, queue);
+                    }
                 } else {
                     // log_1(&format!("Loading Chunk {:?} at LOD {}", chunk_coord, required_lod).into());
-                    used_compute_budget += 2;
-                    let mut new_mesh = new_chunk.get_mesh(required_lod);
-                    new_mesh.offset_vertices_by(&chunk_coord);
-
-                    Some(Rc::new((new_chunk, (required_lod, new_mesh.upload(device)))))
-                };
+                    self.active[active_idx] = Some(Rc::new((new_chunk, (required_lod, ArenaMesh::empty()))));
+                    if required_lod == 0 {
+                        self.light_chunk(&chunk_coord);
+                    }
+                    used_compute_budget += 1 + self.remesh_with_light(&chunk_coord, device, queue);
+                }
             }
 
             if used_compute_budget >= compute_budget {
                 break;
             }
         }
+
+        if self.dimension.horizon {
+            self.horizon.update((player.0 as i32, player.2 as i32), &self.generator, device, HORIZON_TILES_PER_UPDATE);
+        }
     }
 
 
@@ -303,3 +665,93 @@ impl Scene {
         self.previous_player_chunk_coord = player_chunk_coord;
     }
 }
+
+// Light propagation runs through the lit chunks (see light.rs). Lit chunks are
+// all near the player, far inside the window, so the slots it looks at always
+// hold the chunk at their coordinate.
+impl LightMap for Scene {
+    fn cell(&self, coord: &WorldCoord) -> Option<(Block, Light)> {
+        let entry = self.active[self.active_idx(&coord.to_chunk_coord())].as_ref()?;
+        // air chunks share one entry, under open sky
+        if Rc::ptr_eq(entry, &self.empty_entry) {
+            return Some((Block::Empty, Light::SKY));
+        }
+        let block_coord = coord.to_block_coord();
+        Some((entry.0.get_block(&block_coord), entry.0.get_light(&block_coord)?))
+    }
+
+    fn set_light(&mut self, coord: &WorldCoord, light: Light) {
+        let chunk_coord = coord.to_chunk_coord();
+        let active_idx = self.active_idx(&chunk_coord);
+        let Some(entry) = self.active[active_idx].as_mut() else { return };
+        if Rc::ptr_eq(entry, &self.empty_entry) {
+            if light == Light::SKY {
+                return;
+            }
+            // a shaded air chunk needs an entry of its own to hold its light
+            let mut chunk = Chunk::new_empty();
+            chunk.fill_light(Light::SKY);
+            *entry = Rc::new((chunk, (0, ArenaMesh::empty())));
+        }
+        let Some((chunk, _)) = Rc::get_mut(entry) else { return };
+        let block_coord = coord.to_block_coord();
+        if chunk.get_light(&block_coord) == Some(light) {
+            return;
+        }
+        chunk.set_light(&block_coord, light);
+
+        // the faces of the neighbors look into the border blocks
+        self.light_dirty.insert(chunk_coord);
+        let BlockCoord(x, y, z) = block_coord;
+        for (axis, c) in [x, y, z].into_iter().enumerate() {
+            if c == 0 || c == CHUNK_SIZE as usize - 1 {
+                let mut neighbor = [chunk_coord.0, chunk_coord.1, chunk_coord.2];
+                neighbor[axis] += if c == 0 { -1 } else { 1 };
+                self.light_dirty.insert(ChunkCoord(neighbor[0], neighbor[1], neighbor[2]));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lod_rings_with_hysteresis() {
+        // every ring is reachable for new chunks
+        let lods: Vec<LOD> = [0, 7, 8, 15, 16, 31, 32, 47, 48, 100].iter().map(|&d| select_lod(d, None)).collect();
+        assert_eq!(lods, [0, 0, 1, 1, 2, 2, 3, 3, 4, 4]);
+
+        // a chunk keeps its LOD until it is well past the ring edge, in both directions
+        assert_eq!(select_lod(8, Some(0)), 0);
+        assert_eq!(select_lod(9, Some(0)), 0);
+        assert_eq!(select_lod(10, Some(0)), 1);
+        assert_eq!(select_lod(7, Some(1)), 1);
+        assert_eq!(select_lod(6, Some(1)), 1);
+        assert_eq!(select_lod(5, Some(1)), 0);
+        assert_eq!(select_lod(60, Some(0)), 4);
+    }
+
+    #[test]
+    fn test_edits_round_trip_as_text() {
+        let generator = || WorldGenerator::Noise(VoxelDensityGenerator::with_config(TerrainConfig::default()));
+        let mut scene = Scene::parked([4, 4, 4], generator(), DimensionSettings::overworld());
+        scene.queue_block(&WorldCoord(-3, -10, 17), Block::Torch);
+        scene.queue_block(&WorldCoord(5, 64, -1), Block::Stone);
+        scene.queue_block(&WorldCoord(5, 64, -1), Block::Empty);
+        let text = scene.edits_to_text();
+
+        // only the last edit of a block counts, broken lines are skipped
+        let mut loaded = Scene::parked([4, 4, 4], generator(), DimensionSettings::overworld());
+        loaded.load_edits_text(&format!("{text}1 2\n7 7 7 255\n"));
+        let lines = |text: String| {
+            let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+            lines.sort();
+            lines
+        };
+        assert_eq!(lines(loaded.edits_to_text()), lines(text));
+        let torch = format!("-3 -10 17 {}", Block::Torch as u8);
+        assert_eq!(lines(loaded.edits_to_text()), [torch, "5 64 -1 0".to_string()]);
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+
+use crate::model::world::{Block, CHUNK_SIZE};
+use crate::utils::ChunkCoord;
+
+// ============================================================================
+// VISIBILITY: occlusion culling with a chunk visibility graph
+// ============================================================================
+//
+// Cave culling in the style of Tommaso Checchi's "advanced cave culling":
+//   → at mesh time every chunk flood-fills its non-opaque blocks and records
+//     which of its 6 faces are connected through them (FaceConnections)
+//   → every frame a breadth-first search starts at the camera chunk and steps
+//     into a neighbor only if the chunk it leaves connects the face it was
+//     entered through with the face it leaves through
+//   → the search never steps against a direction it already went (a path
+//     that turns back towards the camera cannot be looked along) and never
+//     enters chunks outside the view frustum
+//
+// Chunks the search does not reach are hidden behind solid rock and are not
+// drawn. The test is conservative: a chunk is only culled if no path of open
+// chunk faces leads to it.
+// ============================================================================
+
+/// Face directions in the order of the mesher (+X, -X, +Y, -Y, +Z, -Z)
+pub const FACE_OFFSETS: [(isize, isize, isize); 6] = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+
+/// Face on the other side of the chunk
+pub fn opposite_face(face: usize) -> usize {
+    face ^ 1
+}
+
+/// Which pairs of chunk faces are connected through non-opaque blocks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FaceConnections(u16);
+
+impl FaceConnections {
+    /// Open chunks (air, not yet loaded): everything connects
+    pub const ALL: FaceConnections = FaceConnections(u16::MAX >> 1);
+    pub const NONE: FaceConnections = FaceConnections(0);
+
+    /// Bit of a face pair, 15 pairs of 6 faces
+    fn bit(a: usize, b: usize) -> u16 {
+        let (a, b) = (a.min(b), a.max(b));
+        // pairs (0,1)..(0,5), (1,2)..(1,5), ...
+        let index = a * 5 - a * (a.saturating_sub(1)) / 2 + (b - a - 1);
+        1 << index
+    }
+
+    pub fn connects(self, a: usize, b: usize) -> bool {
+        a == b || self.0 & Self::bit(a, b) != 0
+    }
+
+    /// Flood-fill the non-opaque blocks of a chunk (`block(x, y, z)`)
+    pub fn compute(block: impl Fn(usize, usize, usize) -> Block) -> Self {
+        let size = CHUNK_SIZE as usize;
+        let index = |x: usize, y: usize, z: usize| x + y * size + z * size * size;
+        let mut seen = vec![false; size * size * size];
+        let mut connections = 0u16;
+        let mut stack = Vec::new();
+
+        for start in 0..seen.len() {
+            let (x, y, z) = (start % size, start / size % size, start / (size * size));
+            if seen[start] || block(x, y, z).is_opaque() {
+                continue;
+            }
+
+            // faces touched by this region of open blocks
+            let mut faces = 0u8;
+            seen[start] = true;
+            stack.push((x, y, z));
+            while let Some((x, y, z)) = stack.pop() {
+                for (face, &(dx, dy, dz)) in FACE_OFFSETS.iter().enumerate() {
+                    let (nx, ny, nz) = (x as isize + dx, y as isize + dy, z as isize + dz);
+                    let outside = |c: isize| !(0..CHUNK_SIZE).contains(&c);
+                    if outside(nx) || outside(ny) || outside(nz) {
+                        faces |= 1 << face;
+                        continue;
+                    }
+                    let (nx, ny, nz) = (nx as usize, ny as usize, nz as usize);
+                    if !seen[index(nx, ny, nz)] && !block(nx, ny, nz).is_opaque() {
+                        seen[index(nx, ny, nz)] = true;
+                        stack.push((nx, ny, nz));
+                    }
+                }
+            }
+
+            for a in 0..6 {
+                for b in a + 1..6 {
+                    if faces & (1 << a) != 0 && faces & (1 << b) != 0 {
+                        connections |= Self::bit(a, b);
+                    }
+                }
+            }
+        }
+        FaceConnections(connections)
+    }
+}
+
+/// Breadth-first search over chunks from the camera chunk
+///
+/// `connections` gives the graph of a chunk, `in_view` whether a chunk may be
+/// entered (loaded window and view frustum) and `visit` marks a chunk as
+/// visible, returning false if it already was.
+pub fn flood_visible(
+    start: ChunkCoord,
+    connections: impl Fn(&ChunkCoord) -> FaceConnections,
+    in_view: impl Fn(&ChunkCoord) -> bool,
+    mut visit: impl FnMut(&ChunkCoord) -> bool,
+) {
+    // (chunk, face it was entered through, directions travelled so far)
+    let mut queue = VecDeque::new();
+    visit(&start);
+    queue.push_back((start, None, 0u8));
+
+    while let Some((coord, entered, directions)) = queue.pop_front() {
+        let graph = connections(&coord);
+        for (face, &(dx, dy, dz)) in FACE_OFFSETS.iter().enumerate() {
+            if directions & (1 << opposite_face(face)) != 0 {
+                continue;
+            }
+            if entered.is_some_and(|entered| !graph.connects(entered, face)) {
+                continue;
+            }
+            let next = ChunkCoord(coord.0 + dx, coord.1 + dy, coord.2 + dz);
+            if in_view(&next) && visit(&next) {
+                queue.push_back((next, Some(opposite_face(face)), directions | 1 << face));
+            }
+        }
+    }
+}
+
+/// Meshed chunks by what the culling did with them in the last frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CullStats {
+    pub drawn: usize,
+    /// In the view frustum, but hidden behind opaque chunks
+    pub occluded: usize,
+    pub outside_frustum: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_cave_culling_stops_at_solid_rock() {
+        // a chunk with a tunnel along x connects only -X and +X
+        let tunnel = FaceConnections::compute(|_, y, z| if y == 8 && z == 8 { Block::Empty } else { Block::Stone });
+        assert!(tunnel.connects(0, 1));
+        assert!(!tunnel.connects(0, 2) && !tunnel.connects(3, 4));
+        assert_eq!(FaceConnections::compute(|_, _, _| Block::Stone), FaceConnections::NONE);
+        assert_eq!(FaceConnections::compute(|_, _, _| Block::Empty), FaceConnections::ALL);
+
+        // camera in open air at x = 0, two chunks of rock at x = 2..4 hide everything
+        // behind them, the tunnel at (2, 0, 0) only leads into more rock
+        let connections = |c: &ChunkCoord| match c {
+            ChunkCoord(2, 0, 0) => tunnel,
+            ChunkCoord(2 | 3, _, _) => FaceConnections::NONE,
+            _ => FaceConnections::ALL,
+        };
+        let in_view = |c: &ChunkCoord| (0..6).contains(&c.0) && (-2..=2).contains(&c.1) && (-2..=2).contains(&c.2);
+        let mut visible = HashSet::new();
+        flood_visible(ChunkCoord(0, 0, 0), connections, in_view, |c| visible.insert(*c));
+
+        assert!(visible.contains(&ChunkCoord(2, 1, 0)), "the rock itself is seen");
+        assert!(visible.contains(&ChunkCoord(3, 0, 0)), "seen through the tunnel");
+        assert!(!visible.contains(&ChunkCoord(3, 1, 0)));
+        assert!(!visible.contains(&ChunkCoord(4, 0, 0)) && !visible.contains(&ChunkCoord(5, -2, 2)));
+    }
+}
@@ -0,0 +1,220 @@
+// biome.rs - Smooth biome transitions
+//
+// `get_biome_type` classifies a single column. Used directly, every biome
+// border becomes a hard line (sand next to grass, flat next to steep).
+// This module turns those hard classifications into weights:
+//
+//   1. BiomeBlender samples `get_biome_type` on a coarse, world-aligned lattice
+//   2. weights_at() averages all lattice samples within `biome_blend_radius`
+//      using a tent filter, so every column gets a mix of nearby biomes
+//   3. Height parameters and tints are blended with these weights
+//   4. Surface materials pick ONE biome per column, randomly proportional to
+//      the weights (jittered transition instead of a straight line)
+//
+// The lattice is aligned to world coordinates, so a column always gets the
+// same weights no matter which chunk asks for it (seamless chunk borders).
+
+use super::biome_def::BiomeId;
+use super::terrain::{hash01, VoxelDensityGenerator};
+
+/// Distance (in blocks) between two biome lattice samples
+const BIOME_SAMPLE_SPACING: i32 = 4;
+
+/// Blend weights of all biomes influencing a single column (weights sum to 1)
+#[derive(Clone, Debug, Default)]
+pub struct BiomeWeights {
+    entries: Vec<(BiomeId, f32)>,
+}
+
+impl BiomeWeights {
+    /// Weights for a column that is fully inside one biome
+    pub fn single(biome: BiomeId) -> Self {
+        Self { entries: vec![(biome, 1.0)] }
+    }
+
+    /// Add weight for a biome (accumulates if the biome is already present)
+    pub fn add(&mut self, biome: BiomeId, weight: f32) {
+        if let Some(entry) = self.entries.iter_mut().find(|(b, _)| *b == biome) {
+            entry.1 += weight;
+        } else {
+            self.entries.push((biome, weight));
+        }
+    }
+
+    /// Scale all weights so they sum to 1
+    pub fn normalize(&mut self) {
+        let total: f32 = self.entries.iter().map(|(_, w)| w).sum();
+        if total > 0.0 {
+            for (_, w) in self.entries.iter_mut() {
+                *w /= total;
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(BiomeId, f32)> {
+        self.entries.iter()
+    }
+
+    /// Weight of a single biome (0 if it does not influence this column)
+    pub fn get(&self, biome: BiomeId) -> f32 {
+        self.entries.iter().find(|(b, _)| *b == biome).map_or(0.0, |(_, w)| *w)
+    }
+
+    /// Biome with the highest weight
+    pub fn dominant(&self) -> BiomeId {
+        self.entries
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(BiomeId::default(), |(b, _)| *b)
+    }
+
+    /// Weighted average of a per-biome scalar parameter
+    pub fn blend(&self, f: impl Fn(BiomeId) -> f32) -> f32 {
+        self.entries.iter().map(|(b, w)| f(*b) * w).sum()
+    }
+
+    /// Weighted average of a per-biome color
+    pub fn blend_color(&self, f: impl Fn(BiomeId) -> [f32; 3]) -> [f32; 3] {
+        let mut out = [0.0; 3];
+        for (b, w) in self.entries.iter() {
+            let c = f(*b);
+            for i in 0..3 {
+                out[i] += c[i] * w;
+            }
+        }
+        out
+    }
+
+    /// Pick one biome with probability proportional to its weight
+    ///
+    /// `r` is a random value in [0, 1). `jitter` controls how ragged the border is:
+    /// 0 = always the dominant biome (hard edge), 1 = fully proportional.
+    pub fn pick(&self, r: f32, jitter: f32) -> BiomeId {
+        if jitter <= 0.0 || self.entries.len() == 1 {
+            return self.dominant();
+        }
+
+        // sharpen weights for jitter < 1 so the transition band gets narrower
+        let exponent = 1.0 / jitter.min(1.0);
+        let total: f32 = self.entries.iter().map(|(_, w)| w.powf(exponent)).sum();
+        let mut threshold = r * total;
+        for (b, w) in self.entries.iter() {
+            threshold -= w.powf(exponent);
+            if threshold < 0.0 {
+                return *b;
+            }
+        }
+        self.dominant()
+    }
+}
+
+/// Coarse biome lattice around an area (usually one chunk) for cheap blending
+pub struct BiomeBlender {
+    /// World (x, z) of the first lattice sample
+    origin: (i32, i32),
+    spacing: i32,
+    size: usize,
+    samples: Vec<BiomeId>,
+    radius: f32,
+}
+
+impl BiomeBlender {
+    /// Sample biomes for the area [min_x, min_x + extent) x [min_z, min_z + extent)
+    /// plus the blend radius around it
+    pub fn new(generator: &VoxelDensityGenerator, min_x: i32, min_z: i32, extent: i32) -> Self {
+        let radius = generator.config.biome_blend_radius.max(0.0);
+
+        // without blending every column is sampled exactly (hard classification)
+        let spacing = if radius > 0.0 { BIOME_SAMPLE_SPACING } else { 1 };
+        let margin = radius.ceil() as i32;
+
+        // align lattice to world coordinates so results don't depend on the area
+        let origin = (
+            (min_x - margin).div_euclid(spacing) * spacing,
+            (min_z - margin).div_euclid(spacing) * spacing,
+        );
+        // enough samples to reach past the last column plus margin on both axes
+        let span = (min_x - origin.0).max(min_z - origin.1) + extent + margin;
+        let size = (span / spacing + 2) as usize;
+
+        let mut samples = Vec::with_capacity(size * size);
+        for iz in 0..size {
+            for ix in 0..size {
+                let wx = origin.0 + ix as i32 * spacing;
+                let wz = origin.1 + iz as i32 * spacing;
+                samples.push(generator.get_biome_type(wx as f32, wz as f32, 30.0));
+            }
+        }
+
+        Self { origin, spacing, size, samples, radius }
+    }
+
+    /// Blend weights for a world column inside the sampled area
+    pub fn weights_at(&self, wx: i32, wz: i32) -> BiomeWeights {
+        if self.radius <= 0.0 {
+            return BiomeWeights::single(self.sample(wx - self.origin.0, wz - self.origin.1));
+        }
+
+        let r = self.radius.ceil() as i32;
+        let first_x = (wx - r - self.origin.0).div_euclid(self.spacing).max(0);
+        let last_x = ((wx + r - self.origin.0).div_euclid(self.spacing)).min(self.size as i32 - 1);
+        let first_z = (wz - r - self.origin.1).div_euclid(self.spacing).max(0);
+        let last_z = ((wz + r - self.origin.1).div_euclid(self.spacing)).min(self.size as i32 - 1);
+
+        let mut weights = BiomeWeights::default();
+        for iz in first_z..=last_z {
+            for ix in first_x..=last_x {
+                let dx = (self.origin.0 + ix * self.spacing - wx) as f32;
+                let dz = (self.origin.1 + iz * self.spacing - wz) as f32;
+                let dist = (dx * dx + dz * dz).sqrt();
+
+                // tent filter: full weight at the column, zero at the blend radius
+                let weight = 1.0 - dist / self.radius;
+                if weight > 0.0 {
+                    weights.add(self.samples[ix as usize + iz as usize * self.size], weight);
+                }
+            }
+        }
+
+        if weights.entries.is_empty() {
+            return BiomeWeights::single(self.sample(wx - self.origin.0, wz - self.origin.1));
+        }
+        weights.normalize();
+        weights
+    }
+
+    /// Lattice sample closest to a lattice-relative position
+    fn sample(&self, dx: i32, dz: i32) -> BiomeId {
+        let ix = ((dx + self.spacing / 2).div_euclid(self.spacing)).clamp(0, self.size as i32 - 1);
+        let iz = ((dz + self.spacing / 2).div_euclid(self.spacing)).clamp(0, self.size as i32 - 1);
+        self.samples[ix as usize + iz as usize * self.size]
+    }
+}
+
+/// Deterministic per-column random value in [0, 1) used for jittered transitions
+pub fn column_random(wx: i32, wz: i32) -> f32 {
+    hash01(wx.wrapping_add(7919), wz.wrapping_sub(104729))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blend_weights_are_seamless() {
+        let generator = VoxelDensityGenerator::new();
+
+        // the same column seen from two neighboring chunks must get the same weights
+        let left = BiomeBlender::new(&generator, 0, 0, 16);
+        let right = BiomeBlender::new(&generator, 16, 0, 16);
+        for z in 0..16 {
+            let a = left.weights_at(15, z);
+            let b = right.weights_at(15, z);
+            let total: f32 = a.iter().map(|(_, w)| w).sum();
+            assert!((total - 1.0).abs() < 1e-4, "weights must be normalized");
+            for (biome, w) in a.iter() {
+                assert!((b.get(*biome) - w).abs() < 1e-4, "weights differ across chunk border");
+            }
+        }
+    }
+}
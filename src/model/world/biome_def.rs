@@ -0,0 +1,445 @@
+// ============================================================================
+// BIOME DEFINITIONS
+// ============================================================================
+//
+// Everything that makes a biome is data, loaded from small TOML files (see
+// biomes/*.toml for the built-in set):
+//   → climate ranges: where the biome appears (temperature, humidity,
+//     continentalness, erosion, lake noise, height and the coast zone)
+//   → terrain shape, surface and subsurface block rules
+//   → tree type weights, plant table and surface decorations
+//   → tint, map, fog and sky colors
+//
+// The generator only samples the climate and asks the BiomeRegistry which
+// biome matches, so new biomes are added by loading another definition.
+//
+// File format: TOML, deserialized into BiomeDefinition. Each `[[climate]]`
+// table is one climate range; a biome may have several. All ranges of all
+// biomes are checked by ascending `priority`, the first one containing the
+// climate sample wins.
+//
+// Block rules are arrays of tables, the first rule whose ranges all contain
+// the sample picks the block:
+//   surface = [
+//       { block = "snow", y = [20, inf], variety = [-inf, -0.2] },
+//       { block = "moss" },
+//   ]
+// Rules may limit `y` (world height), `variety` (a per-biome noise, see
+// `variety`) and `noise` (the plant noise). Like climate ranges they are open
+// intervals, a missing range holds everywhere. Trees and decorations are
+// tables of relative weights, e.g. `trees = { oak = 0.4, birch = 0.6 }`.
+// ============================================================================
+
+use std::cell::OnceCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Deserializer};
+
+use super::block::Block;
+use super::decorations::Decoration;
+use super::trees::TreeType;
+
+/// Built-in biome definitions, in id order
+pub const BUILTIN_BIOMES: &[&str] = &[
+    include_str!("biomes/ocean.toml"),
+    include_str!("biomes/deep_ocean.toml"),
+    include_str!("biomes/beach.toml"),
+    include_str!("biomes/plain.toml"),
+    include_str!("biomes/forest.toml"),
+    include_str!("biomes/mountain.toml"),
+    include_str!("biomes/tundra.toml"),
+    include_str!("biomes/desert.toml"),
+    include_str!("biomes/cliff.toml"),
+    include_str!("biomes/lake.toml"),
+    include_str!("biomes/jungle.toml"),
+    include_str!("biomes/frozen_ocean.toml"),
+];
+
+/// Index of a biome in its BiomeRegistry
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BiomeId(pub u8);
+
+/// Coast zone of a column, derived from continentalness and the ocean thresholds
+/// of the terrain config (so presets can move the coast line)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Zone {
+    DeepOcean,
+    Ocean,
+    Coast,
+    Land,
+}
+
+/// Climate sample of a position, matched against ClimateRange
+#[derive(Clone, Copy, Debug)]
+pub struct Climate {
+    pub zone: Zone,
+    pub temperature: f32,
+    pub humidity: f32,
+    pub continentalness: f32,
+    pub erosion: f32,
+    pub lake: f32,
+    pub height: f32,
+}
+
+/// One region of the climate space where a biome appears
+///
+/// Ranges are open intervals (min, max), infinite by default.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClimateRange {
+    pub priority: i32,
+    pub zone: Zone,
+    pub temperature: (f32, f32),
+    pub humidity: (f32, f32),
+    pub continentalness: (f32, f32),
+    pub erosion: (f32, f32),
+    pub lake: (f32, f32),
+    pub height: (f32, f32),
+}
+
+/// Open range containing every value
+const ALL: (f32, f32) = (f32::NEG_INFINITY, f32::INFINITY);
+
+fn inside((min, max): (f32, f32), v: f32) -> bool {
+    v > min && v < max
+}
+
+impl Default for ClimateRange {
+    fn default() -> Self {
+        Self {
+            priority: 100,
+            zone: Zone::Land,
+            temperature: ALL,
+            humidity: ALL,
+            continentalness: ALL,
+            erosion: ALL,
+            lake: ALL,
+            height: ALL,
+        }
+    }
+}
+
+impl ClimateRange {
+    pub fn contains(&self, climate: &Climate) -> bool {
+        self.zone == climate.zone
+            && inside(self.temperature, climate.temperature)
+            && inside(self.humidity, climate.humidity)
+            && inside(self.continentalness, climate.continentalness)
+            && inside(self.erosion, climate.erosion)
+            && inside(self.lake, climate.lake)
+            && inside(self.height, climate.height)
+    }
+}
+
+/// Block rule: the block and the ranges its variables must lie in
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockRule {
+    #[serde(deserialize_with = "named")]
+    pub block: Block,
+    #[serde(default = "all")]
+    pub y: (f32, f32),
+    #[serde(default = "all")]
+    pub variety: (f32, f32),
+    #[serde(default = "all")]
+    pub noise: (f32, f32),
+}
+
+impl BlockRule {
+    /// Rule that always picks a block
+    pub const fn always(block: Block) -> Self {
+        Self { block, y: ALL, variety: ALL, noise: ALL }
+    }
+}
+
+fn all() -> (f32, f32) {
+    ALL
+}
+
+/// Ordered block rules, the first rule whose ranges hold picks the block
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct BlockRules(pub Vec<BlockRule>);
+
+impl BlockRules {
+    /// Block of the first matching rule; `variety` is only evaluated if a rule needs it
+    pub fn select(&self, y: f32, variety: impl Fn() -> f32, noise: f32) -> Option<Block> {
+        let cached = OnceCell::new();
+        self.0
+            .iter()
+            .find(|rule| {
+                inside(rule.y, y)
+                    && inside(rule.noise, noise)
+                    && (rule.variety == ALL || inside(rule.variety, *cached.get_or_init(&variety)))
+            })
+            .map(|rule| rule.block)
+    }
+}
+
+/// Everything the generator needs to know about a biome
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BiomeDefinition {
+    pub name: String,
+    pub climate: Vec<ClimateRange>,
+    /// Terrain shape as (amplitude, offset), see blended_terrain_height
+    pub height_shape: (f32, f32),
+    /// Scale and frequency of the variety noise used by the block rules
+    pub variety: (f32, f32),
+    pub surface: BlockRules,
+    pub subsurface: BlockRules,
+    /// Tree types with relative weights, empty for a biome without trees
+    #[serde(deserialize_with = "weights")]
+    pub trees: Vec<(TreeType, f32)>,
+    pub plants: BlockRules,
+    /// Decorations with their chance per decoration cell (coverage for column decorations)
+    #[serde(deserialize_with = "weights")]
+    pub decorations: Vec<(Decoration, f32)>,
+    /// Color multiplier for grass, leaves and plants
+    pub tint: [f32; 3],
+    /// Color on maps (terrain preview, biome color maps)
+    pub map_color: [u8; 3],
+    pub fog_color: [f32; 3],
+    pub sky_color: [f32; 3],
+}
+
+impl Default for BiomeDefinition {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            climate: Vec::new(),
+            height_shape: (1.0, 0.0),
+            variety: (0.3, 0.01),
+            surface: BlockRules(vec![BlockRule::always(Block::Grass)]),
+            subsurface: BlockRules(vec![BlockRule::always(Block::Stone)]),
+            trees: Vec::new(),
+            plants: BlockRules::default(),
+            decorations: Vec::new(),
+            tint: [1.0, 1.0, 1.0],
+            map_color: [128, 128, 128],
+            fog_color: [0.75, 0.85, 0.95],
+            sky_color: [0.5, 0.8, 1.0],
+        }
+    }
+}
+
+impl BiomeDefinition {
+    /// Parse a biome file; keys missing from the file keep their default
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let biome: Self = toml::from_str(text).map_err(|e| e.to_string())?;
+        if biome.name.is_empty() {
+            return Err("biome without name".to_string());
+        }
+        if biome.climate.is_empty() {
+            return Err(format!("biome '{}' has no [[climate]] range", biome.name));
+        }
+        Ok(biome)
+    }
+
+    /// Whether any climate range of this biome lies in a zone
+    pub fn in_zone(&self, zone: Zone) -> bool {
+        self.climate.iter().any(|range| range.zone == zone)
+    }
+
+    /// Water filling the biome's basins up to sea level: sea water if any climate
+    /// range reaches the coast or the sea, lake water for inland biomes
+    pub fn water(&self) -> Block {
+        if self.in_zone(Zone::Land) && self.climate.iter().all(|range| range.zone == Zone::Land) {
+            Block::LakeWater
+        } else {
+            Block::Water
+        }
+    }
+
+    /// Tree type for a random value in [0, 1), None for a biome without trees
+    pub fn pick_tree(&self, r: f32) -> Option<TreeType> {
+        let total: f32 = self.trees.iter().map(|(_, w)| w).sum();
+        let mut threshold = r * total;
+        for (tree, weight) in &self.trees {
+            if threshold < *weight {
+                return Some(*tree);
+            }
+            threshold -= weight;
+        }
+        self.trees.last().map(|(tree, _)| *tree)
+    }
+}
+
+/// Set of loaded biome definitions, indexed by BiomeId
+#[derive(Clone, Debug)]
+pub struct BiomeRegistry {
+    definitions: Vec<BiomeDefinition>,
+    /// All climate ranges as (biome, range index), by ascending priority
+    order: Vec<(BiomeId, usize)>,
+}
+
+thread_local! {
+    static BUILTIN: Rc<BiomeRegistry> = Rc::new(
+        BiomeRegistry::from_toml(BUILTIN_BIOMES).expect("built-in biome definitions are valid"),
+    );
+}
+
+impl BiomeRegistry {
+    /// Registry of the built-in biomes (shared, parsed once per thread)
+    pub fn builtin() -> Rc<Self> {
+        BUILTIN.with(Rc::clone)
+    }
+
+    /// Parse a set of biome files, ids follow the order of the files
+    pub fn from_toml(files: &[&str]) -> Result<Self, String> {
+        let definitions = files
+            .iter()
+            .map(|text| BiomeDefinition::from_toml(text))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(definitions)
+    }
+
+    pub fn new(definitions: Vec<BiomeDefinition>) -> Result<Self, String> {
+        if definitions.is_empty() || definitions.len() > u8::MAX as usize + 1 {
+            return Err(format!("need 1 to 256 biomes, got {}", definitions.len()));
+        }
+        for (i, biome) in definitions.iter().enumerate() {
+            if definitions[..i].iter().any(|other| other.name == biome.name) {
+                return Err(format!("duplicate biome '{}'", biome.name));
+            }
+        }
+
+        let mut order: Vec<(BiomeId, usize)> = definitions
+            .iter()
+            .enumerate()
+            .flat_map(|(id, biome)| (0..biome.climate.len()).map(move |r| (BiomeId(id as u8), r)))
+            .collect();
+        // stable sort: equal priorities keep the file order
+        order.sort_by_key(|(id, r)| definitions[id.0 as usize].climate[*r].priority);
+        Ok(Self { definitions, order })
+    }
+
+    /// Parse a biome file and add it with with_definition
+    pub fn with_toml(self, text: &str) -> Result<Self, String> {
+        self.with_definition(BiomeDefinition::from_toml(text)?)
+    }
+
+    /// Add a biome, or replace the one with the same name (ids of others are kept)
+    pub fn with_definition(mut self, biome: BiomeDefinition) -> Result<Self, String> {
+        match self.definitions.iter().position(|b| b.name == biome.name) {
+            Some(i) => self.definitions[i] = biome,
+            None => self.definitions.push(biome),
+        }
+        Self::new(self.definitions)
+    }
+
+    pub fn get(&self, id: BiomeId) -> &BiomeDefinition {
+        &self.definitions[id.0 as usize]
+    }
+
+    pub fn id(&self, name: &str) -> Option<BiomeId> {
+        self.definitions.iter().position(|b| b.name == name).map(|i| BiomeId(i as u8))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BiomeId, &BiomeDefinition)> {
+        self.definitions.iter().enumerate().map(|(i, b)| (BiomeId(i as u8), b))
+    }
+
+    /// Biome of the first climate range (by priority) containing the sample,
+    /// the first biome if none does
+    pub fn classify(&self, climate: &Climate) -> BiomeId {
+        self.order
+            .iter()
+            .find(|(id, r)| self.get(*id).climate[*r].contains(climate))
+            .map_or(BiomeId(0), |(id, _)| *id)
+    }
+
+    /// Biome whose map color is closest to a color (e.g. a pixel of a painted biome map)
+    pub fn nearest_map_color(&self, rgb: [u8; 3]) -> BiomeId {
+        let distance = |biome: &BiomeDefinition| {
+            (0..3).map(|i| (biome.map_color[i] as i32 - rgb[i] as i32).pow(2)).sum::<i32>()
+        };
+        self.iter().min_by_key(|(_, biome)| distance(biome)).map_or(BiomeId(0), |(id, _)| id)
+    }
+}
+
+/// Types referred to by name in biome files
+trait Named: Sized {
+    const KIND: &'static str;
+    fn from_name(name: &str) -> Option<Self>;
+}
+
+impl Named for Block {
+    const KIND: &'static str = "block";
+    fn from_name(name: &str) -> Option<Self> {
+        Block::from_name(name)
+    }
+}
+
+impl Named for TreeType {
+    const KIND: &'static str = "tree type";
+    fn from_name(name: &str) -> Option<Self> {
+        TreeType::from_name(name)
+    }
+}
+
+impl Named for Decoration {
+    const KIND: &'static str = "decoration";
+    fn from_name(name: &str) -> Option<Self> {
+        Decoration::from_name(name)
+    }
+}
+
+fn named<'de, D: Deserializer<'de>, T: Named>(deserializer: D) -> Result<T, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    T::from_name(&name).ok_or_else(|| serde::de::Error::custom(format!("unknown {} '{name}'", T::KIND)))
+}
+
+/// `{ oak = 0.4, birch = 0.6 }`, in file order
+fn weights<'de, D: Deserializer<'de>, T: Named>(deserializer: D) -> Result<Vec<(T, f32)>, D::Error> {
+    toml::Table::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, weight)| {
+            let item = T::from_name(&name).ok_or_else(|| format!("unknown {} '{name}'", T::KIND))?;
+            let weight = match weight {
+                toml::Value::Float(w) => w as f32,
+                toml::Value::Integer(w) => w as f32,
+                other => return Err(format!("invalid {} weight {other} for '{name}'", T::KIND)),
+            };
+            Ok((item, weight))
+        })
+        .collect::<Result<_, String>>()
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_biome_definitions_parse() {
+        let registry = BiomeRegistry::builtin();
+        assert_eq!(registry.iter().count(), BUILTIN_BIOMES.len());
+        let forest = registry.get(registry.id("forest").unwrap());
+        assert_eq!(forest.pick_tree(0.1), Some(TreeType::Oak));
+        assert_eq!(forest.pick_tree(0.9), Some(TreeType::Birch));
+
+        let tundra = registry.get(registry.id("tundra").unwrap());
+        assert_eq!(tundra.surface.select(70.0, || 0.0, 0.0), Some(Block::Snow));
+        assert_eq!(tundra.surface.select(30.0, || -0.5, 0.0), Some(Block::Snow));
+        assert_eq!(tundra.surface.select(30.0, || 0.0, 0.0), Some(Block::Moss));
+
+        // a mod biome slots in between existing ones by priority
+        let volcano = "name = \"volcano\"\nsurface = [{ block = \"basalt\" }]\n[[climate]]\npriority = 45\nheight = [90, inf]\n";
+        let registry = (*registry).clone().with_toml(volcano).unwrap();
+        let climate = Climate {
+            zone: Zone::Land,
+            temperature: 0.0,
+            humidity: 0.0,
+            continentalness: 0.5,
+            erosion: 0.0,
+            lake: 0.0,
+            height: 120.0,
+        };
+        assert_eq!(registry.get(registry.classify(&climate)).name, "volcano");
+        assert!(BiomeDefinition::from_toml("name = \"x\"\nsurface = [{ block = \"unobtanium\" }]\n[[climate]]\n").is_err());
+        assert!(BiomeDefinition::from_toml("name = \"x\"\ntrees = { oak = 1, palm = 1 }\n[[climate]]\n").is_err());
+        assert!(BiomeDefinition::from_toml("name = \"x\"\n[[climate]]\nheigth = [0, 10]\n").is_err());
+    }
+}
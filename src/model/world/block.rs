@@ -45,9 +45,35 @@ pub enum Block {
     // Cliff blocks
     Basalt = 38,
     BlackStone = 39,
+    // Ocean plants
+    Kelp = 40,
+    // Cave blocks
+    CaveVines = 41,
+    Dripstone = 42,
+    PointedDripstone = 43,
+    Magma = 44,
+    // Giant jungle trees
+    JungleLeaves = 45,
+    JungleWood = 46,
+    // Surface decorations
+    MossyCobblestone = 47,
+    // Dimension portals
+    Portal = 48,
+    SkyPortal = 49,
+    // Light sources
+    Torch = 50,
+    Lantern = 51,
+    Glowstone = 52,
+    Lava = 53,
 }
 
 impl Block {
+    /// Number of block types (all ids are below this)
+    pub const COUNT: usize = 54;
+
+    /// Block ids the color palette of the chunk shader has room for (see chunk.wgsl)
+    pub const PALETTE_SIZE: usize = 64;
+
     pub fn from_u8(v: u8) -> Self {
         match v {
             0 => Block::Empty,
@@ -90,6 +116,20 @@ impl Block {
             37 => Block::LakeWater,
             38 => Block::Basalt,
             39 => Block::BlackStone,
+            40 => Block::Kelp,
+            41 => Block::CaveVines,
+            42 => Block::Dripstone,
+            43 => Block::PointedDripstone,
+            44 => Block::Magma,
+            45 => Block::JungleLeaves,
+            46 => Block::JungleWood,
+            47 => Block::MossyCobblestone,
+            48 => Block::Portal,
+            49 => Block::SkyPortal,
+            50 => Block::Torch,
+            51 => Block::Lantern,
+            52 => Block::Glowstone,
+            53 => Block::Lava,
             _ => Block::Empty,
         }
     }
@@ -97,15 +137,85 @@ impl Block {
     pub fn to_u8(self) -> u8 {
         self as u8
     }
+
+    /// Name of the block in data files: snake case of the variant (`BlackStone` → `black_stone`)
+    pub fn name(self) -> String {
+        let mut name = String::new();
+        for (i, c) in format!("{self:?}").chars().enumerate() {
+            if c.is_ascii_uppercase() && i > 0 && !name.ends_with('_') {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        }
+        name
+    }
+
+    /// Block by its data file name (see `name`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        (0..Self::COUNT as u8).map(Self::from_u8).find(|block| block.name() == name)
+    }
     
     pub fn is_empty(self) -> bool {
         self == Block::Empty
     }
 
     pub fn is_solid(self) -> bool {
-        !matches!(self, Block::Empty | Block::Water | Block::Cloud)
+        !matches!(self, Block::Empty | Block::Cloud) && !self.is_liquid() && !self.is_portal()
+    }
+
+    /// Solid and not see-through like ice: chunk visibility stops here (see model/visibility.rs)
+    pub fn is_opaque(self) -> bool {
+        self.is_solid() && self.color(2)[3] >= 1.0
+    }
+
+    /// Portal blocks move the player to another dimension (see model/dimension.rs)
+    pub fn is_portal(self) -> bool {
+        matches!(self, Block::Portal | Block::SkyPortal)
+    }
+
+    /// Air and small plants, overgrown by trees and decorations (see trees.rs, decorations.rs)
+    pub fn is_open(self) -> bool {
+        matches!(
+            self,
+            Block::Empty | Block::Grass_Short | Block::Grass_Tall | Block::RedFlower |
+            Block::YellowFlower | Block::DeadBush
+        )
+    }
+
+    /// Sea water (`Water`, at and below sea level), inland `LakeWater` and `Lava`
+    pub fn is_liquid(self) -> bool {
+        matches!(self, Block::Water | Block::LakeWater | Block::Lava)
     }
     
+    /// Block light level the block glows with (see model/light.rs)
+    pub fn light_emission(self) -> u8 {
+        match self {
+            Block::Torch => 14,
+            Block::Lantern | Block::Glowstone | Block::Lava => 15,
+            Block::Magma => 6,
+            _ => 0,
+        }
+    }
+
+    /// Color of the block light the block glows with
+    pub fn light_color(self) -> [f32; 3] {
+        match self {
+            Block::Torch => [1.0, 0.75, 0.45],
+            Block::Lantern => [1.0, 0.85, 0.6],
+            Block::Glowstone => [1.0, 0.95, 0.75],
+            Block::Lava | Block::Magma => [1.0, 0.55, 0.25],
+            _ => [1.0, 1.0, 1.0],
+        }
+    }
+
+    /// Health the player loses per second inside the block
+    pub fn damage_per_second(self) -> f32 {
+        match self {
+            Block::Lava => 8.0,
+            _ => 0.0,
+        }
+    }
+
     pub fn color(self, face_dir: u8) -> [f32; 4] {
         match self {
             Block::Empty => [0.0, 0.0, 0.0, 1.0],
@@ -153,8 +263,81 @@ impl Block {
             Block::LakeWater => [0.0, 0.15, 0.5, 1.0],
             Block::Basalt => [0.3, 0.3, 0.35, 1.0],
             Block::BlackStone => [0.25, 0.25, 0.28, 1.0],
+            Block::Kelp => [0.15, 0.4, 0.15, 1.0],
+            Block::CaveVines => [0.3, 0.55, 0.2, 1.0],
+            Block::Dripstone => [0.55, 0.45, 0.38, 1.0],
+            Block::PointedDripstone => [0.62, 0.52, 0.44, 1.0],
+            Block::Magma => [0.75, 0.3, 0.1, 1.0],
+            Block::JungleLeaves => [0.15, 0.5, 0.12, 1.0],
+            Block::JungleWood => [0.45, 0.33, 0.18, 1.0],
+            Block::MossyCobblestone => [0.4, 0.48, 0.36, 1.0],
+            Block::Portal => [0.55, 0.2, 0.85, 0.75],
+            Block::SkyPortal => [0.95, 0.85, 0.45, 0.75],
+            Block::Torch => [1.0, 0.7, 0.3, 1.0],
+            Block::Lantern => [0.95, 0.8, 0.5, 1.0],
+            Block::Glowstone => [1.0, 0.9, 0.6, 1.0],
+            Block::Lava => [0.95, 0.35, 0.05, 1.0],
+        }
+    }
+
+    /// Whether this face takes the biome tint of its column (grass, leaves, plants)
+    pub fn is_tinted(self, face_dir: u8) -> bool {
+        match self {
+            Block::Grass => face_dir == 2,
+            Block::OakLeaves | Block::SpruceLeaves | Block::BirchLeaves |
+            Block::AcaciaLeaves | Block::DarkOakLeaves | Block::JungleLeaves |
+            Block::Grass_Tall | Block::Grass_Short | Block::Moss => true,
+            _ => false,
         }
     }
+
+    /// Untinted face colors of all blocks, indexed by block id * 6 + face direction,
+    /// followed by the light color and emission (0..1) of every block at PALETTE_SIZE * 6 + id
+    ///
+    /// Uploaded once as the palette the chunk shader looks packed vertices up in.
+    pub fn palette() -> Vec<[f32; 4]> {
+        const { assert!(Self::COUNT <= Self::PALETTE_SIZE, "the chunk shader palette is too small") };
+        let mut palette = vec![[0.0; 4]; Self::PALETTE_SIZE * 7];
+        for id in 0..Self::COUNT {
+            let block = Self::from_u8(id as u8);
+            for face_dir in 0..6 {
+                palette[id * 6 + face_dir] = block.color(face_dir as u8);
+            }
+            let [r, g, b] = block.light_color();
+            palette[Self::PALETTE_SIZE * 6 + id] = [r, g, b, block.light_emission() as f32 / 15.0];
+        }
+        palette
+    }
+}
+
+/// Per-column biome color multiplier, quantized so equal tints still merge in greedy meshing
+///
+/// Each channel stores factor * 128, so 128 = unchanged and 255 ~= 2x brighter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Tint(pub [u8; 3]);
+
+impl Tint {
+    pub const NEUTRAL: Tint = Tint([128, 128, 128]);
+
+    pub fn from_factor(factor: [f32; 3]) -> Self {
+        let q = |f: f32| (f * 128.0).round().clamp(0.0, 255.0) as u8;
+        Tint([q(factor[0]), q(factor[1]), q(factor[2])])
+    }
+
+    pub fn factor(self) -> [f32; 3] {
+        [self.0[0] as f32 / 128.0, self.0[1] as f32 / 128.0, self.0[2] as f32 / 128.0]
+    }
+
+    /// Multiply a block color with this tint (alpha unchanged)
+    pub fn apply(self, color: [f32; 4]) -> [f32; 4] {
+        let f = self.factor();
+        [
+            (color[0] * f[0]).min(1.0),
+            (color[1] * f[1]).min(1.0),
+            (color[2] * f[2]).min(1.0),
+            color[3],
+        ]
+    }
 }
 
 // Convert face direction to normal vector
@@ -0,0 +1,93 @@
+// ============================================================================
+// VERTICAL WORLD BOUNDS
+// ============================================================================
+//
+// The world is unbounded along x and z but has a floor and a ceiling:
+//   → blocks exist only for min_y <= y < max_y, chunks entirely outside are
+//     never generated and edits outside are refused (build limits)
+//   → the lowest `bedrock_layers` layers are bedrock: the bottom layer is
+//     solid, the layers above it get rarer towards the top (ragged floor)
+//   → the cloud layer of the sky (view/sky.rs) lies at cloud_height
+//
+// The values come from TerrainConfig (min_y, max_y, bedrock_layers,
+// cloud_height), so presets can change them.
+// ============================================================================
+
+use super::block::Block;
+use super::chunk::{Chunk, CHUNK_SIZE};
+use super::terrain::hash01;
+use crate::utils::{BlockCoord, ChunkCoord};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldBounds {
+    /// Lowest world y that holds blocks
+    pub min_y: isize,
+    /// First world y above the build limit
+    pub max_y: isize,
+    /// Thickness of the bedrock floor
+    pub bedrock_layers: isize,
+    /// World y of the cloud layer drawn by the sky pass
+    pub cloud_height: isize,
+}
+
+impl WorldBounds {
+    pub fn contains_y(&self, y: isize) -> bool {
+        y >= self.min_y && y < self.max_y
+    }
+
+    /// Whether any block of the chunk lies inside the bounds
+    pub fn contains_chunk(&self, chunk_coord: &ChunkCoord) -> bool {
+        let bottom = chunk_coord.1 * CHUNK_SIZE;
+        bottom + CHUNK_SIZE > self.min_y && bottom < self.max_y
+    }
+
+    /// Whether the block at a world position is part of the bedrock floor
+    pub fn is_bedrock(&self, x: isize, y: isize, z: isize) -> bool {
+        let layer = y - self.min_y;
+        if layer < 0 || layer >= self.bedrock_layers {
+            return false;
+        }
+        // layer 0 is always bedrock, the top layer has a 1 in bedrock_layers chance
+        let chance = 1.0 - layer as f32 / self.bedrock_layers as f32;
+        let r = hash01((x as i32).wrapping_mul(31).wrapping_add(y as i32), z as i32);
+        r < chance
+    }
+
+    /// Clear the blocks of a generated chunk that lie outside the bounds
+    pub fn clip_chunk(&self, chunk: &mut Chunk, chunk_coord: &ChunkCoord) {
+        let bottom = chunk_coord.1 * CHUNK_SIZE;
+        for y in 0..CHUNK_SIZE {
+            if self.contains_y(bottom + y) {
+                continue;
+            }
+            for z in 0..CHUNK_SIZE as usize {
+                for x in 0..CHUNK_SIZE as usize {
+                    chunk.set_block(&BlockCoord(x, y as usize, z), Block::Empty, true);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bedrock_floor_and_chunk_bounds() {
+        let bounds = WorldBounds { min_y: -40, max_y: 100, bedrock_layers: 4, cloud_height: 90 };
+
+        let count = |y: isize| (0..64).filter(|i| bounds.is_bedrock(i % 8, y, i / 8)).count();
+        assert_eq!(count(-41), 0);
+        assert_eq!(count(-40), 64);
+        assert!(count(-38) < count(-39) && count(-37) > 0);
+        assert_eq!(count(-36), 0);
+
+        // chunk -3 covers y -48..-32, chunk 6 covers 96..112, chunk 7 is above the limit
+        assert!(!bounds.contains_chunk(&ChunkCoord(0, -4, 0)));
+        assert!(bounds.contains_chunk(&ChunkCoord(0, -3, 0)));
+        assert!(bounds.contains_chunk(&ChunkCoord(0, 6, 0)));
+        assert!(!bounds.contains_chunk(&ChunkCoord(0, 7, 0)));
+        assert!(bounds.contains_y(99) && !bounds.contains_y(100));
+    }
+}
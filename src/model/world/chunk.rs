@@ -1,17 +1,29 @@
-use super::terrain::VoxelDensityGenerator;
-use crate::utils::{ChunkCoord, BlockCoord, Mesh, Vertex};
-use super::block::{Block, face_dir_to_normal};
+use super::generator::WorldGenerator;
+use crate::utils::{ChunkCoord, BlockCoord, ChunkMesh, ChunkVertex};
+use super::block::{Block, Tint};
+use crate::model::light::{Light, LightVolume};
+use crate::model::visibility::{FaceConnections, FACE_OFFSETS};
 
 
 pub const CHUNK_SIZE: isize = 16;
 const N_BLOCKS_PER_CHUNK: usize = CHUNK_SIZE.pow(3) as usize;
+const N_COLUMNS_PER_CHUNK: usize = CHUNK_SIZE.pow(2) as usize;
 const LOD_LEVELS: usize = CHUNK_SIZE.ilog2() as usize + 1; // e.g., 16 -> 5 levels (0-4)
 #[derive(Clone)]
 pub struct Chunk {
     blocks: [Block; N_BLOCKS_PER_CHUNK],
+
+    /// biome tint per (x, z) column, applied to grass/leaves when meshing
+    tints: [Tint; N_COLUMNS_PER_CHUNK],
+
+    /// sky and block light per block, only while the chunk is lit (see model/light.rs)
+    light: Option<Box<[Light; N_BLOCKS_PER_CHUNK]>>,
     
     /// stores precomputed meshes for different LOD levels
-    meshes: [Option<Mesh>; LOD_LEVELS],
+    meshes: [Option<ChunkMesh>; LOD_LEVELS],
+
+    /// which faces see each other through the chunk, computed along with the meshes
+    connections: Option<FaceConnections>,
 
     // tracks number of blocks that are Block::Empty (optimization for skipping empty chunks)
     n_empty_blocks: usize,
@@ -23,7 +35,10 @@ impl Chunk {
     pub fn new_empty() -> Self {
         Self {
             blocks: [Block::Empty; N_BLOCKS_PER_CHUNK],
+            tints: [Tint::NEUTRAL; N_COLUMNS_PER_CHUNK],
+            light: None,
             meshes: Default::default(),
+            connections: None,
             n_empty_blocks: N_BLOCKS_PER_CHUNK,
         }
     }
@@ -42,17 +57,20 @@ impl Chunk {
         chunk
     }
 
-    pub fn new_polulated(density_generator: &VoxelDensityGenerator, chunk_coord: &ChunkCoord) -> Self {
+    pub fn new_polulated(generator: &WorldGenerator, chunk_coord: &ChunkCoord) -> Self {
 
         let mut chunk = Self::new_empty();
-        density_generator.populate_chunk_simple(&mut chunk, chunk_coord);
+        generator.populate_chunk(&mut chunk, chunk_coord);
         chunk
     }
 
     pub fn with_blocks(blocks: [Block; N_BLOCKS_PER_CHUNK]) -> Self {
         Self {
             blocks,
+            tints: [Tint::NEUTRAL; N_COLUMNS_PER_CHUNK],
+            light: None,
             meshes: Default::default(),
+            connections: None,
             n_empty_blocks: blocks.iter().filter(|b| b.is_empty()).count(),
         }
     }
@@ -161,6 +179,19 @@ impl Chunk {
         self.n_empty_blocks == N_BLOCKS_PER_CHUNK
     }
 
+    /// Deterministic 64 bit hash of the chunk contents (blocks and column tints)
+    ///
+    /// Stable across platforms and runs (FNV-1a over the raw ids), so it can be
+    /// compared against committed values to detect changes in generated worlds.
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let blocks = self.blocks.iter().map(|b| b.to_u8());
+        let tints = self.tints.iter().flat_map(|t| t.0);
+        blocks.chain(tints).fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+    }
+
 
     pub fn get_block(&self, coord: &BlockCoord) -> Block {
         self.blocks[coord.get_block_idx()]
@@ -184,21 +215,71 @@ impl Chunk {
 
             // invalidate meshes
             self.meshes = Default::default();
+            self.connections = None;
 
             true
         } else { false }
     }
 
-    pub fn get_mesh(&mut self, lod: u8) -> Mesh {
+    pub fn get_tint(&self, x: usize, z: usize) -> Tint {
+        self.tints[x + z * CHUNK_SIZE as usize]
+    }
+
+    pub fn set_tint(&mut self, x: usize, z: usize, tint: Tint) {
+        self.tints[x + z * CHUNK_SIZE as usize] = tint;
+        self.meshes = Default::default();
+    }
+
+    pub fn is_lit(&self) -> bool {
+        self.light.is_some()
+    }
+
+    /// Start storing light, every block at `light`
+    pub fn fill_light(&mut self, light: Light) {
+        self.light = Some(Box::new([light; N_BLOCKS_PER_CHUNK]));
+    }
+
+    /// Stop storing light (e.g. when the chunk is too far away to be lit)
+    pub fn clear_light(&mut self) {
+        self.light = None;
+    }
+
+    /// Light of a block, None if the chunk is not lit
+    pub fn get_light(&self, coord: &BlockCoord) -> Option<Light> {
+        self.light.as_ref().map(|light| light[coord.get_block_idx()])
+    }
+
+    pub fn set_light(&mut self, coord: &BlockCoord, light: Light) {
+        if let Some(lights) = self.light.as_mut() {
+            lights[coord.get_block_idx()] = light;
+        }
+    }
+
+    /// Mesh of a LOD, lit by `light` (the light around the chunk) or full sky light
+    ///
+    /// Lit meshes are not cached, the light changes with blocks in other chunks.
+    pub fn get_mesh(&mut self, lod: u8, light: Option<&LightVolume>) -> ChunkMesh {
+
+        if self.connections.is_none() {
+            self.connections = Some(if self.is_empty() {
+                FaceConnections::ALL
+            } else {
+                FaceConnections::compute(|x, y, z| self.blocks[BlockCoord(x, y, z).get_block_idx()])
+            });
+        }
+
+        if lod == 0 && light.is_some() {
+            return compute_mesh(&self.blocks, &self.tints, light);
+        }
 
         if self.meshes[lod as usize].is_none() {
 
             self.meshes[lod as usize] = if lod == 0 {
                 // if lod 0, use original blocks
-                Some(compute_mesh(&self.blocks))
+                Some(compute_mesh(&self.blocks, &self.tints, None))
             } else {
                 let downsampled = self.compute_downsampled(lod);
-                Some(compute_mesh(&downsampled.blocks))
+                Some(compute_mesh(&downsampled.blocks, &downsampled.tints, None))
             }
         };
 
@@ -207,6 +288,11 @@ impl Chunk {
     }
 
 
+    /// Face connectivity for cave culling, everything connects until the chunk is meshed
+    pub fn face_connections(&self) -> FaceConnections {
+        self.connections.unwrap_or(FaceConnections::ALL)
+    }
+
     /// Compute a subsampled version of this chunk for the given LOD level
     /// Strategy: for each window_size^3 cell, pick the modal block (ignoring air so surface wins),
     /// then fill ALL blocks in that cell with the chosen block type.
@@ -216,6 +302,7 @@ impl Chunk {
         assert_ne!(lod, 0, "LOD 0 is the original chunk");
 
         let mut downsampled_chunk = Chunk::new_empty();
+        downsampled_chunk.tints = self.tints;
 
         // return empty chunk if the original is empty
         if self.is_empty() {
@@ -231,7 +318,7 @@ impl Chunk {
             for y in 0..lod_size {
                 for x in 0..lod_size {
                     // Pick the modal block inside this window_size^3 cell (ignore air so surface wins over empty)
-                    let mut block_counts = [0u32; 30]; // Updated for 30 block types
+                    let mut block_counts = [0u32; Block::COUNT];
                     let mut any = false;
 
                     for oz in 0..window_size {
@@ -286,14 +373,15 @@ impl Chunk {
 
 
 // Greedy meshing with face culling - merges adjacent faces of same block type
-pub fn compute_mesh(blocks: &[Block; N_BLOCKS_PER_CHUNK]) -> Mesh {
+// Faces are lit by the light of the cell in front of them (full sky light without a light volume)
+pub fn compute_mesh(blocks: &[Block; N_BLOCKS_PER_CHUNK], tints: &[Tint; N_COLUMNS_PER_CHUNK], light: Option<&LightVolume>) -> ChunkMesh {
 
     let mut verts = Vec::new();
     let mut idxs = Vec::new();
     let mut index: u32 = 0;
 
     // Process each of the 6 face directions
-    for dir in 0..6 {
+    for (dir, &(dx, dy, dz)) in FACE_OFFSETS.iter().enumerate() {
         // Determine axis and direction for this sweep
         let (axis, back_face) = match dir {
             0 => (0, false), // +X
@@ -310,8 +398,9 @@ pub fn compute_mesh(blocks: &[Block; N_BLOCKS_PER_CHUNK]) -> Mesh {
 
         // Sweep through each slice along the axis
         for w in 0..w_dim {
-            // Create a mask for this slice (stores block or air for culled)
-            let mut mask = vec![Block::Empty; (u_dim * v_dim) as usize];
+            // Create a mask for this slice (stores block + tint + light, or air for culled)
+            // Faces only merge if block, tint and light match
+            let mut mask = vec![(Block::Empty, Tint::NEUTRAL, Light::SKY); u_dim * v_dim];
 
             // Fill mask with visible faces
             for v in 0..v_dim {
@@ -324,7 +413,7 @@ pub fn compute_mesh(blocks: &[Block; N_BLOCKS_PER_CHUNK]) -> Mesh {
                         _ => unreachable!(),
                     };
 
-                    let block = blocks[BlockCoord(x as usize, y as usize, z as usize).get_block_idx()];
+                    let block = blocks[BlockCoord(x, y, z).get_block_idx()];
 
                     // Render water and solid blocks, skip air
                     if block.is_empty() { continue; }
@@ -368,35 +457,49 @@ pub fn compute_mesh(blocks: &[Block; N_BLOCKS_PER_CHUNK]) -> Mesh {
 
                     // Face is visible if neighbor is air or different material (e.g., water next to land)
                     let should_render = neighbor == Block::Empty || 
-                                        (block == Block::Water && neighbor != Block::Water) ||
-                                        (block != Block::Water && neighbor == Block::Water);
+                                        (block.is_liquid() && neighbor != block) ||
+                                        (!block.is_liquid() && neighbor.is_liquid());
                     if should_render {
-                        mask[(u + v * u_dim) as usize] = block;
+                        let tint = if block.is_tinted(dir as u8) {
+                            tints[x + z * CHUNK_SIZE as usize]
+                        } else {
+                            Tint::NEUTRAL
+                        };
+                        let face_light = light.map_or(Light::SKY, |light| light.get(x as isize + dx, y as isize + dy, z as isize + dz));
+                        mask[u + v * u_dim] = (block, tint, face_light);
                     }
                 }
             }
 
+            // Faces in the outer ring of the slice are never merged, so every quad edge on
+            // the chunk border has a vertex at each block. Neighbors meshed at any LOD then
+            // share their border vertices and no T-junction gaps open between them.
+            // (Height steps between LODs are closed by the border faces, which are always
+            // emitted since out of bounds counts as air, and act as skirts.)
+            let is_rim = |u: usize, v: usize| u == 0 || v == 0 || u + 1 == u_dim || v + 1 == v_dim;
+
             // Greedy meshing: merge adjacent faces into rectangles
             for v in 0..v_dim {
                 for u in 0..u_dim {
-                    let mask_idx = (u + v * u_dim) as usize;
-                    let block = mask[mask_idx];
+                    let mask_idx = u + v * u_dim;
+                    let (block, tint, face_light) = mask[mask_idx];
                     if block == Block::Empty { continue; }
+                    let rim = is_rim(u, v);
 
                     // Find width (u direction)
                     let mut width = 1;
-                    while u + width < u_dim {
-                        let check_idx = (u + width + v * u_dim) as usize;
-                        if mask[check_idx] != block { break; }
+                    while !rim && u + width < u_dim {
+                        let check_idx = u + width + v * u_dim;
+                        if mask[check_idx] != (block, tint, face_light) || is_rim(u + width, v) { break; }
                         width += 1;
                     }
 
                     // Find height (v direction)
                     let mut height = 1;
-                    'height_loop: while v + height < v_dim {
+                    'height_loop: while !rim && v + height < v_dim {
                         for du in 0..width {
-                            let check_idx = (u + du + (v + height) * u_dim) as usize;
-                            if mask[check_idx] != block {
+                            let check_idx = u + du + (v + height) * u_dim;
+                            if mask[check_idx] != (block, tint, face_light) || is_rim(u + du, v + height) {
                                 break 'height_loop;
                             }
                         }
@@ -406,84 +509,80 @@ pub fn compute_mesh(blocks: &[Block; N_BLOCKS_PER_CHUNK]) -> Mesh {
                     // Clear merged area from mask
                     for dv in 0..height {
                         for du in 0..width {
-                            let clear_idx = (u + du + (v + dv) * u_dim) as usize;
-                            mask[clear_idx] = Block::Empty;
+                            let clear_idx = u + du + (v + dv) * u_dim;
+                            mask[clear_idx] = (Block::Empty, Tint::NEUTRAL, Light::SKY);
                         }
                     }
 
                     // Generate quad for this merged rectangle
                     let face_dir = dir as u8;
-                    let color = block.color(face_dir);
-                    let normal = face_dir_to_normal(face_dir);
 
                     // Generate quad vertices based on axis and dimensions
                     // For each axis, we need to map (u,v,w) and (width,height) correctly
                     let (p0, p1, p2, p3) = match axis {
                         0 => { // X-axis: u=Y, v=Z, w=X
-                            let xf = if back_face { w as f32 } else { (w + 1) as f32 };
+                            let xf = if back_face { w as u8 } else { (w + 1) as u8 };
                             if back_face {
                                 (
-                                    [xf, u as f32, v as f32],
-                                    [xf, (u + width) as f32, v as f32],
-                                    [xf, (u + width) as f32, (v + height) as f32],
-                                    [xf, u as f32, (v + height) as f32],
+                                    [xf, u as u8, v as u8],
+                                    [xf, (u + width) as u8, v as u8],
+                                    [xf, (u + width) as u8, (v + height) as u8],
+                                    [xf, u as u8, (v + height) as u8],
                                 )
                             } else {
                                 (
-                                    [xf, u as f32, (v + height) as f32],
-                                    [xf, (u + width) as f32, (v + height) as f32],
-                                    [xf, (u + width) as f32, v as f32],
-                                    [xf, u as f32, v as f32],
+                                    [xf, u as u8, (v + height) as u8],
+                                    [xf, (u + width) as u8, (v + height) as u8],
+                                    [xf, (u + width) as u8, v as u8],
+                                    [xf, u as u8, v as u8],
                                 )
                             }
                         },
                         1 => { // Y-axis: u=X, v=Z, w=Y
-                            let yf = if back_face { w as f32 } else { (w + 1) as f32 };
+                            let yf = if back_face { w as u8 } else { (w + 1) as u8 };
                             if back_face {
                                 (
-                                    [u as f32, yf, v as f32],
-                                    [u as f32, yf, (v + height) as f32],
-                                    [(u + width) as f32, yf, (v + height) as f32],
-                                    [(u + width) as f32, yf, v as f32],
+                                    [u as u8, yf, v as u8],
+                                    [u as u8, yf, (v + height) as u8],
+                                    [(u + width) as u8, yf, (v + height) as u8],
+                                    [(u + width) as u8, yf, v as u8],
                                 )
                             } else {
                                 (
-                                    [(u + width) as f32, yf, v as f32],
-                                    [(u + width) as f32, yf, (v + height) as f32],
-                                    [u as f32, yf, (v + height) as f32],
-                                    [u as f32, yf, v as f32],
+                                    [(u + width) as u8, yf, v as u8],
+                                    [(u + width) as u8, yf, (v + height) as u8],
+                                    [u as u8, yf, (v + height) as u8],
+                                    [u as u8, yf, v as u8],
                                 )
                             }
                         },
                         2 => { // Z-axis: u=X, v=Y, w=Z
-                            let zf = if back_face { w as f32 } else { (w + 1) as f32 };
+                            let zf = if back_face { w as u8 } else { (w + 1) as u8 };
                             if back_face {
                                 (
-                                    [u as f32, v as f32, zf],
-                                    [(u + width) as f32, v as f32, zf],
-                                    [(u + width) as f32, (v + height) as f32, zf],
-                                    [u as f32, (v + height) as f32, zf],
+                                    [u as u8, v as u8, zf],
+                                    [(u + width) as u8, v as u8, zf],
+                                    [(u + width) as u8, (v + height) as u8, zf],
+                                    [u as u8, (v + height) as u8, zf],
                                 )
                             } else {
                                 (
-                                    [(u + width) as f32, v as f32, zf],
-                                    [u as f32, v as f32, zf],
-                                    [u as f32, (v + height) as f32, zf],
-                                    [(u + width) as f32, (v + height) as f32, zf],
+                                    [(u + width) as u8, v as u8, zf],
+                                    [u as u8, v as u8, zf],
+                                    [u as u8, (v + height) as u8, zf],
+                                    [(u + width) as u8, (v + height) as u8, zf],
                                 )
                             }
                         },
                         _ => unreachable!(),
                     };
 
-                    // UV coordinates scaled by quad size
-                    let uv_scale_u = width as f32;
-                    let uv_scale_v = height as f32;
-
-                    verts.push(Vertex { pos: p0, normal, color, uv: [0.0, 0.0] });
-                    verts.push(Vertex { pos: p1, normal, color, uv: [0.0, uv_scale_v] });
-                    verts.push(Vertex { pos: p2, normal, color, uv: [uv_scale_u, uv_scale_v] });
-                    verts.push(Vertex { pos: p3, normal, color, uv: [uv_scale_u, 0.0] });
+                    // UV coordinates scaled by quad size, color comes from the block palette
+                    let (w, h) = (width as u8, height as u8);
+                    verts.push(ChunkVertex::new(p0, face_dir, [0, 0], block, tint, face_light));
+                    verts.push(ChunkVertex::new(p1, face_dir, [0, h], block, tint, face_light));
+                    verts.push(ChunkVertex::new(p2, face_dir, [w, h], block, tint, face_light));
+                    verts.push(ChunkVertex::new(p3, face_dir, [w, 0], block, tint, face_light));
 
                     // Reverse winding order to match CCW front face
                     idxs.extend_from_slice(&[index, index + 2, index + 1, index, index + 3, index + 2]);
@@ -493,6 +592,6 @@ pub fn compute_mesh(blocks: &[Block; N_BLOCKS_PER_CHUNK]) -> Mesh {
         }
     }
 
-    Mesh { vertices: verts, indices: idxs }
+    ChunkMesh { vertices: verts, indices: idxs }
 }
 
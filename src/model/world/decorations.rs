@@ -0,0 +1,328 @@
+// ============================================================================
+// SURFACE DECORATIONS
+// ============================================================================
+//
+// Small features placed on the finished terrain of a chunk, before its trees.
+// Biomes list the decorations they get with a weight (`decorations` in the
+// biome files), there are two kinds:
+//   → column decorations (ice, snow) change single columns; the weight is the
+//     share of columns covered, in patches of a low frequency noise
+//   → cell decorations (boulders, fallen logs, ponds, sand patches, icebergs)
+//     grow from one anchor per CELL_SIZE x CELL_SIZE cell; the weight is the
+//     chance that a cell of the biome gets one
+//
+// Everything is derived from world positions with the noise helpers of
+// terrain.rs, and cell decorations read the terrain around them through the
+// ground callback (which gives the same answer for every chunk). Like trees they
+// may cross chunk borders, every chunk writes only its own part.
+// ============================================================================
+
+use super::biome_def::{BiomeId, BiomeRegistry};
+use super::block::Block;
+use super::chunk::{Chunk, CHUNK_SIZE};
+use super::terrain::{fbm, hash01, SEA_LEVEL};
+use crate::utils::{BlockCoord, ChunkCoord};
+
+/// Farthest any decoration block lies from its anchor along x or z
+pub const DECORATION_REACH: i32 = 7;
+
+/// Edge length of a decoration cell, a cell holds at most one cell decoration
+const CELL_SIZE: i32 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decoration {
+    /// Mossy cobblestone rock half buried in the ground
+    Boulder,
+    /// Tree trunk lying on flat ground, made of the biome's tree wood
+    FallenLog,
+    /// Small pool of lake water with a sandy rim
+    Pond,
+    /// Sand instead of grass in a ragged disk
+    SandPatch,
+    /// Ice mountain floating in the sea, snow on top
+    Iceberg,
+    /// Ice on the sea surface (column decoration)
+    Ice,
+    /// Snow layer on top of the ground (column decoration)
+    Snow,
+}
+
+impl Decoration {
+    /// Decoration by its name in biome files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "boulder" => Some(Decoration::Boulder),
+            "fallen_log" => Some(Decoration::FallenLog),
+            "pond" => Some(Decoration::Pond),
+            "sand_patch" => Some(Decoration::SandPatch),
+            "iceberg" => Some(Decoration::Iceberg),
+            "ice" => Some(Decoration::Ice),
+            "snow" => Some(Decoration::Snow),
+            _ => None,
+        }
+    }
+
+    /// Column decorations change single columns, all others grow from a cell anchor
+    pub fn is_column(self) -> bool {
+        matches!(self, Decoration::Ice | Decoration::Snow)
+    }
+}
+
+/// Random value in [0, 1) of a world column, different for every salt and seed
+fn random(x: i32, z: i32, salt: i32, seed: u32) -> f32 {
+    hash01(x.wrapping_mul(31).wrapping_add(salt), z ^ seed as i32)
+}
+
+/// Patch noise in about [0, 1) for column decorations
+fn patch(x: i32, z: i32, salt: i32, seed: u32) -> f32 {
+    let offset = (seed % 10007) as f32 * 7.0 + salt as f32 * 131.0;
+    fbm(x as f32 + offset, z as f32 - offset, 0.08, 0.5, 2) + 1.0
+}
+
+/// Writes the blocks of one decoration that fall into the chunk
+struct Slice<'a> {
+    chunk: &'a mut Chunk,
+    min: (i32, i32, i32),
+    anchor: (i32, i32),
+}
+
+impl Slice<'_> {
+    fn get(&self, x: i32, y: i32, z: i32) -> Option<Block> {
+        let size = CHUNK_SIZE as i32;
+        let (lx, ly, lz) = (x - self.min.0, y - self.min.1, z - self.min.2);
+        if lx < 0 || ly < 0 || lz < 0 || lx >= size || ly >= size || lz >= size {
+            return None;
+        }
+        Some(self.chunk.get_block(&BlockCoord(lx as usize, ly as usize, lz as usize)))
+    }
+
+    /// Set a block if it lies in the chunk and `replaces` accepts the current block
+    fn put(&mut self, x: i32, y: i32, z: i32, block: Block, replaces: impl Fn(Block) -> bool) {
+        if (x - self.anchor.0).abs() > DECORATION_REACH || (z - self.anchor.1).abs() > DECORATION_REACH {
+            return; // keep every decoration inside the area its neighbors look at
+        }
+        if self.get(x, y, z).is_some_and(replaces) {
+            let coord = BlockCoord((x - self.min.0) as usize, (y - self.min.1) as usize, (z - self.min.2) as usize);
+            self.chunk.set_block(&coord, block, true);
+        }
+    }
+}
+
+/// Place the decorations of the columns in and around a chunk
+///
+/// `ground(x, z)` returns (first world y above the ground, biome) of any column
+/// within 2 * DECORATION_REACH of the chunk.
+pub fn decorate_chunk(
+    chunk: &mut Chunk,
+    chunk_coord: &ChunkCoord,
+    biomes: &BiomeRegistry,
+    seed: u32,
+    ground: &dyn Fn(i32, i32) -> (isize, BiomeId),
+) {
+    let size = CHUNK_SIZE as i32;
+    let min = (chunk_coord.0 as i32 * size, chunk_coord.1 as i32 * size, chunk_coord.2 as i32 * size);
+
+    // Column decorations of the chunk's own columns
+    for z in min.2..min.2 + size {
+        for x in min.0..min.0 + size {
+            let (height, biome) = ground(x, z);
+            for &(decoration, coverage) in &biomes.get(biome).decorations {
+                if decoration.is_column() && patch(x, z, decoration as i32, seed) < coverage {
+                    let mut slice = Slice { chunk: &mut *chunk, min, anchor: (x, z) };
+                    column_decoration(&mut slice, decoration, x, z, height);
+                }
+            }
+        }
+    }
+
+    // Cell decorations anchored close enough to reach into the chunk
+    let first = ((min.0 - DECORATION_REACH).div_euclid(CELL_SIZE), (min.2 - DECORATION_REACH).div_euclid(CELL_SIZE));
+    let last = ((min.0 + size + DECORATION_REACH).div_euclid(CELL_SIZE), (min.2 + size + DECORATION_REACH).div_euclid(CELL_SIZE));
+    for cz in first.1..=last.1 {
+        for cx in first.0..=last.0 {
+            let x = cx * CELL_SIZE + (random(cx, cz, 1, seed) * CELL_SIZE as f32) as i32;
+            let z = cz * CELL_SIZE + (random(cx, cz, 2, seed) * CELL_SIZE as f32) as i32;
+            if x < min.0 - DECORATION_REACH || x >= min.0 + size + DECORATION_REACH ||
+               z < min.2 - DECORATION_REACH || z >= min.2 + size + DECORATION_REACH {
+                continue;
+            }
+
+            // one decoration per cell, picked by its chance
+            let (height, biome) = ground(x, z);
+            let definition = biomes.get(biome);
+            let mut r = random(cx, cz, 3, seed);
+            let picked = definition.decorations.iter().filter(|(d, _)| !d.is_column()).find(|(_, chance)| {
+                r -= chance;
+                r < 0.0
+            });
+            if let Some(&(decoration, _)) = picked {
+                let wood = definition.trees.first().map_or(Block::Wood, |(tree, _)| tree.wood());
+                let mut slice = Slice { chunk: &mut *chunk, min, anchor: (x, z) };
+                let rng = [random(x, z, 4, seed), random(x, z, 5, seed), random(x, z, 6, seed)];
+                cell_decoration(&mut slice, decoration, (x, height as i32, z), rng, wood, seed, ground);
+            }
+        }
+    }
+}
+
+fn column_decoration(slice: &mut Slice, decoration: Decoration, x: i32, z: i32, height: isize) {
+    match decoration {
+        // freeze the sea or lake surface (water only reaches SEA_LEVEL where the ground is lower)
+        Decoration::Ice if height <= SEA_LEVEL => {
+            slice.put(x, SEA_LEVEL as i32, z, Block::Ice, |b| matches!(b, Block::Water | Block::LakeWater));
+        }
+        Decoration::Snow if height > SEA_LEVEL + 1 => {
+            let y = height as i32;
+            let on_ground = slice.get(x, y - 1, z).is_none_or(|b| b.is_solid() && !b.is_open());
+            if on_ground {
+                slice.put(x, y, z, Block::Snow, Block::is_open);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn cell_decoration(
+    slice: &mut Slice,
+    decoration: Decoration,
+    (x, height, z): (i32, i32, i32),
+    rng: [f32; 3],
+    wood: Block,
+    seed: u32,
+    ground: &dyn Fn(i32, i32) -> (isize, BiomeId),
+) {
+    let on_land = height > SEA_LEVEL as i32 + 2;
+    match decoration {
+        Decoration::Boulder if on_land => {
+            // sunk half a block into the ground, cobblestone showing through the moss
+            let radius = 1.2 + rng[0] * 1.3;
+            let r = radius.ceil() as i32;
+            let center_y = height as f32 - 0.5;
+            for dy in -r..=r {
+                for dz in -r..=r {
+                    for dx in -r..=r {
+                        let d = (dx * dx + dz * dz) as f32 + (dy * dy) as f32 * 1.6;
+                        if d > radius * radius {
+                            continue;
+                        }
+                        let (bx, by, bz) = (x + dx, (center_y + dy as f32).round() as i32, z + dz);
+                        let block = if random(bx, bz, by, seed) < 0.3 { Block::Cobblestone } else { Block::MossyCobblestone };
+                        slice.put(bx, by, bz, block, |b| !b.is_liquid() && b != Block::Bedrock);
+                    }
+                }
+            }
+        }
+        Decoration::FallenLog if on_land => {
+            // only on flat ground, a log never floats
+            let length = 4 + (rng[0] * 4.0) as i32;
+            let (dx, dz) = if rng[1] < 0.5 { (1, 0) } else { (0, 1) };
+            let start = -length / 2;
+            let flat = (start..start + length).all(|i| ground(x + dx * i, z + dz * i).0 as i32 == height);
+            if flat {
+                for i in start..start + length {
+                    slice.put(x + dx * i, height, z + dz * i, wood, Block::is_open);
+                }
+            }
+        }
+        Decoration::Pond if on_land => {
+            let radius = 2.5 + rng[0] * 2.0;
+            let r = radius.ceil() as i32;
+            let disk: Vec<(i32, i32, i32)> = (-r..=r)
+                .flat_map(|dz| (-r..=r).map(move |dx| (dx, dz)))
+                .filter(|(dx, dz)| ((dx * dx + dz * dz) as f32) <= radius * radius)
+                .map(|(dx, dz)| (dx, dz, ground(x + dx, z + dz).0 as i32 - 1))
+                .collect();
+            // a pond needs nearly flat ground, its surface is the lowest ground block
+            let level = disk.iter().map(|c| c.2).min().unwrap_or(height - 1);
+            if disk.iter().any(|c| c.2 > level + 1) {
+                return;
+            }
+            for &(dx, dz, top) in &disk {
+                let depth = if ((dx * dx + dz * dz) as f32) < radius * radius * 0.3 { 2 } else { 1 };
+                for y in level - depth + 1..=level {
+                    slice.put(x + dx, y, z + dz, Block::LakeWater, |b| b != Block::Bedrock);
+                }
+                for y in level + 1..=top + 1 {
+                    slice.put(x + dx, y, z + dz, Block::Empty, |b| b != Block::Bedrock);
+                }
+            }
+            sand_disk(slice, (x, z), radius + 1.5, seed, ground);
+        }
+        Decoration::SandPatch if on_land => sand_disk(slice, (x, z), 2.0 + rng[0] * 3.0, seed, ground),
+        Decoration::Iceberg if height < SEA_LEVEL as i32 - 3 => {
+            // rounded peak above the water, twice as deep below it
+            let radius = 2.5 + rng[0] * 2.5;
+            let peak = 2.0 + rng[1] * 4.0;
+            let r = radius.ceil() as i32;
+            let sea = SEA_LEVEL as i32;
+            for dz in -r..=r {
+                for dx in -r..=r {
+                    let t = ((dx * dx + dz * dz) as f32).sqrt() / radius;
+                    if t > 1.0 {
+                        continue;
+                    }
+                    let shape = 1.0 - t * t;
+                    let top = sea + (peak * shape + random(x + dx, z + dz, 7, seed) * 0.8).round() as i32;
+                    let bottom = (sea - (peak * 2.0 * shape).round() as i32).max(height);
+                    for y in bottom..=top {
+                        let block = if y == top && y > sea { Block::Snow } else { Block::Ice };
+                        slice.put(x + dx, y, z + dz, block, |b| b.is_open() || matches!(b, Block::Water | Block::Kelp | Block::SeaGrass));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Ragged disk of sand replacing grass, moss and dirt at the surface
+fn sand_disk(slice: &mut Slice, (x, z): (i32, i32), radius: f32, seed: u32, ground: &dyn Fn(i32, i32) -> (isize, BiomeId)) {
+    let r = radius.ceil() as i32;
+    for dz in -r..=r {
+        for dx in -r..=r {
+            let (bx, bz) = (x + dx, z + dz);
+            let edge = radius - random(bx, bz, 8, seed) * 1.5;
+            if ((dx * dx + dz * dz) as f32) > edge * edge {
+                continue;
+            }
+            let top = ground(bx, bz).0 as i32 - 1;
+            slice.put(bx, top, bz, Block::Sand, |b| matches!(b, Block::Grass | Block::Moss | Block::Dirt));
+            // plants don't grow on sand
+            slice.put(bx, top + 1, bz, Block::Empty, |b| b.is_open() && b != Block::Empty);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decorations_match_across_chunks() {
+        // flat plain at y = 10 where every cell gets a pond
+        let mut biomes = (*BiomeRegistry::builtin()).clone();
+        let plain = biomes.id("plain").unwrap();
+        let mut definition = biomes.get(plain).clone();
+        definition.decorations = vec![(Decoration::Pond, 1.0)];
+        biomes = biomes.with_definition(definition).unwrap();
+        let ground = |_: i32, _: i32| (10, plain);
+
+        let mut chunks = Vec::new();
+        for cx in 0..2 {
+            let mut chunk = Chunk::new_empty();
+            for i in 0..16 * 16 {
+                chunk.set_block(&BlockCoord(i % 16, 9, i / 16), Block::Grass, true);
+            }
+            decorate_chunk(&mut chunk, &ChunkCoord(cx, 0, 0), &biomes, 3, &ground);
+            chunks.push(chunk);
+        }
+
+        // ponds crossing the border are written by both chunks
+        let water = |chunk: &Chunk, x: usize| (0..16).filter(|&z| chunk.get_block(&BlockCoord(x, 9, z)) == Block::LakeWater).count();
+        let total: usize = (0..16).map(|x| water(&chunks[0], x) + water(&chunks[1], x)).sum();
+        assert!(total > 20, "expected ponds, found {total} water blocks");
+        assert!(water(&chunks[0], 15) > 0, "no pond at the border");
+        assert_eq!(water(&chunks[0], 15) > 0, water(&chunks[1], 0) > 0);
+        assert_eq!(Decoration::from_name("fallen_log"), Some(Decoration::FallenLog));
+    }
+}
@@ -0,0 +1,306 @@
+// erosion.rs - Hydraulic and thermal erosion for the simple generator
+//
+// Noise heightmaps have no drainage: every mountain is a smooth blob. This
+// module runs a droplet simulation on coarse heightmap tiles:
+//
+//   1. A tile covers TILE_SIZE x TILE_SIZE blocks, sampled every CELL_SIZE blocks
+//   2. Droplets spawn at seeded random cells, flow downhill, pick up sediment on
+//      steep slopes and drop it where they slow down (gullies + sediment fans)
+//   3. A thermal pass lets slopes steeper than the talus limit slide down
+//   4. The result is stored as a height delta per cell (eroded - original)
+//
+// Tiles overlap: their centers are TILE_SIZE / 2 apart and every column blends
+// the four tiles around it with tent weights that reach zero at a tile's edge.
+// The blend is continuous everywhere, so chunk and tile borders are seamless,
+// and each tile only depends on its position and the config (deterministic).
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+use super::biome::BiomeBlender;
+use super::terrain::VoxelDensityGenerator;
+
+/// Blocks covered by one erosion tile along each axis
+const TILE_SIZE: i32 = 256;
+/// Distance between tile centers (tiles overlap by half)
+const TILE_SPACING: i32 = TILE_SIZE / 2;
+/// Blocks per heightmap cell
+const CELL_SIZE: i32 = 2;
+const CELLS: usize = (TILE_SIZE / CELL_SIZE) as usize + 1;
+/// Number of tiles kept in memory before the oldest is dropped (~66 KB each);
+/// enough for the default 128 x 128 chunk window (about 17 x 17 tiles)
+const MAX_CACHED_TILES: usize = 512;
+
+/// Droplet simulation constants (the tunable ones live in TerrainConfig)
+const MAX_DROPLET_STEPS: usize = 48;
+const INERTIA: f32 = 0.1;
+const CAPACITY_FACTOR: f32 = 0.1;
+const MIN_CAPACITY: f32 = 0.01;
+const EVAPORATION: f32 = 0.03;
+const GRAVITY: f32 = 0.5;
+const THERMAL_ITERATIONS: usize = 8;
+
+/// Height change of every cell of one tile after erosion
+struct ErosionTile {
+    delta: Vec<f32>,
+}
+
+/// Lazily computed, cached erosion tiles of one generator
+#[derive(Default)]
+pub struct ErosionCache {
+    tiles: RefCell<HashMap<(i32, i32), Rc<ErosionTile>>>,
+    /// Insertion order for evicting the oldest tiles
+    order: RefCell<VecDeque<(i32, i32)>>,
+}
+
+impl ErosionCache {
+    /// Erosion height change at a world column (negative = carved, positive = sediment)
+    pub fn delta(&self, generator: &VoxelDensityGenerator, wx: f32, wz: f32) -> f32 {
+        // the four tiles whose centers surround this column
+        let fx = wx / TILE_SPACING as f32;
+        let fz = wz / TILE_SPACING as f32;
+        let (tx, tz) = (fx.floor() as i32, fz.floor() as i32);
+        let (u, v) = (fx - tx as f32, fz - tz as f32);
+
+        let mut delta = 0.0;
+        for (dx, dz, weight) in [
+            (0, 0, (1.0 - u) * (1.0 - v)),
+            (1, 0, u * (1.0 - v)),
+            (0, 1, (1.0 - u) * v),
+            (1, 1, u * v),
+        ] {
+            if weight > 0.0 {
+                let key = (tx + dx, tz + dz);
+                delta += self.tile(generator, key).sample(key, wx, wz) * weight;
+            }
+        }
+        delta
+    }
+
+    fn tile(&self, generator: &VoxelDensityGenerator, key: (i32, i32)) -> Rc<ErosionTile> {
+        if let Some(tile) = self.tiles.borrow().get(&key) {
+            return tile.clone();
+        }
+
+        let tile = Rc::new(ErosionTile::generate(generator, key));
+        let mut tiles = self.tiles.borrow_mut();
+        let mut order = self.order.borrow_mut();
+        if order.len() >= MAX_CACHED_TILES {
+            if let Some(oldest) = order.pop_front() {
+                tiles.remove(&oldest);
+            }
+        }
+        tiles.insert(key, tile.clone());
+        order.push_back(key);
+        tile
+    }
+}
+
+/// World position of the first cell of a tile
+fn tile_origin(key: (i32, i32)) -> (i32, i32) {
+    (key.0 * TILE_SPACING - TILE_SIZE / 2, key.1 * TILE_SPACING - TILE_SIZE / 2)
+}
+
+impl ErosionTile {
+    fn generate(generator: &VoxelDensityGenerator, key: (i32, i32)) -> Self {
+        let config = &generator.config;
+        let (min_x, min_z) = tile_origin(key);
+
+        // original (uneroded) heights of the tile
+        let blender = BiomeBlender::new(generator, min_x, min_z, TILE_SIZE + 1);
+        let mut heights = Vec::with_capacity(CELLS * CELLS);
+        for iz in 0..CELLS {
+            for ix in 0..CELLS {
+                let wx = (min_x + ix as i32 * CELL_SIZE) as f32;
+                let wz = (min_z + iz as i32 * CELL_SIZE) as f32;
+                heights.push(generator.uneroded_height(&blender, wx, wz) as f32);
+            }
+        }
+        let original = heights.clone();
+
+        let mut map = Heightmap { heights, size: CELLS };
+        let mut rng = Rng::new(config.seed, key);
+        let droplets = (config.erosion_droplets.max(0.0) * (CELLS * CELLS) as f32) as usize;
+        for _ in 0..droplets {
+            let x = rng.next_f32() * (CELLS - 1) as f32;
+            let z = rng.next_f32() * (CELLS - 1) as f32;
+            map.simulate_droplet(x, z, config.erosion_strength, config.erosion_deposition);
+        }
+        map.thermal(config.erosion_talus * CELL_SIZE as f32);
+
+        let delta = map.heights.iter().zip(original.iter()).map(|(h, o)| h - o).collect();
+        Self { delta }
+    }
+
+    /// Bilinear delta at a world column inside the tile
+    fn sample(&self, key: (i32, i32), wx: f32, wz: f32) -> f32 {
+        let (min_x, min_z) = tile_origin(key);
+        let cx = ((wx - min_x as f32) / CELL_SIZE as f32).clamp(0.0, (CELLS - 1) as f32);
+        let cz = ((wz - min_z as f32) / CELL_SIZE as f32).clamp(0.0, (CELLS - 1) as f32);
+        let ix = (cx as usize).min(CELLS - 2);
+        let iz = (cz as usize).min(CELLS - 2);
+        let (u, v) = (cx - ix as f32, cz - iz as f32);
+
+        let d = |x: usize, z: usize| self.delta[x + z * CELLS];
+        let top = d(ix, iz) * (1.0 - u) + d(ix + 1, iz) * u;
+        let bottom = d(ix, iz + 1) * (1.0 - u) + d(ix + 1, iz + 1) * u;
+        top * (1.0 - v) + bottom * v
+    }
+}
+
+struct Heightmap {
+    heights: Vec<f32>,
+    size: usize,
+}
+
+impl Heightmap {
+    /// Height and gradient at a fractional cell position (bilinear)
+    fn height_and_gradient(&self, x: f32, z: f32) -> (f32, f32, f32) {
+        let (ix, iz) = (x as usize, z as usize);
+        let (u, v) = (x - ix as f32, z - iz as f32);
+        let h = |x: usize, z: usize| self.heights[x + z * self.size];
+        let (nw, ne, sw, se) = (h(ix, iz), h(ix + 1, iz), h(ix, iz + 1), h(ix + 1, iz + 1));
+
+        let grad_x = (ne - nw) * (1.0 - v) + (se - sw) * v;
+        let grad_z = (sw - nw) * (1.0 - u) + (se - ne) * u;
+        let height = nw * (1.0 - u) * (1.0 - v) + ne * u * (1.0 - v) + sw * (1.0 - u) * v + se * u * v;
+        (height, grad_x, grad_z)
+    }
+
+    /// Add (or with a negative amount remove) material at a fractional cell position
+    fn add(&mut self, x: f32, z: f32, amount: f32) {
+        let (ix, iz) = (x as usize, z as usize);
+        let (u, v) = (x - ix as f32, z - iz as f32);
+        let size = self.size;
+        self.heights[ix + iz * size] += amount * (1.0 - u) * (1.0 - v);
+        self.heights[ix + 1 + iz * size] += amount * u * (1.0 - v);
+        self.heights[ix + (iz + 1) * size] += amount * (1.0 - u) * v;
+        self.heights[ix + 1 + (iz + 1) * size] += amount * u * v;
+    }
+
+    /// Let one water droplet run downhill, eroding and depositing sediment
+    fn simulate_droplet(&mut self, mut x: f32, mut z: f32, erode_speed: f32, deposit_speed: f32) {
+        let (mut dir_x, mut dir_z) = (0.0f32, 0.0f32);
+        let (mut speed, mut water, mut sediment) = (1.0f32, 1.0f32, 0.0f32);
+        let max = (self.size - 1) as f32;
+
+        for _ in 0..MAX_DROPLET_STEPS {
+            let (height, grad_x, grad_z) = self.height_and_gradient(x, z);
+
+            // keep some momentum, otherwise follow the slope
+            dir_x = dir_x * INERTIA - grad_x * (1.0 - INERTIA);
+            dir_z = dir_z * INERTIA - grad_z * (1.0 - INERTIA);
+            let len = (dir_x * dir_x + dir_z * dir_z).sqrt();
+            if len < 1e-6 {
+                break;
+            }
+            dir_x /= len;
+            dir_z /= len;
+
+            let (new_x, new_z) = (x + dir_x, z + dir_z);
+            if new_x < 0.0 || new_z < 0.0 || new_x >= max || new_z >= max {
+                break;
+            }
+
+            let dh = self.height_and_gradient(new_x, new_z).0 - height;
+            let capacity = (-dh * speed * water * CAPACITY_FACTOR).max(MIN_CAPACITY);
+
+            if sediment > capacity || dh > 0.0 {
+                // uphill: fill the pit behind, otherwise drop what can't be carried
+                let deposit = if dh > 0.0 {
+                    dh.min(sediment)
+                } else {
+                    (sediment - capacity) * deposit_speed
+                };
+                sediment -= deposit;
+                self.add(x, z, deposit);
+            } else {
+                // never dig deeper than the step down, that would create spikes
+                let erode = ((capacity - sediment) * erode_speed).min(-dh);
+                self.add(x, z, -erode);
+                sediment += erode;
+            }
+
+            speed = (speed * speed - dh * GRAVITY).max(0.0).sqrt();
+            water *= 1.0 - EVAPORATION;
+            x = new_x;
+            z = new_z;
+        }
+    }
+
+    /// Thermal erosion: move material down slopes steeper than `talus` (per cell)
+    fn thermal(&mut self, talus: f32) {
+        let size = self.size;
+        for _ in 0..THERMAL_ITERATIONS {
+            for iz in 0..size - 1 {
+                for ix in 0..size - 1 {
+                    let i = ix + iz * size;
+                    for j in [i + 1, i + size] {
+                        let diff = self.heights[i] - self.heights[j];
+                        if diff.abs() > talus {
+                            let moved = (diff.abs() - talus) * 0.25 * diff.signum();
+                            self.heights[i] -= moved;
+                            self.heights[j] += moved;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Small deterministic xorshift generator for droplet positions
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u32, key: (i32, i32)) -> Self {
+        let state = (seed as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+            ^ (key.0 as i64 as u64).wrapping_mul(0xbf58_476d_1ce4_e5b9)
+            ^ (key.1 as i64 as u64).wrapping_mul(0x94d0_49bb_1331_11eb);
+        // xorshift must not start at 0
+        Self(state | 1)
+    }
+
+    /// Uniform value in [0, 1)
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::world::terrain::TerrainConfig;
+
+    #[test]
+    fn test_erosion_is_deterministic_and_seamless() {
+        let config = TerrainConfig { erosion: true, ..TerrainConfig::default() };
+        let generator = VoxelDensityGenerator::with_config(config);
+        let (a, b) = (ErosionCache::default(), ErosionCache::default());
+
+        // load tiles in different orders, results must not depend on it
+        let _ = b.delta(&generator, 400.0, -300.0);
+        for x in 120..136 {
+            let wx = x as f32;
+            assert_eq!(a.delta(&generator, wx, 40.0), b.delta(&generator, wx, 40.0));
+        }
+
+        // no jumps where the tile blend weights change (x = 128 is a tile center line)
+        for x in 120..136 {
+            let step = a.delta(&generator, x as f32 + 1.0, 40.0) - a.delta(&generator, x as f32, 40.0);
+            assert!(step.abs() < 4.0, "erosion delta jumps by {step} at x = {x}");
+        }
+
+        // and erosion actually reshapes the heightfield: rivers cut in, sediment piles up
+        let deltas: Vec<f32> = (0..64 * 64)
+            .map(|i| a.delta(&generator, (i % 64) as f32 * 4.0, (i / 64) as f32 * 4.0))
+            .collect();
+        let max_cut = deltas.iter().fold(0.0f32, |m, d| m.min(*d));
+        let max_fill = deltas.iter().fold(0.0f32, |m, d| m.max(*d));
+        assert!(max_cut < -1.0 && max_fill > 0.5, "erosion barely changes the terrain ({max_cut} to {max_fill})");
+    }
+}
@@ -0,0 +1,72 @@
+use super::bounds::WorldBounds;
+use super::chunk::Chunk;
+use super::heightmap::HeightmapGenerator;
+use super::terrain::{ColumnSample, TerrainConfig, VoxelDensityGenerator};
+use super::underworld::UnderworldGenerator;
+use crate::utils::ChunkCoord;
+
+/// Source of the terrain of a world
+pub enum WorldGenerator {
+    /// Procedural terrain from noise (the simple 2D generator)
+    Noise(VoxelDensityGenerator),
+    /// Terrain shape from a painted heightmap, materials and vegetation from noise
+    Heightmap(HeightmapGenerator),
+    /// Closed basalt and blackstone cavern with a lava sea (the underworld dimension)
+    Underworld(UnderworldGenerator),
+}
+
+impl WorldGenerator {
+    /// Generate a chunk, nothing is left below the floor or above the build limit
+    pub fn populate_chunk(&self, chunk: &mut Chunk, chunk_coord: &ChunkCoord) {
+        match self {
+            WorldGenerator::Noise(generator) => generator.populate_chunk_simple(chunk, chunk_coord),
+            WorldGenerator::Heightmap(generator) => generator.populate_chunk(chunk, chunk_coord),
+            WorldGenerator::Underworld(generator) => generator.populate_chunk(chunk, chunk_coord),
+        }
+        self.bounds().clip_chunk(chunk, chunk_coord);
+    }
+
+    pub fn bounds(&self) -> WorldBounds {
+        self.terrain_config().bounds()
+    }
+
+    /// Height, biome and surface block of a column without generating chunks
+    ///
+    /// Used for far terrain, so noise worlds skip the exact biome blend and erosion.
+    pub fn coarse_column(&self, wx: f32, wz: f32) -> ColumnSample {
+        match self {
+            WorldGenerator::Noise(generator) => generator.coarse_column(wx, wz),
+            WorldGenerator::Heightmap(generator) => generator.sample_column(wx, wz),
+            WorldGenerator::Underworld(generator) => generator.sample_column(wx, wz),
+        }
+    }
+
+    /// Noise generator (for heightmaps and the underworld: climate, materials and vegetation)
+    pub fn terrain(&self) -> &VoxelDensityGenerator {
+        match self {
+            WorldGenerator::Noise(generator) => generator,
+            WorldGenerator::Heightmap(generator) => &generator.terrain,
+            WorldGenerator::Underworld(generator) => &generator.terrain,
+        }
+    }
+
+    /// Noise config (for heightmaps: climate, materials and vegetation)
+    pub fn terrain_config(&self) -> &TerrainConfig {
+        &self.terrain().config
+    }
+
+    pub fn set_terrain_config(&mut self, config: TerrainConfig) {
+        // keep the loaded biome definitions, only the noise parameters change
+        match self {
+            WorldGenerator::Noise(generator) => {
+                *generator = VoxelDensityGenerator::with_biomes(config, generator.biomes.clone())
+            }
+            WorldGenerator::Heightmap(generator) => {
+                generator.terrain = VoxelDensityGenerator::with_biomes(config, generator.terrain.biomes.clone())
+            }
+            WorldGenerator::Underworld(generator) => {
+                generator.terrain = VoxelDensityGenerator::with_biomes(config, generator.terrain.biomes.clone())
+            }
+        }
+    }
+}
@@ -0,0 +1,327 @@
+// ============================================================================
+// HEIGHTMAP WORLDS
+// ============================================================================
+//
+// Terrain read from a painted grayscale image instead of noise:
+//   → pixel intensity (0 = black, 1 = white) maps linearly to the column height,
+//     `sea_level` is the intensity that ends exactly at SEA_LEVEL
+//   → an optional biome map (colored with the biome map_color, the same palette
+//     the terrain preview writes) picks the biome per pixel, otherwise the biome
+//     comes from the climate noise of the terrain config and the painted height
+//   → the image is centered on world x = 0, z = 0; outside it is tiled or clamped
+//
+// Surface/subsurface blocks, trees and plants use the same functions as the noise
+// generator, so a heightmap world looks like a generated one with a designed shape.
+// ============================================================================
+
+use super::biome::BiomeWeights;
+use super::block::Block;
+use super::chunk::{Chunk, CHUNK_SIZE};
+use super::biome_def::{BiomeId, Zone};
+use super::decorations;
+use super::terrain::{ColumnSample, VoxelDensityGenerator, SEA_LEVEL};
+use super::trees::{self, TreeAnchor};
+use crate::utils::{BlockCoord, ChunkCoord};
+
+/// How an image is mapped onto the world
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeightmapConfig {
+    /// Blocks per pixel along x and z (heights are interpolated between pixels)
+    pub horizontal_scale: f32,
+    /// Height difference in blocks between a black and a white pixel
+    pub vertical_scale: f32,
+    /// Intensity (0..1) of the coast line, darker pixels are under water
+    pub sea_level: f32,
+    /// Repeat the image outside its bounds instead of extending the edge pixels
+    pub tile: bool,
+}
+
+impl Default for HeightmapConfig {
+    fn default() -> Self {
+        Self {
+            horizontal_scale: 1.0,
+            vertical_scale: 128.0,
+            sea_level: 0.25,
+            tile: false,
+        }
+    }
+}
+
+impl HeightmapConfig {
+    /// Set a field by name from a string value (e.g. a URL parameter)
+    pub fn set_field(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        let number = || value.parse::<f32>().map_err(|e| format!("{name}: {e}"));
+        match name {
+            "horizontal_scale" => self.horizontal_scale = number()?.max(0.01),
+            "vertical_scale" => self.vertical_scale = number()?,
+            "sea_level" => self.sea_level = number()?,
+            "tile" => self.tile = matches!(value, "1" | "true"),
+            _ => return Err(format!("unknown heightmap field '{name}'")),
+        }
+        Ok(())
+    }
+}
+
+/// Decoded image with one value per pixel
+struct Raster<T> {
+    width: usize,
+    height: usize,
+    pixels: Vec<T>,
+}
+
+impl<T: Copy> Raster<T> {
+    /// Pixel at integer image coordinates, tiled or clamped outside the image
+    fn get(&self, px: i64, pz: i64, tile: bool) -> T {
+        let (w, h) = (self.width as i64, self.height as i64);
+        let (px, pz) = if tile {
+            (px.rem_euclid(w), pz.rem_euclid(h))
+        } else {
+            (px.clamp(0, w - 1), pz.clamp(0, h - 1))
+        };
+        self.pixels[px as usize + pz as usize * self.width]
+    }
+}
+
+/// World generator that reads the terrain shape from a heightmap image
+pub struct HeightmapGenerator {
+    pub config: HeightmapConfig,
+    /// Supplies surface materials, trees, plants and (without biome map) the climate
+    pub terrain: VoxelDensityGenerator,
+    heights: Raster<f32>,
+    biomes: Option<Raster<BiomeId>>,
+}
+
+impl HeightmapGenerator {
+    /// Decode a heightmap (and optional biome map) from PNG or WebP bytes
+    ///
+    /// 16-bit grayscale PNGs keep their full precision. The biome map may have a
+    /// different resolution; it is stretched over the area of the heightmap. Its
+    /// colors are matched against the biomes of `terrain`.
+    pub fn from_memory(
+        heightmap: &[u8],
+        biome_map: Option<&[u8]>,
+        config: HeightmapConfig,
+        terrain: VoxelDensityGenerator,
+    ) -> Result<Self, String> {
+        let image = image::load_from_memory(heightmap).map_err(|e| format!("heightmap: {e}"))?.to_luma16();
+        let heights = Raster {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            pixels: image.pixels().map(|p| p.0[0] as f32 / u16::MAX as f32).collect(),
+        };
+        if heights.pixels.is_empty() {
+            return Err("heightmap: image is empty".to_string());
+        }
+
+        let biomes = match biome_map {
+            Some(bytes) => {
+                let image = image::load_from_memory(bytes).map_err(|e| format!("biome map: {e}"))?.to_rgb8();
+                if image.width() == 0 || image.height() == 0 {
+                    return Err("biome map: image is empty".to_string());
+                }
+                Some(Raster {
+                    width: image.width() as usize,
+                    height: image.height() as usize,
+                    pixels: image.pixels().map(|p| terrain.biomes.nearest_map_color(p.0)).collect(),
+                })
+            }
+            None => None,
+        };
+
+        Ok(Self { config, terrain, heights, biomes })
+    }
+
+    /// Read a heightmap (and optional biome map) from files
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(
+        heightmap: &std::path::Path,
+        biome_map: Option<&std::path::Path>,
+        config: HeightmapConfig,
+        terrain: VoxelDensityGenerator,
+    ) -> Result<Self, String> {
+        let read = |path: &std::path::Path| std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()));
+        let biome_bytes = biome_map.map(read).transpose()?;
+        Self::from_memory(&read(heightmap)?, biome_bytes.as_deref(), config, terrain)
+    }
+
+    /// Heightmap position (in pixels) of a world column
+    fn pixel_position(&self, wx: f32, wz: f32) -> (f32, f32) {
+        let scale = self.config.horizontal_scale.max(0.01);
+        (
+            wx / scale + self.heights.width as f32 * 0.5,
+            wz / scale + self.heights.height as f32 * 0.5,
+        )
+    }
+
+    /// Bilinearly interpolated intensity (0..1) at a world column
+    fn intensity(&self, wx: f32, wz: f32) -> f32 {
+        // pixel centers are at +0.5
+        let (px, pz) = self.pixel_position(wx, wz);
+        let (px, pz) = (px - 0.5, pz - 0.5);
+        let (x0, z0) = (px.floor() as i64, pz.floor() as i64);
+        let (fx, fz) = (px - x0 as f32, pz - z0 as f32);
+
+        let tile = self.config.tile;
+        let top = self.heights.get(x0, z0, tile) * (1.0 - fx) + self.heights.get(x0 + 1, z0, tile) * fx;
+        let bottom = self.heights.get(x0, z0 + 1, tile) * (1.0 - fx) + self.heights.get(x0 + 1, z0 + 1, tile) * fx;
+        top * (1.0 - fz) + bottom * fz
+    }
+
+    /// First world y above the terrain of a column (the surface block is at height - 1)
+    pub fn height(&self, wx: f32, wz: f32) -> isize {
+        let relative = (self.intensity(wx, wz) - self.config.sea_level) * self.config.vertical_scale;
+        (SEA_LEVEL as f32 + 1.0 + relative).round() as isize
+    }
+
+    /// Biome of a column: painted in the biome map, or from climate and height
+    fn biome(&self, wx: f32, wz: f32, height: isize) -> BiomeId {
+        if let Some(biomes) = &self.biomes {
+            // stretch the biome map over the heightmap area
+            let (px, pz) = self.pixel_position(wx, wz);
+            let bx = px * biomes.width as f32 / self.heights.width as f32;
+            let bz = pz * biomes.height as f32 / self.heights.height as f32;
+            return biomes.get(bx.floor() as i64, bz.floor() as i64, self.config.tile);
+        }
+
+        // the painted coast line replaces the continentalness based oceans
+        let depth = SEA_LEVEL + 1 - height;
+        let zone = if depth > 20 {
+            Zone::DeepOcean
+        } else if depth > 0 {
+            Zone::Ocean
+        } else if depth > -3 {
+            Zone::Coast
+        } else {
+            Zone::Land
+        };
+        self.terrain.classify_biome(wx, wz, height as f32, zone)
+    }
+
+    /// Biome, height and surface block of a column
+    pub fn sample_column(&self, wx: f32, wz: f32) -> ColumnSample {
+        let height = self.height(wx, wz);
+        let biome = self.biome(wx, wz, height);
+        let weights = BiomeWeights::single(biome);
+        let tint = self.terrain.biome_tint(&weights);
+        let surface_block = self.terrain.get_surface_block_for_biome(wx, wz, (height - 1) as f32, biome);
+
+        ColumnSample { weights, biome, height, surface_block, tint, erosion: 0.0 }
+    }
+
+    /// Populate a chunk from the heightmap
+    ///
+    /// Water fills everything above the terrain up to SEA_LEVEL. Trees and plants
+    /// grow on grass and moss and biome decorations are placed like in the noise
+    /// generators.
+    pub fn populate_chunk(&self, chunk: &mut Chunk, chunk_coord: &ChunkCoord) {
+        let world_coord = chunk_coord.to_world_coord();
+
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let wx = world_coord.0 as f32 + x as f32;
+                let wz = world_coord.2 as f32 + z as f32;
+
+                let column = self.sample_column(wx, wz);
+                let (biome, terrain_height) = (column.biome, column.height);
+                chunk.set_tint(x as usize, z as usize, column.tint);
+                let seabed_plant = self.terrain.seabed_plant(wx, wz, terrain_height);
+                let water = self.terrain.biomes.get(biome).water();
+                let is_tree_center = self.terrain.calculate_tree_data(wx, wz, biome).should_spawn;
+
+                for y in 0..CHUNK_SIZE {
+                    let world_y = chunk_coord.1 * CHUNK_SIZE + y;
+
+                    let block = if world_y >= terrain_height {
+                        match seabed_plant {
+                            Some((plant, top)) if world_y < top => plant,
+                            _ if world_y <= SEA_LEVEL => water,
+                            _ => Block::Empty,
+                        }
+                    } else if world_y == terrain_height - 1 {
+                        column.surface_block
+                    } else {
+                        self.terrain.get_subsurface_block(wx, wz, world_y as f32, biome)
+                    };
+                    chunk.set_block(&BlockCoord(x as usize, y as usize, z as usize), block, false);
+
+                    let is_grass = world_y == terrain_height - 1 && matches!(block, Block::Grass | Block::Moss);
+                    if is_grass && !is_tree_center && world_y > SEA_LEVEL && y + 1 < CHUNK_SIZE {
+                        if let Some(plant) = self.terrain.plant_block(wx, wz, biome) {
+                            chunk.set_block(&BlockCoord(x as usize, y as usize + 1, z as usize), plant, false);
+                        }
+                    }
+                }
+            }
+        }
+
+        let ground = |x: i32, z: i32| {
+            let height = self.height(x as f32, z as f32);
+            (height, self.biome(x as f32, z as f32, height))
+        };
+        decorations::decorate_chunk(chunk, chunk_coord, &self.terrain.biomes, self.terrain.config.seed, &ground);
+        let mut tree_anchors = self.tree_anchors(chunk_coord);
+        trees::place_trees(chunk, chunk_coord, &mut tree_anchors, self.terrain.config.seed);
+    }
+
+    /// Trees on grass and moss of the columns around a chunk, they may reach into it
+    fn tree_anchors(&self, chunk_coord: &ChunkCoord) -> Vec<TreeAnchor> {
+        let mut anchors = Vec::new();
+        for (x, z) in trees::anchor_columns(chunk_coord) {
+            let (wx, wz) = (x as f32, z as f32);
+            if !self.terrain.is_tree_center(wx, wz) || !trees::reaches_layer(self.height(wx, wz) as i32, chunk_coord.1) {
+                continue;
+            }
+            let column = self.sample_column(wx, wz);
+            let tree_data = self.terrain.calculate_tree_data(wx, wz, column.biome);
+            if tree_data.should_spawn && matches!(column.surface_block, Block::Grass | Block::Moss) {
+                anchors.push(tree_data.anchor(x, column.height, z));
+            }
+        }
+        anchors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, ImageFormat, Luma};
+    use std::io::Cursor;
+
+    fn encode(image: GrayImage) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_heightmap_maps_intensity_and_edges() {
+        // 4x1 ramp: black, dark, light, white
+        let ramp = GrayImage::from_fn(4, 1, |x, _| Luma([(x * 85) as u8]));
+        let config = HeightmapConfig { vertical_scale: 100.0, sea_level: 0.0, ..HeightmapConfig::default() };
+        let clamped = HeightmapGenerator::from_memory(&encode(ramp.clone()), None, config, VoxelDensityGenerator::new()).unwrap();
+
+        // image spans world x -2..2, pixel centers at -1.5, -0.5, 0.5, 1.5
+        assert_eq!(clamped.height(-1.5, 0.0), SEA_LEVEL + 1);
+        assert_eq!(clamped.height(1.5, 0.0), SEA_LEVEL + 101);
+        assert_eq!(clamped.height(0.0, 0.0), SEA_LEVEL + 51);
+        // clamped: the white edge continues, tiled: the ramp starts over
+        assert_eq!(clamped.height(100.5, 0.0), SEA_LEVEL + 101);
+        // URL values may come with spaces around them
+        let mut tile = config;
+        tile.set_field("tile", " true ").unwrap();
+        let tiled = HeightmapGenerator::from_memory(&encode(ramp), None, tile, VoxelDensityGenerator::new()).unwrap();
+        assert_eq!(tiled.height(2.5, 0.0), SEA_LEVEL + 1);
+
+        // dark pixels below the sea level intensity are under water
+        let mut chunk = Chunk::new_empty();
+        let flat = GrayImage::from_pixel(8, 8, Luma([0]));
+        let sea = HeightmapGenerator::from_memory(
+            &encode(flat), None, HeightmapConfig { sea_level: 0.5, ..config }, VoxelDensityGenerator::new(),
+        ).unwrap();
+        sea.populate_chunk(&mut chunk, &ChunkCoord(0, 0, 0));
+        assert_eq!(chunk.get_block(&BlockCoord(0, SEA_LEVEL as usize, 0)), Block::Water);
+        let biomes = &sea.terrain.biomes;
+        assert_eq!(biomes.nearest_map_color([238, 218, 128]), biomes.id("beach").unwrap());
+    }
+}
@@ -1,7 +1,19 @@
+pub mod biome;
+pub mod biome_def;
 pub mod block;
+pub mod bounds;
 pub mod chunk;
+pub mod decorations;
+pub mod erosion;
+pub mod generator;
+pub mod heightmap;
 pub mod terrain;
+pub mod trees;
+pub mod underworld;
 
-pub use block::Block;
+pub use biome_def::{BiomeDefinition, BiomeId, BiomeRegistry};
+pub use block::{Block, Tint};
+pub use bounds::WorldBounds;
 pub use chunk::{Chunk, CHUNK_SIZE};
+pub use generator::WorldGenerator;
 pub use terrain::VoxelDensityGenerator;
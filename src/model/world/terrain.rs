@@ -9,8 +9,10 @@
 //
 // STEP 1: Biome Determination (2D Noise)
 //   → Uses 2D perlin noise to determine biome type from temperature & humidity
-//   → Biomes: Tundra, Mountain, Forest, Desert, Beach, Plain, Ocean
-//   → Called by: get_biome_type()
+//   → Biomes: Tundra, Mountain, Forest, Desert, Beach, Plain, Ocean, DeepOcean
+//   → Oceans, deep oceans and beaches come from low continentalness
+//   → Biomes are blended over a small radius for smooth transitions (biome.rs)
+//   → Called by: get_biome_type(), BiomeBlender
 //
 // STEP 2: Density Calculation (3D Noise with Gravity)
 //   → Uses 3D FBM noise to calculate terrain density at each (x,y,z) position
@@ -21,10 +23,12 @@
 // STEP 3: Cave Carving (3D Noise Ranges)
 //   → During density calculation, specific noise ranges force air (caves)
 //   → Creates natural cave systems integrated with terrain
-//   → Included in: calculate_density()
+//   → Cave biomes (lush, dripstone, basalt deltas) are picked in 3D and
+//     decorate floors, ceilings and the air next to them
+//   → Included in: calculate_density(), get_cave_biome()
 //
 // STEP 4: Water/Terrain Filling (Y-Level Checks)
-//   → If y <= 0 (below sea level): place water
+//   → If y <= SEA_LEVEL: place water (Block::Water, LakeWater in inland biomes)
 //   → If y > 0 but no solid density: place air
 //   → Creates natural water bodies at sea level
 //   → Implemented in: populate_chunk()
@@ -33,35 +37,130 @@
 //   → Use 2D noise to determine tree centers
 //   → Only place trees on Grass/Moss surface blocks
 //   → Tree type determined by biome
-//   → Tree height randomized per position
-//   → Implemented in: populate_chunk() and plant_tree()
+//   → Tree height randomized per position, shape grown per position (trees.rs)
+//   → Trees of neighboring columns reach into the chunk
+//   → Implemented in: tree_anchors(), density_tree_anchors() and trees::place_trees()
+//   → Before the trees, the simple generator places the biome's surface
+//     decorations (boulders, logs, ponds, ice, snow...) in decorations.rs
 //
-// STEP 6: Clouds (Y == 255)
-//   → 2D noise determines cloud coverage at height 255
-//   → Only top of world
-//   → Implemented in: populate_chunk()
+// STEP 6: Clouds (Y == cloud_height)
+//   → No blocks: the sky pass draws a cloud layer at cloud_height (255 by
+//     default), see view/sky.rs
+//
+// Optional: Sky Islands (second density field in a high altitude band)
+//   → 2D mask picks island areas, islands have a flat grassy top and a stone
+//     underside tapering to a point, roughened by 3D noise
+//   → Rim columns may carry a waterfall down to the ground
+//   → Band stays below the cloud layer
+//   → Called by: calculate_density(), populate_chunk(), populate_chunk_simple()
 //
 // Result: Coherent, natural terrain with forests, mountains, caves, and water!
 //
 
-use super::block::Block;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use super::biome::{column_random, BiomeBlender, BiomeWeights};
+use super::biome_def::{BiomeDefinition, BiomeId, BiomeRegistry, Climate, Zone};
+use super::erosion::ErosionCache;
+use super::block::{Block, Tint};
+use super::bounds::WorldBounds;
 use super::chunk::CHUNK_SIZE;
+use super::decorations;
+use super::trees::{self, TreeAnchor, TreeType, TREE_REACH};
+
+/// Highest world y filled with sea water
+pub const SEA_LEVEL: isize = 0;
+
+/// Highest sky island mask value (fbm rarely exceeds it, see hash2d)
+const SKY_ISLAND_MASK_MAX: f32 = -0.2;
 
 // ============================================================================
 // DATA STRUCTURES
 // ============================================================================
 
 /// Tree properties calculated from biome and 2D noise
-struct TreeData {
-    tree_type: TreeType,
-    tree_height: i32,
-    should_spawn: bool,
+pub(super) struct TreeData {
+    pub(super) tree_type: TreeType,
+    pub(super) tree_height: i32,
+    pub(super) should_spawn: bool,
+}
+
+impl TreeData {
+    /// Tree of this column growing from world y (the block above the ground)
+    pub(super) fn anchor(&self, x: i32, y: isize, z: i32) -> TreeAnchor {
+        TreeAnchor {
+            pos: (x, y as i32, z),
+            tree_type: self.tree_type,
+            height: self.tree_height,
+        }
+    }
+}
+
+/// How far below a chunk the density generator looks for the sea floor its kelp grows from
+const SEA_FLOOR_SCAN: isize = 2 * CHUNK_SIZE;
+
+/// Longest vine or stalactite hanging from a cave ceiling
+const CAVE_HANGING_MAX: isize = 4;
+
+/// Cells of a CaveColumn: one below the chunk, the chunk, and room for hanging plants above
+const CAVE_COLUMN_LEN: usize = CHUNK_SIZE as usize + 2 + CAVE_HANGING_MAX as usize;
+
+/// Carved (cave air) and solid cells of a column around one chunk
+///
+/// Precomputed per column so floors, ceilings and hanging plants can look at
+/// their neighbors without evaluating the cave noise again. Open air above the
+/// terrain is neither carved nor solid.
+struct CaveColumn {
+    base_y: isize,
+    solid: [bool; CAVE_COLUMN_LEN],
+    cave: [bool; CAVE_COLUMN_LEN],
+}
+
+impl CaveColumn {
+    fn index(&self, world_y: isize) -> Option<usize> {
+        usize::try_from(world_y - self.base_y).ok().filter(|i| *i < CAVE_COLUMN_LEN)
+    }
+
+    fn is_cave(&self, world_y: isize) -> bool {
+        self.index(world_y).is_some_and(|i| self.cave[i])
+    }
+
+    fn is_solid(&self, world_y: isize) -> bool {
+        self.index(world_y).is_some_and(|i| self.solid[i])
+    }
+}
+
+/// Vertical extent of a sky island over a column, before 3D detail
+struct SkyIslandShape {
+    bottom: f32,
+    top: f32,
+    /// Rim column with water running off the island
+    waterfall: bool,
 }
 
 // ============================================================================
 // NOISE FUNCTIONS
 // ============================================================================
 
+/// Hash function: converts 2D integer to pseudo-random [-1, 0)
+///
+/// The final xor with the sign-extended shift always clears the sign bit, so only
+/// the lower half of [-1, 1) is reached. Worlds depend on it, use hash01 for [0, 1).
+pub(crate) fn hash2d(x: i32, z: i32) -> f32 {
+    let mut n = x.wrapping_mul(374761393).wrapping_add(z.wrapping_mul(668265263));
+    n = (n ^ (n >> 13)).wrapping_mul(1274126177);
+    ((n ^ (n >> 16)) as u32 as f32 / 4294967296.0) * 2.0 - 1.0
+}
+
+/// Hash function: converts 2D integer to pseudo-random [0, 1)
+pub(crate) fn hash01(x: i32, z: i32) -> f32 {
+    hash2d(x, z) + 1.0
+}
+
 /// 2D Perlin Noise using gradient hash
 fn noise2d(x: f32, z: f32) -> f32 {
     let ix = x.floor() as i32;
@@ -69,23 +168,16 @@ fn noise2d(x: f32, z: f32) -> f32 {
     let fx = x - ix as f32;
     let fz = z - iz as f32;
     
-    // Hash function: converts 2D integer to pseudo-random [-1, 1]
-    let hash = |x: i32, z: i32| -> f32 {
-        let mut n = x.wrapping_mul(374761393).wrapping_add(z.wrapping_mul(668265263));
-        n = (n ^ (n >> 13)).wrapping_mul(1274126177);
-        ((n ^ (n >> 16)) as u32 as f32 / 4294967296.0) * 2.0 - 1.0
-    };
-    
     // Fade curve: smooth interpolation
     let fade = |t: f32| t * t * (3.0 - 2.0 * t);
     let u = fade(fx);
     let v = fade(fz);
     
     // Sample 4 corner gradients and interpolate
-    let a = hash(ix, iz);
-    let b = hash(ix + 1, iz);
-    let c = hash(ix, iz + 1);
-    let d = hash(ix + 1, iz + 1);
+    let a = hash2d(ix, iz);
+    let b = hash2d(ix + 1, iz);
+    let c = hash2d(ix, iz + 1);
+    let d = hash2d(ix + 1, iz + 1);
     
     let x1 = a * (1.0 - u) + b * u;
     let x2 = c * (1.0 - u) + d * u;
@@ -138,40 +230,36 @@ pub fn fbm_3d(x: f32, y: f32, z: f32, base_freq: f32, gain: f32, octaves: u32) -
 // ============================================================================
 // BIOME TYPES AND TREE GENERATION
 // ============================================================================
-
-#[derive(Clone, Copy, Debug)]
-pub enum BiomeType {
-    Ocean,
-    Beach,
-    Plain,
-    Forest,
-    Mountain,
-    Tundra,
-    Desert,
-    Cliff,      // Steile Klippen mit Basalt
-    Lake,       // Seen/Seen-Biom
-    Jungle,     // Dschungel mit Acacia/DarkOak
-}
-
-#[derive(Clone, Copy, Debug)]
-pub enum TreeType {
-    Oak,
-    Spruce,
-    Birch,
-    Acacia,     // Baum für Trockengebiete/Jungle
-    DarkOak,    // Großer Baum
-}
-
-pub struct Tree {
-    pub pos: (i32, i32),  // (x, z) in chunk
-    pub tree_type: TreeType,
-    pub trunk_height: i32,
+// Surface biomes are data (BiomeDefinition, see biome_def.rs).
+
+/// Underground biome of caves, selected in 3D (see get_cave_biome)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaveBiome {
+    /// Plain stone caves
+    Stone,
+    /// Moss on floors and ceilings, hanging vines
+    LushCaves,
+    /// Dripstone floors and ceilings with stalactites and stalagmites
+    DripstoneCaves,
+    /// Basalt floors with magma pools under blackstone ceilings
+    BasaltDeltas,
 }
 
 // ============================================================================
 // TERRAIN CONFIGURATION
 // ============================================================================
 
+/// Built-in terrain presets (name, file contents), shipped in `presets/`
+pub const TERRAIN_PRESETS: &[(&str, &str)] = &[
+    ("default", include_str!("presets/default.toml")),
+    ("amplified", include_str!("presets/amplified.toml")),
+    ("archipelago", include_str!("presets/archipelago.toml")),
+    ("flat_plains", include_str!("presets/flat_plains.toml")),
+    ("cave_world", include_str!("presets/cave_world.toml")),
+    ("sky_islands", include_str!("presets/sky_islands.toml")),
+    ("sky_world", include_str!("presets/sky_world.toml")),
+];
+
 /// Configuration for terrain generation parameters
 /// 
 /// Usage:
@@ -183,7 +271,15 @@ pub struct Tree {
 ///   config.tree_spawn_threshold = 0.2;  // Fewer trees
 ///   config.base_height = 30.0;           // Lower terrain
 ///   let gen = VoxelDensityGenerator::with_config(config);
-#[derive(Clone, Copy, Debug)]
+///
+///   // Or load a named preset / a preset file:
+///   let config = TerrainConfig::preset("archipelago").unwrap();
+///   let config = TerrainConfig::from_toml(&std::fs::read_to_string("my.toml")?)?;
+///
+/// Preset files are TOML with one key per field; fields missing from a file keep
+/// their default value, unknown keys are an error.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct TerrainConfig {
     // Noise frequencies for terrain shape
     pub continentalness_freq: f32,
@@ -211,15 +307,52 @@ pub struct TerrainConfig {
     
     // Lake generation
     pub lake_frequency: f32,
-    pub lake_threshold: f32,
     
     // Cliff generation
-    pub cliff_threshold: f32,
     pub cliff_steepness: f32,
     
     // Vegetation placement
     pub plant_frequency: f32,
     pub plant_density: f32,
+
+    // Biome blending
+    pub biome_blend_radius: f32,
+    pub biome_jitter: f32,
+
+    // Simple generator shape (populate_chunk_simple)
+    pub height_freq: f32,
+    pub height_scale: f32,
+    pub height_offset: f32,
+    pub caves: bool,
+
+    // Oceans (continentalness below the thresholds)
+    pub ocean_threshold: f32,
+    pub deep_ocean_threshold: f32,
+    pub ocean_depth: f32,
+    pub coast_width: f32,
+
+    // Erosion of the simple generator heightmap (erosion.rs)
+    pub erosion: bool,
+    pub erosion_droplets: f32,
+    pub erosion_strength: f32,
+    pub erosion_deposition: f32,
+    pub erosion_talus: f32,
+
+    // Sky islands: band of world y, mask frequency and threshold (higher = fewer islands)
+    pub sky_islands: bool,
+    pub sky_island_min: f32,
+    pub sky_island_max: f32,
+    pub sky_island_freq: f32,
+    pub sky_island_threshold: f32,
+
+    // Vertical world bounds (bounds.rs): blocks exist for min_y <= y < max_y
+    pub min_y: i32,
+    pub max_y: i32,
+    pub bedrock_layers: i32,
+    pub cloud_height: i32,
+
+    // World seed (0 = original world)
+    pub seed: u32,
 }
 
 impl Default for TerrainConfig {
@@ -251,36 +384,202 @@ impl Default for TerrainConfig {
             
             // Lake parameters
             lake_frequency: 0.35,
-            lake_threshold: -0.5,
             
             // Cliff parameters
-            cliff_threshold: 0.75,
             cliff_steepness: 2.0,
             
             // Plant parameters
             plant_frequency: 0.8,
             plant_density: 0.6,
+
+            // Biome blending parameters - radius in blocks, 0 = hard borders
+            biome_blend_radius: 12.0,
+            biome_jitter: 0.6,
+
+            // Simple generator - height noise frequency, amplitude multiplier and
+            // vertical shift around the mid height; caves off keeps the classic look
+            height_freq: 0.08,
+            height_scale: 1.0,
+            height_offset: 0.0,
+            caves: false,
+
+            // Ocean parameters - continentalness is centered around -0.5 (see hash2d),
+            // so -0.6 turns roughly a quarter of the world into sea
+            ocean_threshold: -0.6,
+            deep_ocean_threshold: -0.7,
+            ocean_depth: 40.0,
+            coast_width: 0.15,
+
+            // Erosion parameters - droplets per heightmap cell, how fast droplets
+            // carve and drop sediment, steepest stable slope (blocks per block)
+            erosion: false,
+            erosion_droplets: 1.0,
+            erosion_strength: 0.3,
+            erosion_deposition: 0.3,
+            erosion_talus: 1.5,
+
+            // Sky island parameters - islands float between min and max (kept
+            // below cloud_height), threshold -0.36 covers roughly an eighth of the sky
+            sky_islands: false,
+            sky_island_min: 170.0,
+            sky_island_max: 230.0,
+            sky_island_freq: 0.012,
+            sky_island_threshold: -0.36,
+
+            // World bounds - 8 chunks below sea level, 20 above, clouds just below y=256
+            min_y: -128,
+            max_y: 320,
+            bedrock_layers: 5,
+            cloud_height: 255,
+
+            seed: 0,
+        }
+    }
+}
+
+impl TerrainConfig {
+    /// Set a single field by name from a TOML value (`0.3`, `true`, ...), used by
+    /// tools, the command line and the page URL
+    pub fn set_field(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let value = value
+            .trim()
+            .parse::<toml::Value>()
+            .map_err(|_| format!("invalid value '{value}' for '{name}'"))?;
+        self.set_value(name, value)
+    }
+
+    /// Set a single field by name, the value must have the type of the field
+    pub fn set_value(&mut self, name: &str, value: toml::Value) -> Result<(), String> {
+        let mut fields = self.fields();
+        match fields.get_mut(name) {
+            Some(field) => *field = value,
+            None => return Err(format!("unknown terrain config field '{name}'")),
+        }
+        *self = fields
+            .try_into()
+            .map_err(|e: toml::de::Error| format!("invalid value for '{name}': {}", e.message()))?;
+        Ok(())
+    }
+
+    /// All fields by name with their current value, in declaration order
+    pub fn fields(&self) -> toml::Table {
+        toml::Table::try_from(self).expect("terrain config serializes to a table")
+    }
+
+    /// Parse a preset file
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Write all fields as a preset file
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("terrain config serializes to TOML")
+    }
+
+    /// Built-in preset by name (see TERRAIN_PRESETS)
+    pub fn preset(name: &str) -> Option<Self> {
+        TERRAIN_PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, text)| Self::from_toml(text).expect("built-in terrain preset must parse"))
+    }
+
+    /// Vertical bounds of the world (floor, build limit, bedrock and clouds)
+    pub fn bounds(&self) -> WorldBounds {
+        WorldBounds {
+            min_y: self.min_y as isize,
+            max_y: self.max_y as isize,
+            bedrock_layers: self.bedrock_layers.max(0) as isize,
+            cloud_height: self.cloud_height as isize,
         }
     }
 }
 
+/// Everything the simple generator knows about a single (x, z) column
+pub struct ColumnSample {
+    /// Blended biome weights around the column
+    pub weights: BiomeWeights,
+    /// Biome used for surface materials and trees (jittered pick from weights)
+    pub biome: BiomeId,
+    /// First world y above the terrain (the surface block is at height - 1)
+    pub height: isize,
+    /// Block at the top of the column
+    pub surface_block: Block,
+    pub tint: Tint,
+    /// Height change by erosion (negative = gully, positive = sediment), 0 without erosion
+    pub erosion: f32,
+}
+
 // ============================================================================
 // VOXEL DENSITY GENERATOR
 // ============================================================================
 
 pub struct VoxelDensityGenerator {
     pub config: TerrainConfig,
+    /// Biome definitions used for climate, surface blocks, trees and plants
+    pub biomes: Rc<BiomeRegistry>,
+    /// Eroded heightmap tiles, only used with `config.erosion`
+    erosion_cache: ErosionCache,
 }
 
 impl VoxelDensityGenerator {
     pub fn new() -> Self {
-        Self {
-            config: TerrainConfig::default(),
-        }
+        Self::with_config(TerrainConfig::default())
     }
     
     pub fn with_config(config: TerrainConfig) -> Self {
-        Self { config }
+        Self::with_biomes(config, BiomeRegistry::builtin())
+    }
+
+    /// Generator with custom biome definitions (e.g. the built-in set extended by a mod)
+    pub fn with_biomes(config: TerrainConfig, biomes: Rc<BiomeRegistry>) -> Self {
+        Self { config, biomes, erosion_cache: ErosionCache::default() }
+    }
+
+    // ------------------------------------------------------------------------
+    // Seeded noise: every generator noise lookup goes through these wrappers,
+    // which shift the sample position by a seed-dependent offset.
+    // ------------------------------------------------------------------------
+
+    /// World offset applied to all noise lookups for the configured seed
+    fn seed_offset(&self) -> (f32, f32) {
+        if self.config.seed == 0 {
+            return (0.0, 0.0); // seed 0 keeps the original world
+        }
+        let seed = self.config.seed as i32;
+        (hash2d(seed, 0x5EED) * 50000.0, hash2d(0x5EED, seed) * 50000.0)
+    }
+
+    fn noise2d(&self, x: f32, z: f32) -> f32 {
+        let (ox, oz) = self.seed_offset();
+        noise2d(x + ox, z + oz)
+    }
+
+    pub(super) fn fbm(&self, x: f32, z: f32, base_freq: f32, gain: f32, octaves: u32) -> f32 {
+        let (ox, oz) = self.seed_offset();
+        fbm(x + ox, z + oz, base_freq, gain, octaves)
+    }
+
+    pub(super) fn fbm_3d(&self, x: f32, y: f32, z: f32, base_freq: f32, gain: f32, octaves: u32) -> f32 {
+        let (ox, oz) = self.seed_offset();
+        fbm_3d(x + ox, y, z + oz, base_freq, gain, octaves)
+    }
+
+    /// Raw cave noise at a position; caves are carved where it lies in
+    /// (cave_noise_min, cave_noise_max)
+    pub fn cave_noise(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.fbm_3d(x, y, z, self.config.cave_freq, 0.55, 3)
+    }
+
+    /// Continentalness of a column: low = ocean, around the threshold = coast, high = inland
+    pub fn continentalness(&self, x: f32, z: f32) -> f32 {
+        self.fbm(x, z, self.config.continentalness_freq, 0.55, 4)
+    }
+
+    /// Whether the cave noise carves out this position
+    fn is_cave(&self, x: f32, y: f32, z: f32) -> bool {
+        let cave_noise = self.cave_noise(x, y, z);
+        cave_noise > self.config.cave_noise_min && cave_noise < self.config.cave_noise_max
     }
 
     /// Calculate 3D density at position (x, y, z) - STEP 2 OF GENERATION PIPELINE
@@ -295,17 +594,34 @@ impl VoxelDensityGenerator {
     ///   > 0 = solid block
     ///   <= 0 = air/empty/caves
     pub fn calculate_density(&self, x: f32, y: f32, z: f32) -> f32 {
-        // 1. Continentalness: determines mountain vs plateau heights
-        let continentalness = fbm(x, z, self.config.continentalness_freq, 0.55, 4);
+        let mut density = self.ground_density(x, y, z);
+
+        // 7. STEP 3 - Cave carving: if cave noise is in narrow band, force air
+        if self.is_cave(x, y, z) {
+            return -1.0; // Force air (caves)
+        }
+
+        // 8. Sky islands: second density field, only inside their altitude band
+        if let Some(shape) = self.sky_island_shape(x, z) {
+            density = density.max(self.sky_island_density(x, y, z, &shape));
+        }
+
+        density
+    }
+
+    /// Density of the terrain before caves are carved (steps 1-6 of calculate_density)
+    fn ground_density(&self, x: f32, y: f32, z: f32) -> f32 {
+        // 1. Continentalness: determines ocean vs mountain vs plateau heights
+        let continentalness = self.continentalness(x, z);
         // Range: -1 to 1
 
         // 2. Erosion: determines flatness vs jaggedness
-        let erosion = fbm(x * 1.5, z * 1.5, self.config.erosion_freq, 0.55, 3);
+        let erosion = self.fbm(x * 1.5, z * 1.5, self.config.erosion_freq, 0.55, 3);
         // Range: -1 to 1
 
         // 3. Temperature & Humidity for biome (used later in GetBiomeType)
-        let temperature = fbm(x, z, self.config.temperature_freq, 0.55, 3);
-        let humidity = fbm(x + 5000.0, z - 5000.0, self.config.humidity_freq, 0.55, 3);
+        let temperature = self.fbm(x, z, self.config.temperature_freq, 0.55, 3);
+        let humidity = self.fbm(x + 5000.0, z - 5000.0, self.config.humidity_freq, 0.55, 3);
 
         // 4. Calculate terrain height baseline - gravity-based terrain
         let continental_height = continentalness * self.config.continental_height_amplitude;
@@ -317,236 +633,376 @@ impl VoxelDensityGenerator {
         let mut density = 0.5 - (y_diff / self.config.y_gradient_scale).clamp(-1.0, 1.0);
 
         // 6. Base 3D Noise: add surface distortion for overhangs and detail
-        let base_3d = fbm_3d(x, y, z, self.config.base_3d_freq, 0.55, 3);
+        let base_3d = self.fbm_3d(x, y, z, self.config.base_3d_freq, 0.55, 3);
         density += base_3d * self.config.base_3d_noise_strength;
-
-        // 7. STEP 3 - Cave carving: if cave noise is in narrow band, force air
-        let cave_noise = fbm_3d(x, y, z, self.config.cave_freq, 0.55, 3);
-        if cave_noise > self.config.cave_noise_min && cave_noise < self.config.cave_noise_max {
-            return -1.0; // Force air (caves)
-        }
-
         density
     }
 
-    /// Determine biome type based on temperature, humidity, and height - STEP 1 OF GENERATION PIPELINE
-    /// 
-    /// Uses 2D noise to determine biome type from three factors:
-    /// - Temperature (cold → hot)
-    /// - Humidity (dry → wet)  
-    /// - Height (elevation)
-    /// 
-    /// Results in biomes: Tundra, Mountain, Forest, Desert, Beach, Plain, Ocean, Lake, Cliff, Jungle
-    pub fn get_biome_type(&self, x: f32, z: f32, y: f32) -> BiomeType {
-        let temperature = fbm(x, z, self.config.temperature_freq, 0.55, 3);
-        let humidity = fbm(x + 5000.0, z - 5000.0, self.config.humidity_freq, 0.55, 3);
-        let continentalness = fbm(x, z, self.config.continentalness_freq, 0.55, 4);
-        let erosion = fbm(x, z, self.config.erosion_freq, 0.55, 3);
-        let lake_noise = fbm(x + 2000.0, z + 2000.0, self.config.lake_frequency, 0.55, 3);
-
-        // Lakes: depressions with moderate-high humidity and low continentalness
-        if lake_noise < self.config.lake_threshold && humidity > 0.3 && y < 30.0 {
-            return BiomeType::Lake;
+    /// Underground biome at a position inside the terrain
+    ///
+    /// Unlike get_biome_type this varies with y as well: two low-frequency 3D
+    /// noise fields pick lush or dripstone caves, basalt deltas form only in
+    /// the lower part of the world.
+    pub fn get_cave_biome(&self, x: f32, y: f32, z: f32) -> CaveBiome {
+        // both fields are centered around -0.5 (see hash2d)
+        let heat = self.fbm_3d(x - 7000.0, y, z + 7000.0, 0.012, 0.5, 2);
+        if heat > -0.42 && y < 30.0 {
+            return CaveBiome::BasaltDeltas;
         }
+        let wetness = self.fbm_3d(x + 7000.0, y, z - 7000.0, 0.012, 0.5, 2);
+        if wetness > -0.45 {
+            CaveBiome::LushCaves
+        } else if wetness < -0.57 {
+            CaveBiome::DripstoneCaves
+        } else {
+            CaveBiome::Stone
+        }
+    }
 
-        // Cliffs: high erosion and steep mountains
-        if erosion > self.config.cliff_threshold && y > 60.0 && continentalness > 0.4 {
-            return BiomeType::Cliff;
+    /// Carved and solid cells of a column around one chunk (see CaveColumn)
+    ///
+    /// `is_ground(y)` tells whether the terrain is solid before carving,
+    /// `is_carved(y)` whether the generator carves a cave there.
+    fn cave_column(&self, base_y: isize, is_ground: impl Fn(isize) -> bool, is_carved: impl Fn(isize) -> bool) -> CaveColumn {
+        let mut column = CaveColumn { base_y: base_y - 1, solid: [false; CAVE_COLUMN_LEN], cave: [false; CAVE_COLUMN_LEN] };
+        for i in 0..CAVE_COLUMN_LEN {
+            let world_y = column.base_y + i as isize;
+            if is_ground(world_y) {
+                column.cave[i] = is_carved(world_y);
+                column.solid[i] = !column.cave[i];
+            }
         }
+        column
+    }
+
+    /// Cave floor or ceiling block replacing the terrain at a solid position
+    fn cave_surface_block(&self, x: f32, world_y: isize, z: f32, column: &CaveColumn) -> Option<Block> {
+        self.cave_wall_block(x, world_y, z, column.is_cave(world_y + 1), column.is_cave(world_y - 1))
+    }
+
+    /// Whether the density generator carves a cave at a position (see cave_column)
+    fn is_carved(&self, x: f32, world_y: isize, z: f32) -> bool {
+        self.ground_density(x, world_y as f32, z) > 0.0 && self.is_cave(x, world_y as f32, z)
+    }
 
-        // Hot jungle - hot and very humid
-        if temperature > 0.5 && humidity > 0.6 && continentalness > 0.1 {
-            return BiomeType::Jungle;
+    /// Top block of density ground with open air above, like populate_chunk writes it
+    fn density_surface_block(&self, x: f32, world_y: isize, z: f32) -> Block {
+        let wy = world_y as f32;
+        // a thin roof over a cave gets the block of the cave ceiling
+        self.cave_wall_block(x, world_y, z, false, self.is_carved(x, world_y - 1, z))
+            .unwrap_or_else(|| self.get_surface_block_for_biome(x, z, wy, self.get_biome_type(x, z, wy)))
+    }
+
+    fn cave_wall_block(&self, x: f32, world_y: isize, z: f32, is_floor: bool, is_ceiling: bool) -> Option<Block> {
+        if !is_floor && !is_ceiling {
+            return None;
         }
 
-        // High mountains (snow-covered peaks)
-        if y > 80.0 && continentalness > 0.3 {
-            if temperature < -0.6 {
-                return BiomeType::Tundra;
-            } else {
-                return BiomeType::Mountain;
+        match self.get_cave_biome(x, world_y as f32, z) {
+            CaveBiome::Stone => None,
+            CaveBiome::LushCaves => Some(Block::Moss),
+            CaveBiome::DripstoneCaves => Some(Block::Dripstone),
+            CaveBiome::BasaltDeltas if is_floor => {
+                // magma pools in the low parts of the delta
+                let pools = self.noise2d(x * 0.2 + 1300.0, z * 0.2 - 1300.0);
+                Some(if pools > -0.25 { Block::Magma } else { Block::Basalt })
             }
+            CaveBiome::BasaltDeltas => Some(Block::BlackStone),
+        }
+    }
+
+    /// Hanging or standing cave plant at a carved position, None for air
+    ///
+    /// Vines and stalactites hang up to a few blocks from the ceiling,
+    /// stalagmites and moss plants stand on the floor.
+    fn cave_air_block(&self, x: f32, world_y: isize, z: f32, column: &CaveColumn) -> Option<Block> {
+        if !column.is_cave(world_y) {
+            return None;
         }
+        let on_floor = column.is_solid(world_y - 1);
+        // blocks below the ceiling, if it is close enough for something to hang down
+        let below_ceiling = (1..=CAVE_HANGING_MAX)
+            .find(|k| !column.is_cave(world_y + k))
+            .filter(|k| column.is_solid(world_y + k));
+        if !on_floor && below_ceiling.is_none() {
+            return None;
+        }
+
+        let r = column_random(x as i32 ^ 0x0cafe, z as i32);
+        let length = 1 + (r * 10.0) as isize % CAVE_HANGING_MAX;
+        let hangs = |chance: f32, max: isize| r < chance && below_ceiling.is_some_and(|k| k <= length.min(max));
+        let stands = |chance: f32| on_floor && column_random(z as i32, x as i32 ^ 0x0cafe) < chance;
 
-        // Moderate elevation mountains
-        if y > 50.0 && continentalness > 0.2 {
-            return BiomeType::Mountain;
+        match self.get_cave_biome(x, world_y as f32, z) {
+            CaveBiome::LushCaves if hangs(0.3, CAVE_HANGING_MAX) => Some(Block::CaveVines),
+            CaveBiome::LushCaves if stands(0.25) => Some(Block::Grass_Short),
+            CaveBiome::DripstoneCaves if hangs(0.2, 2) || stands(0.15) => Some(Block::PointedDripstone),
+            _ => None,
         }
+    }
 
-        // Cold regions - tundra
-        if temperature < -0.7 {
-            return BiomeType::Tundra;
+    /// Altitude band of the sky islands, kept clear of the cloud layer
+    fn sky_island_band(&self) -> (f32, f32) {
+        let max = self.config.sky_island_max.min(self.config.cloud_height as f32 - 8.0);
+        (self.config.sky_island_min.min(max), max)
+    }
+
+    /// Sky island over a column, if the island mask covers it
+    ///
+    /// The mask strength `t` (0 at the rim, 1 in the middle) raises the top into a
+    /// flat dome and lowers the bottom much further, so the underside is a cone
+    /// that tapers to a point below the middle of the island.
+    fn sky_island_shape(&self, x: f32, z: f32) -> Option<SkyIslandShape> {
+        if !self.config.sky_islands {
+            return None;
+        }
+        let freq = self.config.sky_island_freq;
+        let threshold = self.config.sky_island_threshold;
+        let mask = self.fbm(x + 31000.0, z - 31000.0, freq, 0.5, 3);
+        // small margin so the 3D detail can grow the rim a little
+        if mask < threshold - 0.02 {
+            return None;
         }
 
-        // Hot, dry regions - desert
-        if temperature > 0.7 && humidity < -0.5 {
-            return BiomeType::Desert;
+        let (min, max) = self.sky_island_band();
+        let t = ((mask - threshold) / (SKY_ISLAND_MASK_MAX - threshold).max(0.01)).clamp(-0.2, 1.0);
+        let thickness = (max - min) * 0.6;
+        // each island floats at its own height in the upper part of the band
+        let altitude = (self.fbm(x - 31000.0, z + 31000.0, freq * 0.5, 0.5, 2) + 1.0).clamp(0.0, 1.0);
+        let center = max - 4.0 - altitude * (max - min - thickness - 4.0).max(0.0);
+
+        let waterfall = t > 0.0 && t < 0.05 && column_random(x as i32 ^ 0x0fa11, z as i32) < 0.04;
+        Some(SkyIslandShape {
+            bottom: center - t.max(0.0).powf(0.7) * thickness,
+            top: center + t * 4.0,
+            waterfall,
+        })
+    }
+
+    /// Sky island density: positive inside the island, roughened by 3D noise
+    fn sky_island_density(&self, x: f32, y: f32, z: f32, shape: &SkyIslandShape) -> f32 {
+        if y < shape.bottom - 4.0 || y > shape.top + 4.0 {
+            return -1.0;
         }
+        let inside = ((shape.top - y).min(y - shape.bottom) / 4.0).min(1.0);
+        inside + (self.fbm_3d(x, y + 500.0, z, 0.08, 0.5, 2) + 0.5) * 1.6
+    }
 
-        // Wet regions - forest
-        if humidity > 0.0 {
-            return BiomeType::Forest;
+    /// Block of a sky island (or its waterfall) at a position, None for air
+    ///
+    /// `ground` is the first world y above the terrain below (waterfalls end there).
+    fn sky_island_block(&self, x: f32, y: f32, z: f32, shape: &SkyIslandShape, ground: f32) -> Option<Block> {
+        let density = self.sky_island_density(x, y, z, shape);
+        if density <= 0.0 {
+            return (shape.waterfall && y < shape.bottom && y >= ground).then_some(Block::Water);
         }
+        if shape.waterfall && y >= shape.top - 1.0 {
+            return Some(Block::Water);
+        }
+
+        let is_top = self.sky_island_density(x, y + 1.0, z, shape) <= 0.0;
+        Some(if y < shape.top - 3.0 {
+            Block::Stone
+        } else if is_top {
+            Block::Grass
+        } else {
+            Block::Dirt
+        })
+    }
 
-        // Coastal areas
-        if continentalness < 0.0 && continentalness > -0.3 {
-            return BiomeType::Beach;
+    /// World y of the grass on top of a sky island column, None for waterfalls and bare rock
+    fn sky_island_grass(&self, x: f32, z: f32, shape: &SkyIslandShape, ground: isize) -> Option<isize> {
+        let lowest = (shape.bottom.floor() as isize).max(ground);
+        for y in (lowest..=shape.top.ceil() as isize).rev() {
+            match self.sky_island_block(x, y as f32, z, shape, ground as f32) {
+                None => continue,
+                Some(Block::Grass) => return Some(y),
+                Some(_) => return None,
+            }
         }
+        None
+    }
 
-        // Default: grassland/plain
-        BiomeType::Plain
+    /// Determine biome type based on temperature, humidity, and height - STEP 1 OF GENERATION PIPELINE
+    /// 
+    /// Samples the climate of a position with 2D noise and lets the biome registry
+    /// pick the matching biome (see BiomeDefinition::climate):
+    /// - Zone (deep ocean, ocean, coast, land) from continentalness
+    /// - Temperature (cold → hot)
+    /// - Humidity (dry → wet)  
+    /// - Erosion, lake noise and height (elevation)
+    pub fn get_biome_type(&self, x: f32, z: f32, y: f32) -> BiomeId {
+        let continentalness = self.continentalness(x, z);
+
+        // Oceans and their coasts depend only on continentalness (not on y),
+        // so every generator agrees on where the sea is
+        let zone = if continentalness < self.config.deep_ocean_threshold {
+            Zone::DeepOcean
+        } else if continentalness < self.config.ocean_threshold {
+            Zone::Ocean
+        } else if continentalness < self.config.ocean_threshold + self.config.coast_width * 0.25 {
+            Zone::Coast
+        } else {
+            Zone::Land
+        };
+        self.classify_biome(x, z, y, zone)
+    }
+
+    /// Biome of a position in a given zone (heightmaps paint their own coast line)
+    pub(super) fn classify_biome(&self, x: f32, z: f32, y: f32, zone: Zone) -> BiomeId {
+        self.biomes.classify(&Climate {
+            zone,
+            temperature: self.fbm(x, z, self.config.temperature_freq, 0.55, 3),
+            humidity: self.fbm(x + 5000.0, z - 5000.0, self.config.humidity_freq, 0.55, 3),
+            continentalness: self.continentalness(x, z),
+            erosion: self.fbm(x, z, self.config.erosion_freq, 0.55, 3),
+            lake: self.fbm(x + 2000.0, z + 2000.0, self.config.lake_frequency, 0.55, 3),
+            height: y,
+        })
+    }
+
+    /// Variety noise of a biome, the `variety` variable of its block rules
+    fn biome_variety(&self, x: f32, z: f32, biome: &BiomeDefinition) -> f32 {
+        let (scale, freq) = biome.variety;
+        self.fbm(x * scale, z * scale, freq, 0.55, 2)
     }
 
     /// Get surface block type based on biome and height
-    pub fn get_surface_block_for_biome(
-        &self,
-        x: f32,
-        z: f32,
-        y: f32,
-        biome: BiomeType,
-    ) -> super::block::Block {
-        use super::block::Block;
+    pub fn get_surface_block_for_biome(&self, x: f32, z: f32, y: f32, biome: BiomeId) -> Block {
+        // anything under the sea gets a sea floor, whatever the biome on land would be
+        if y < SEA_LEVEL as f32 {
+            return self.get_seafloor_block(x, z, y, biome);
+        }
 
-        match biome {
-            BiomeType::Ocean => Block::Water,
-            BiomeType::Beach => {
-                // Beach/sand transition zone
-                if y > 5.0 {
-                    Block::Grass
-                } else {
-                    Block::Sand
-                }
-            }
-            BiomeType::Plain => {
-                // Grassland with some variation
-                let variety = fbm(x * 0.3, z * 0.3, 0.01, 0.55, 2);
-                if variety < -0.3 {
-                    Block::Moss
-                } else if variety < 0.3 {
-                    Block::Grass
-                } else {
-                    Block::Dirt
-                }
-            }
-            BiomeType::Forest => {
-                // Forest floor - mostly grass and moss
-                let variety = fbm(x * 0.3, z * 0.3, 0.01, 0.55, 2);
-                if variety < 0.0 {
-                    Block::Moss
-                } else {
-                    Block::Grass
-                }
-            }
-            BiomeType::Mountain => {
-                // Rocky peaks with bare stone at top, grassed slopes below
-                if y > 100.0 {
-                    // Bare rocky peak - variety of stone types
-                    let variety = fbm(x * 0.4, z * 0.4, 0.02, 0.55, 2) as i32 % 3;
-                    match variety {
-                        0 => Block::Stone,
-                        1 => Block::Granite,
-                        _ => Block::Cobblestone,
-                    }
-                } else if y > 70.0 {
-                    // Upper slopes - grass for trees
-                    Block::Grass
-                } else {
-                    // Lower slopes - grass
-                    Block::Grass
-                }
-            }
-            BiomeType::Tundra => {
-                // Frozen terrain - moss for trees, snow at peaks
-                if y > 60.0 {
-                    Block::Snow
-                } else if y > 40.0 {
-                    Block::Moss  // Allow trees on moss
-                } else if y > 20.0 {
-                    let variety = fbm(x * 0.3, z * 0.3, 0.01, 0.55, 2);
-                    if variety < -0.2 {
-                        Block::Snow
-                    } else {
-                        Block::Moss
-                    }
-                } else {
-                    Block::Moss
-                }
-            }
-            BiomeType::Desert => Block::Sand,
-            BiomeType::Lake => {
-                // Lake shores - sandy/grassy with water plants
-                if y > 5.0 {
-                    Block::Grass
-                } else {
-                    Block::Sand
-                }
-            }
-            BiomeType::Cliff => {
-                // Cliff faces - dark stone, mostly basalt
-                let variety = fbm(x * 0.5, z * 0.5, 0.02, 0.55, 2);
-                if variety > 0.5 {
-                    Block::Basalt
-                } else if variety > 0.0 {
-                    Block::BlackStone
-                } else {
-                    Block::Stone
-                }
-            }
-            BiomeType::Jungle => {
-                // Jungle floor - grass and moss, very green
-                let variety = fbm(x * 0.4, z * 0.4, 0.01, 0.55, 2);
-                if variety < -0.1 {
-                    Block::Moss
-                } else {
-                    Block::Grass
-                }
-            }
+        let biome = self.biomes.get(biome);
+        biome.surface.select(y, || self.biome_variety(x, z, biome), 0.0).unwrap_or(Block::Grass)
+    }
+
+    /// Get sea floor block: sand in the shallows, gravel and clay in deeper water
+    pub fn get_seafloor_block(&self, x: f32, z: f32, y: f32, biome: BiomeId) -> Block {
+        let depth = SEA_LEVEL as f32 - y;
+        // value noise is centered around -0.5, thresholds are spread around that
+        let variety = self.fbm(x * 0.3 + 300.0, z * 0.3 - 300.0, 0.02, 0.55, 2);
+
+        if depth < 6.0 {
+            if variety < -0.8 { Block::Gravel } else { Block::Sand }
+        } else if self.biomes.get(biome).in_zone(Zone::DeepOcean) || depth > 25.0 {
+            if variety < -0.5 { Block::Gravel } else { Block::Clay }
+        } else if variety < -0.65 {
+            Block::Gravel
+        } else if variety > -0.35 {
+            Block::Clay
+        } else {
+            Block::Sand
         }
     }
 
     /// Get subsurface block based on depth and biome
-    pub fn get_subsurface_block(&self, x: f32, z: f32, y: f32, biome: BiomeType) -> super::block::Block {
-        use super::block::Block;
-
-        // Deep underground = stone
-        if y < -20.0 {
+    pub fn get_subsurface_block(&self, x: f32, z: f32, y: f32, biome: BiomeId) -> Block {
+        // Bottom of the world = bedrock
+        if self.config.bounds().is_bedrock(x as isize, y as isize, z as isize) {
             return Block::Bedrock;
         }
 
-        match biome {
-            BiomeType::Desert => {
-                // Desert has sandstone layers
-                if y < 40.0 {
-                    Block::Sandstone
-                } else {
-                    Block::Sand
-                }
-            }
-            BiomeType::Mountain | BiomeType::Tundra => {
-                // Mountains: granite and stone
-                let variety = fbm(x * 0.5, z * 0.5, 0.01, 0.55, 2) as i32 % 2;
-                if variety == 0 {
-                    Block::Granite
-                } else {
-                    Block::Stone
-                }
-            }
-            _ => {
-                // Default: dirt under grass, stone deeper
-                if y > 0.0 {
-                    Block::Dirt
-                } else {
-                    Block::Stone
-                }
+        let biome = self.biomes.get(biome);
+        biome.subsurface.select(y, || self.biome_variety(x, z, biome), 0.0).unwrap_or(Block::Stone)
+    }
+
+    /// Surface biome of a column: one biome picked from the blend weights
+    ///
+    /// Columns near a border randomly pick one of the neighboring biomes (weighted),
+    /// which turns straight biome edges into a ragged, dithered transition.
+    pub fn surface_biome(&self, weights: &BiomeWeights, wx: f32, wz: f32) -> BiomeId {
+        let (ox, oz) = self.seed_offset();
+        weights.pick(column_random((wx + ox) as i32, (wz + oz) as i32), self.config.biome_jitter)
+    }
+
+    /// Grass/foliage tint of a column, interpolated between nearby biomes
+    pub fn biome_tint(&self, weights: &BiomeWeights) -> Tint {
+        Tint::from_factor(weights.blend_color(|b| self.biomes.get(b).tint))
+    }
+
+    /// Terrain height of a column from its height noise and blended biome shape
+    fn blended_terrain_height(&self, height_noise: f32, weights: &BiomeWeights) -> isize {
+        const MID_HEIGHT: f32 = 127.5;
+
+        // biome shape, scaled and shifted by the world-wide preset values
+        let amplitude = weights.blend(|b| self.biomes.get(b).height_shape.0) * self.config.height_scale;
+        let offset = weights.blend(|b| self.biomes.get(b).height_shape.1) + self.config.height_offset;
+        let height = (height_noise + 1.0) * 0.5 * 255.0;
+        (MID_HEIGHT + (height - MID_HEIGHT) * amplitude + offset) as isize
+    }
+
+    /// Column height with oceans: sea floor below the ocean threshold, flat beaches
+    /// just above it, rising to the biome (land) height further inland
+    fn coastal_height(&self, wx: f32, wz: f32, land_height: isize) -> isize {
+        let c = self.continentalness(wx, wz);
+        let threshold = self.config.ocean_threshold;
+        let sea = SEA_LEVEL as f32;
+
+        if c >= threshold {
+            // t^2 keeps the first part of the coast flat (beach), then the land rises
+            let t = ((c - threshold) / self.config.coast_width.max(0.001)).clamp(0.0, 1.0);
+            let t = t * t * (3.0 - 2.0 * t);
+            return (sea + 1.0 + (land_height as f32 - sea - 1.0) * t * t).round() as isize;
+        }
+
+        // sea floor gets deeper towards the open ocean, with some small-scale relief
+        let openness = ((threshold - c) / (threshold + 1.0)).clamp(0.0, 1.0);
+        let relief = (self.fbm(wx + 700.0, wz - 700.0, 0.05, 0.5, 2) + 0.5) * 4.0;
+        let mut depth = 2.0 + openness.sqrt() * self.config.ocean_depth + relief;
+
+        // deep ocean trenches: narrow ridged lines of the noise, fading in past the threshold
+        let deep = ((self.config.deep_ocean_threshold - c) / 0.05).clamp(0.0, 1.0);
+        if deep > 0.0 {
+            let ridge = 1.0 - (self.fbm(wx - 9000.0, wz + 9000.0, 0.004, 0.5, 2) + 0.5).abs() * 2.0;
+            if ridge > 0.85 {
+                depth += (ridge - 0.85) / 0.15 * self.config.ocean_depth * deep;
             }
         }
+        (sea - depth).round() as isize
+    }
+
+    /// Underwater plant of a sea floor column as (block, first world y above the plant)
+    ///
+    /// Sea grass is a single block in shallow water, kelp grows as a column that
+    /// ends at least two blocks below the surface. Depends only on the column,
+    /// so kelp is continuous across vertical chunk borders.
+    pub(super) fn seabed_plant(&self, wx: f32, wz: f32, height: isize) -> Option<(Block, isize)> {
+        let depth = SEA_LEVEL + 1 - height;
+        if depth < 3 {
+            return None;
+        }
+        let r = column_random(wx as i32 ^ 0x5eed, wz as i32);
+        let patch = self.noise2d(wx * 0.15 + 500.0, wz * 0.15 - 500.0);
+
+        if depth >= 6 && patch > -0.3 && r < 0.35 {
+            let length = 2 + ((depth - 4) as f32 * r / 0.35) as isize;
+            Some((Block::Kelp, height + length.min(depth - 2)))
+        } else if depth < 16 && patch < -0.7 && r < 0.5 {
+            Some((Block::SeaGrass, height + 1))
+        } else {
+            None
+        }
+    }
+
+    /// Density sea floor (top solid y) of a column right below `world_y`, if that is under water
+    ///
+    /// Looks at most SEA_FLOOR_SCAN blocks down, deeper floors grow no plants into the chunk at `world_y`.
+    fn sea_floor_below(&self, wx: f32, wz: f32, world_y: isize) -> Option<isize> {
+        if world_y > SEA_LEVEL {
+            return None;
+        }
+        (world_y - SEA_FLOOR_SCAN..world_y)
+            .rev()
+            .find(|&y| self.calculate_density(wx, y as f32, wz) > 0.0)
     }
 
     /// Get ore block if one should spawn here
     pub fn get_ore_block(&self, x: f32, y: f32, z: f32) -> Option<super::block::Block> {
         use super::block::Block;
 
-        let ore_check = noise2d(
+        let ore_check = self.noise2d(
             x * 2.3 + y * 0.5,
             z * 1.7 - y * 0.3,
         );
@@ -564,28 +1020,24 @@ impl VoxelDensityGenerator {
         }
     }
 
+    /// Whether a column is a tree center (before checking its biome and surface)
+    pub(super) fn is_tree_center(&self, wx: f32, wz: f32) -> bool {
+        let tree_location = self.noise2d(wx * self.config.tree_noise_frequency + 200.0, wz * self.config.tree_noise_frequency - 200.0);
+        tree_location > self.config.tree_spawn_threshold
+    }
+
     /// Calculate tree placement data for a column (type, height, whether to spawn)
-    fn calculate_tree_data(&self, wx: f32, wz: f32) -> TreeData {
-        // Determine biome at this location
-        let biome = self.get_biome_type(wx, wz, 30.0);
-        
-        // Check if this is a tree center (using noise)
-        let tree_location = noise2d(wx * self.config.tree_noise_frequency + 200.0, wz * self.config.tree_noise_frequency - 200.0);
-        let should_spawn = tree_location > self.config.tree_spawn_threshold;
+    pub(super) fn calculate_tree_data(&self, wx: f32, wz: f32, biome: BiomeId) -> TreeData {
+        let should_spawn = self.is_tree_center(wx, wz);
         
         // Generate random value for tree type/height variation
-        let tree_chance = noise2d(wx * 0.2 + 200.0, wz * 0.2 - 200.0);
-        let tree_rng = (tree_chance + 1.0) * 0.5;
+        // noise2d covers [-1, 0) (see hash2d), shift that to [0, 1)
+        let tree_chance = self.noise2d(wx * 0.2 + 200.0, wz * 0.2 - 200.0);
+        let tree_rng = tree_chance + 1.0;
         
-        // Determine tree type based on biome
-        let tree_type = match biome {
-            BiomeType::Tundra => TreeType::Spruce,
-            BiomeType::Forest => if tree_rng > 0.4 { TreeType::Birch } else { TreeType::Oak },
-            BiomeType::Mountain => if tree_rng > 0.6 { TreeType::Spruce } else { TreeType::Oak },
-            BiomeType::Jungle => if tree_rng > 0.5 { TreeType::DarkOak } else { TreeType::Acacia },
-            BiomeType::Desert => TreeType::Acacia,
-            BiomeType::Plain => TreeType::Oak,
-            _ => TreeType::Oak,
+        // Determine tree type based on biome (a biome without trees never spawns any)
+        let Some(tree_type) = self.biomes.get(biome).pick_tree(tree_rng) else {
+            return TreeData { tree_type: TreeType::Oak, tree_height: 0, should_spawn: false };
         };
         
         // Calculate height based on tree type with slight variation
@@ -595,55 +1047,103 @@ impl VoxelDensityGenerator {
             TreeType::Oak => 6 + ((tree_rng * 10.0) as i32 % self.config.tree_height_variation),
             TreeType::Acacia => 8 + ((tree_rng * 10.0) as i32 % (self.config.tree_height_variation + 1)),
             TreeType::DarkOak => 12 + ((tree_rng * 10.0) as i32 % (self.config.tree_height_variation + 2)),
+            TreeType::Jungle => 18 + ((tree_rng * 40.0) as i32 % (self.config.tree_height_variation * 4).max(1)),
         };
         
         TreeData { tree_type, tree_height, should_spawn }
     }
 
+    /// Plant growing on a grass/moss surface block of a column, if any
+    pub(super) fn plant_block(&self, wx: f32, wz: f32, biome: BiomeId) -> Option<Block> {
+        let plant_noise = self.noise2d(wx * self.config.plant_frequency + 100.0, wz * self.config.plant_frequency - 100.0);
+        if plant_noise <= self.config.plant_density {
+            return None;
+        }
+        self.biomes.get(biome).plants.select(0.0, || 0.0, plant_noise)
+    }
+
     /// Populate a chunk with terrain and features using a complete generation pipeline:
     /// 
     /// GENERATION PIPELINE:
     /// 1. Use 2D noise to determine biome (Forest, Mountain, Plains, etc.)
     /// 2. Use 3D density to generate solid terrain with natural gravity
-    /// 3. Carve out caves during density calculation
+    /// 3. Carve out caves during density calculation, cave biomes on their floors and ceilings
     /// 4. Fill depressions with water (y <= 0)
     /// 5. Place trees on surface blocks matching biome type
-    /// 6. Add clouds at height 255
+    /// 6. (Clouds are drawn by the sky pass at cloud_height, not generated)
+    /// 7. Optional sky islands with trees and waterfalls
     pub fn populate_chunk(&self, chunk: &mut super::chunk::Chunk, chunk_coord: &crate::utils::ChunkCoord) {
         use crate::utils::BlockCoord;
-            
+
+        let world_coord = chunk_coord.to_world_coord();
+        // the lattice also covers the columns whose trees may reach into the chunk
+        let blender = BiomeBlender::new(
+            self,
+            world_coord.0 as i32 - TREE_REACH,
+            world_coord.2 as i32 - TREE_REACH,
+            CHUNK_SIZE as i32 + 2 * TREE_REACH,
+        );
+
         for z in 0..CHUNK_SIZE {
             for x in 0..CHUNK_SIZE {
-                let world_coord = chunk_coord.to_world_coord();
                 let wx = world_coord.0 as f32 + x as f32;
                 let wz = world_coord.2 as f32 + z as f32;
 
-                // Calculate tree placement and properties once per column (for efficiency)
-                let tree_data = self.calculate_tree_data(wx, wz);
-
-                // STEP 2-6: Process each Y level in this column
+                // Blend biomes around this column for tints, trees and plants
+                let weights = blender.weights_at(wx as i32, wz as i32);
+                let column_biome = self.surface_biome(&weights, wx, wz);
+                chunk.set_tint(x as usize, z as usize, self.biome_tint(&weights));
+
+                // Tree centers get no plants, their trees are grown in STEP 5
+                let tree_data = self.calculate_tree_data(wx, wz, column_biome);
+                let sky_island = self.sky_island_shape(wx, wz);
+                let caves = self.cave_column(
+                    chunk_coord.1 * CHUNK_SIZE,
+                    |world_y| self.ground_density(wx, world_y as f32, wz) > 0.0,
+                    |world_y| self.is_cave(wx, world_y as f32, wz),
+                );
+                // Kelp or sea grass growing in the water above the last sea floor, which
+                // may lie below this chunk
+                let mut seabed_plant = self.sea_floor_below(wx, wz, chunk_coord.1 * CHUNK_SIZE)
+                    .and_then(|floor| self.seabed_plant(wx, wz, floor + 1));
+
+                // STEP 2-7: Process each Y level in this column
                 for y in 0..CHUNK_SIZE {
                     let world_y = chunk_coord.1 * CHUNK_SIZE + y;
                     let wy = world_y as f32;
                     
-                    // STEP 6: Add clouds at height 255
-                    if world_y == 255 {
-                        let cloud_noise = noise2d(wx * 0.04, wz * 0.04);
-                        if cloud_noise > 0.0 {
-                            chunk.set_block(&BlockCoord(x as usize, y as usize, z as usize), Block::Cloud, false);
-                            continue;
-                        }
+                    // STEP 7: Sky islands; waterfalls only fill air of the terrain below
+                    let sky_block = sky_island.as_ref()
+                        .and_then(|shape| self.sky_island_block(wx, wy, wz, shape, SEA_LEVEL as f32 + 1.0))
+                        .filter(|block| *block != Block::Water || self.calculate_density(wx, wy, wz) <= 0.0);
+                    if let Some(block) = sky_block {
+                        chunk.set_block(&BlockCoord(x as usize, y as usize, z as usize), block, false);
+                        continue;
                     }
-                    
+
                     // STEP 2: Use 3D density to calculate terrain (includes cave carving)
                     let density = self.calculate_density(wx, wy, wz);
                     let is_solid = density > 0.0;
-                    let is_surface = is_solid && self.calculate_density(wx, wy + 1.0, wz) <= 0.0;
+                    // cave floors are not the surface, they get cave biome blocks instead
+                    let is_surface = is_solid && self.calculate_density(wx, wy + 1.0, wz) <= 0.0 &&
+                                     !caves.is_cave(world_y + 1);
                     
                     // STEP 3-5: Determine block type
                     let block = if !is_solid {
-                        // STEP 4: Fill with water if below sea level (y <= 0)
-                        if wy <= 0.0 { Block::Water } else { Block::Empty }
+                        // STEP 4: Fill with water if below sea level (y <= SEA_LEVEL)
+                        if world_y <= SEA_LEVEL {
+                            match seabed_plant {
+                                Some((plant, top)) if world_y < top => plant,
+                                _ => self.biomes.get(column_biome).water(),
+                            }
+                        } else if caves.is_cave(world_y) {
+                            self.cave_air_block(wx, world_y, wz, &caves).unwrap_or(Block::Empty)
+                        } else {
+                            Block::Empty
+                        }
+                    } else if let Some(block) = self.cave_surface_block(wx, world_y, wz, &caves) {
+                        // STEP 3: cave biome floors and ceilings (get_cave_biome)
+                        block
                     } else {
                         // Solid block: determine type based on biome and depth
                         let biome = self.get_biome_type(wx, wz, wy);
@@ -659,43 +1159,19 @@ impl VoxelDensityGenerator {
 
                     chunk.set_block(&BlockCoord(x as usize, y as usize, z as usize), block, false);
 
-                    // STEP 5: Plant trees on surface grass/moss blocks
-                    if is_surface && tree_data.should_spawn && matches!(block, Block::Grass | Block::Moss) {
-                        let tree = Tree {
-                            pos: (x as i32, z as i32),
-                            tree_type: tree_data.tree_type,
-                            trunk_height: tree_data.tree_height,
-                        };
-                        Self::plant_tree(&tree, chunk_coord, world_y as i32 + 1, chunk);
+                    // Kelp and sea grass grow from the sea floor into the water above it
+                    if is_solid {
+                        seabed_plant = (is_surface && world_y < SEA_LEVEL)
+                            .then(|| self.seabed_plant(wx, wz, world_y + 1))
+                            .flatten();
                     }
-                    
+
                     // Place vegetation (plants) on surface blocks
                     if is_surface && matches!(block, Block::Grass | Block::Moss) && world_y > 0 {
-                        let plant_noise = noise2d(wx * self.config.plant_frequency + 100.0, wz * self.config.plant_frequency - 100.0);
-                        let biome = self.get_biome_type(wx, wz, wy);
-                        
                         // Only place plants if not tree-center and noise is above threshold
-                        if !tree_data.should_spawn && plant_noise > self.config.plant_density {
-                            let plant_type = match biome {
-                                BiomeType::Forest | BiomeType::Jungle => {
-                                    if plant_noise > 0.8 { Block::Grass_Tall } else { Block::Grass_Short }
-                                }
-                                BiomeType::Desert => {
-                                    if plant_noise > 0.9 { Block::Cactus } else { Block::DeadBush }
-                                }
-                                BiomeType::Lake | BiomeType::Beach => {
-                                    Block::SeaGrass
-                                }
-                                BiomeType::Plain => {
-                                    if plant_noise > 0.85 { Block::RedFlower } else { Block::YellowFlower }
-                                }
-                                _ => {
-                                    if plant_noise > 0.85 { Block::RedFlower } else { Block::YellowFlower }
-                                }
-                            };
-                            
+                        if let Some(plant_type) = self.plant_block(wx, wz, column_biome).filter(|_| !tree_data.should_spawn) {
                             // Place plant on top of surface block
-                            if world_y < 255 {
+                            if world_y + 1 < self.config.max_y as isize {
                                 let plant_y = y + 1;
                                 if plant_y < CHUNK_SIZE {
                                     chunk.set_block(&BlockCoord(x as usize, plant_y as usize, z as usize), plant_type, false);
@@ -706,308 +1182,277 @@ impl VoxelDensityGenerator {
                 }
             }
         }
+
+        // STEP 5: grow the trees of this and the surrounding columns after the terrain,
+        // so it doesn't overwrite them
+        let mut tree_anchors = self.density_tree_anchors(&blender, chunk_coord);
+        trees::place_trees(chunk, chunk_coord, &mut tree_anchors, self.config.seed);
+    }
+
+    /// Trees of the density generator rooted in the columns around a chunk (see
+    /// trees::anchor_columns)
+    ///
+    /// The ground of a density column is not a single height, so every tree center
+    /// is scanned over the heights whose trees reach this chunk layer. Trees grow on
+    /// grass and moss with air above (not on cave floors) and on sky island grass,
+    /// matching the blocks populate_chunk writes. `blender` must cover all these columns.
+    fn density_tree_anchors(&self, blender: &BiomeBlender, chunk_coord: &crate::utils::ChunkCoord) -> Vec<TreeAnchor> {
+        let heights = trees::anchor_heights(chunk_coord.1);
+        let mut anchors = Vec::new();
+        for (x, z) in trees::anchor_columns(chunk_coord) {
+            let (wx, wz) = (x as f32, z as f32);
+            if !self.is_tree_center(wx, wz) {
+                continue;
+            }
+            let column_biome = self.surface_biome(&blender.weights_at(x, z), wx, wz);
+            let tree_data = self.calculate_tree_data(wx, wz, column_biome);
+            if !tree_data.should_spawn {
+                continue;
+            }
+
+            let sky_island = self.sky_island_shape(wx, wz);
+            // top down, so the density above each height is already known
+            let mut solid_above = self.calculate_density(wx, heights.end as f32 - 1.0, wz) > 0.0;
+            for anchor_y in heights.clone().rev() {
+                let world_y = anchor_y as isize - 1;
+                let wy = world_y as f32;
+                let solid = self.calculate_density(wx, wy, wz) > 0.0;
+                let covered = std::mem::replace(&mut solid_above, solid);
+
+                let sky_block = sky_island.as_ref()
+                    .and_then(|shape| self.sky_island_block(wx, wy, wz, shape, SEA_LEVEL as f32 + 1.0))
+                    .filter(|block| *block != Block::Water || !solid);
+                let grows = match sky_block {
+                    Some(block) => block == Block::Grass,
+                    None => solid && !covered && !self.is_carved(wx, world_y + 1, wz) && {
+                        let block = self.density_surface_block(wx, world_y, wz);
+                        matches!(block, Block::Grass | Block::Moss)
+                    },
+                };
+                if grows {
+                    anchors.push(tree_data.anchor(x, anchor_y as isize, z));
+                }
+            }
+        }
+        anchors
     }
 
     /// Populate a chunk with simple 2D terrain (sea level at y=0)
     /// 
     /// Simplified terrain generation using only 2D noise:
-    /// - 2D noise for biome determination
-    /// - 2D noise for terrain height (average 0, maximum 255)
+    /// - 2D noise for biome determination, blended across borders (see biome.rs)
+    /// - 2D noise for terrain height (average 0, maximum 255), shaped by blended biome parameters
+    /// - Continentalness for oceans: sea floor, trenches and beaches (coastal_height)
+    /// - Optional hydraulic and thermal erosion of the heightmap (erosion.rs)
     /// - Height-based block selection:
-    ///   * y < 0: Water
+    ///   * below the surface under water: sand, gravel or clay sea floor
+    ///   * above the surface up to sea level: Water, with kelp and sea grass
     ///   * y >= 200: Snow (no grass)
     ///   * y >= 100: Stone (no grass)
     ///   * y < 100: Grass/biome-specific blocks
-    /// - Trees placed only below y=150
+    /// - Trees placed only below y=150 (and on sky islands), including the parts
+    ///   of trees in neighboring chunks that reach into this one
+    /// - Optional sky islands above the terrain (see sky_island_shape)
     pub fn populate_chunk_simple(&self, chunk: &mut super::chunk::Chunk, chunk_coord: &crate::utils::ChunkCoord) {
         use crate::utils::BlockCoord;
+
+        let world_coord = chunk_coord.to_world_coord();
+        // the lattice also covers the columns whose trees and decorations may reach into the chunk
+        let blender = BiomeBlender::new(
+            self,
+            world_coord.0 as i32 - TREE_REACH,
+            world_coord.2 as i32 - TREE_REACH,
+            CHUNK_SIZE as i32 + 2 * TREE_REACH,
+        );
+        let grounds = RefCell::new(HashMap::new());
         
         for z in 0..CHUNK_SIZE {
             for x in 0..CHUNK_SIZE {
-                let world_coord = chunk_coord.to_world_coord();
                 let wx = world_coord.0 as f32 + x as f32;
                 let wz = world_coord.2 as f32 + z as f32;
 
-                // STEP 1: Determine biome using 2D noise
-                let biome = self.get_biome_type(wx, wz, 30.0);
-                
-                // STEP 2: Calculate terrain height using 2D noise
-                // Use higher frequency (0.08) for more terrain variation and detail
-                // More octaves (6) for realistic mountain/valley transitions
-                let height_noise = fbm(wx * 0.08, wz * 0.08, 0.08, 0.55, 6);
-                let terrain_height = ((height_noise + 1.0) * 0.5 * 255.0) as isize;
+                // STEP 1-2: biome weights, terrain height and surface block of this column
+                let column = self.sample_column(&blender, wx, wz);
+                let (biome, terrain_height) = (column.biome, column.height);
+                chunk.set_tint(x as usize, z as usize, column.tint);
+                grounds.borrow_mut().insert((wx as i32, wz as i32), (terrain_height, biome));
+                let seabed_plant = self.seabed_plant(wx, wz, terrain_height);
+                let water = self.biomes.get(biome).water();
+
+                let sky_island = self.sky_island_shape(wx, wz);
 
-                // Calculate tree data once per column
-                let tree_data = self.calculate_tree_data(wx, wz);
+                // Optional caves, kept a few blocks below the surface so it stays closed
+                let caves = self.config.caves.then(|| self.cave_column(
+                    chunk_coord.1 * CHUNK_SIZE,
+                    |world_y| world_y < terrain_height,
+                    |world_y| world_y >= 0 && world_y < terrain_height - 4 && self.is_cave(wx, world_y as f32, wz),
+                ));
 
                 // Fill entire column based on terrain height
                 for y in 0..CHUNK_SIZE {
                     let world_y = chunk_coord.1 as isize * CHUNK_SIZE as isize + y as isize;
                     
-                    let block = if world_y >= terrain_height {
-                        // STEP 3: Above terrain = air
-                        Block::Empty
-                    } else if world_y < 0 {
-                        // Below sea level = water
-                        Block::Water
+                    let sky_block = match &sky_island {
+                        Some(shape) if world_y >= terrain_height => {
+                            self.sky_island_block(wx, world_y as f32, wz, shape, terrain_height as f32)
+                        }
+                        _ => None,
+                    };
+
+                    let block = if let Some(block) = sky_block {
+                        // Floating island (or its waterfall) above the terrain
+                        block
+                    } else if world_y >= terrain_height {
+                        // STEP 3: Above terrain = kelp/sea grass, sea water up to sea level, then air
+                        match seabed_plant {
+                            Some((plant, top)) if world_y < top => plant,
+                            _ if world_y <= SEA_LEVEL => water,
+                            _ => Block::Empty,
+                        }
                     } else if world_y == terrain_height - 1 {
-                        // Surface layer - height-based determination
-                        if world_y >= 200 {
-                            // Above y=200: Snow
-                            Block::Snow
-                        } else if world_y >= 100 {
-                            // Above y=100: Bare stone (no grass)
-                            Block::Stone
+                        column.surface_block
+                    } else if let Some(caves) = &caves {
+                        // Cave air with hanging/standing plants, cave biome floors and ceilings
+                        if caves.is_cave(world_y) {
+                            self.cave_air_block(wx, world_y, wz, caves).unwrap_or(Block::Empty)
                         } else {
-                            // Below y=100: Grass and biome-specific blocks
-                            self.get_surface_block_for_biome(wx, wz, world_y as f32, biome)
+                            self.cave_surface_block(wx, world_y, wz, caves)
+                                .unwrap_or_else(|| self.get_simple_block(wx, wz, world_y, biome, false))
                         }
                     } else {
-                        // Subsurface blocks
-                        if world_y >= 200 {
-                            Block::Snow
-                        } else if world_y >= 100 {
-                            Block::Stone
-                        } else {
-                            self.get_subsurface_block(wx, wz, world_y as f32, biome)
-                        }
+                        self.get_simple_block(wx, wz, world_y, biome, false)
                     };
 
                     chunk.set_block(&BlockCoord(x as usize, y as usize, z as usize), block, false);
-
-                    // Place trees only below y=150 on grass/moss surface
-                    if world_y < 150 && world_y == terrain_height - 1 && tree_data.should_spawn && 
-                       matches!(block, Block::Grass | Block::Moss) {
-                        let tree = Tree {
-                            pos: (x as i32, z as i32),
-                            tree_type: tree_data.tree_type,
-                            trunk_height: tree_data.tree_height,
-                        };
-                        Self::plant_tree(&tree, chunk_coord, world_y as i32 + 1, chunk);
-                    }
                 }
             }
         }
-    }
 
-    /// Plant a tree of given type at specified location
-    fn plant_tree(tree: &Tree, chunk_coord: &crate::utils::ChunkCoord, height: i32, chunk: &mut super::chunk::Chunk) {
-        
-        let (x, z) = tree.pos;
-        match tree.tree_type {
-            TreeType::Oak => Self::plant_oak(tree, chunk_coord, x, z, height, chunk),
-            TreeType::Spruce => Self::plant_spruce(tree, chunk_coord, x, z, height, chunk),
-            TreeType::Birch => Self::plant_birch(tree, chunk_coord, x, z, height, chunk),
-            TreeType::Acacia => Self::plant_acacia(tree, chunk_coord, x, z, height, chunk),
-            TreeType::DarkOak => Self::plant_darkoak(tree, chunk_coord, x, z, height, chunk),
-        }
+        // STEP 4: decorations and trees of this and the surrounding columns, after the terrain
+        let ground = |x: i32, z: i32| *grounds.borrow_mut().entry((x, z)).or_insert_with(|| {
+            let column = self.sample_column(&blender, x as f32, z as f32);
+            (column.height, column.biome)
+        });
+        decorations::decorate_chunk(chunk, chunk_coord, &self.biomes, self.config.seed, &ground);
+        let mut tree_anchors = self.tree_anchors(&blender, chunk_coord);
+        trees::place_trees(chunk, chunk_coord, &mut tree_anchors, self.config.seed);
     }
 
-    /// Plant an Oak tree: compact tree with 1-block trunk and 2-layer foliage
-    fn plant_oak(tree: &Tree, chunk_coord: &crate::utils::ChunkCoord, x: i32, z: i32, world_y: i32, chunk: &mut super::chunk::Chunk) {
-        use crate::utils::BlockCoord;
-        const CHUNK_SIZE: i32 = 16;
-        const OAK_LEAF_RADIUS: i32 = 1;
-        
-        let trunk_h = tree.trunk_height;
-        
-        // Place trunk vertically
-        for ty in 0..trunk_h {
-            let wy = world_y + ty;
-            let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-            if cy_local >= 0 && cy_local < CHUNK_SIZE {
-                chunk.set_block(&BlockCoord(x as usize, cy_local as usize, z as usize), Block::Wood, false);
+    /// Trees of the simple generator rooted in the columns around a chunk (see
+    /// trees::anchor_columns)
+    ///
+    /// Ground trees grow on grass and moss below y=150, sky island trees on the
+    /// grass of the island top. `blender` must cover all these columns.
+    fn tree_anchors(&self, blender: &BiomeBlender, chunk_coord: &crate::utils::ChunkCoord) -> Vec<TreeAnchor> {
+        let mut anchors = Vec::new();
+        for (x, z) in trees::anchor_columns(chunk_coord) {
+            let (wx, wz) = (x as f32, z as f32);
+            if !self.is_tree_center(wx, wz) {
+                continue;
             }
-        }
-        
-        // Place foliage: 2 layers with compact 3x3 shape
-        let leaves_base = world_y + trunk_h - 2;
-        for ly in 0..2 {
-            let wy = leaves_base + ly;
-            let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-            if cy_local < 0 || cy_local >= CHUNK_SIZE as i32 { continue; }
-            
-            for lx in -OAK_LEAF_RADIUS..=OAK_LEAF_RADIUS {
-                for lz in -OAK_LEAF_RADIUS..=OAK_LEAF_RADIUS {
-                    let nx = x + lx;
-                    let nz = z + lz;
-                    if nx < 0 || nz < 0 || nx >= CHUNK_SIZE as i32 || nz >= CHUNK_SIZE as i32 { continue; }
-                    
-                    // Place leaves in 3x3 area
-                    chunk.set_block(&BlockCoord(nx as usize, cy_local as usize, nz as usize), Block::OakLeaves, false);
-                }
+            let column = self.sample_column(blender, wx, wz);
+            let tree_data = self.calculate_tree_data(wx, wz, column.biome);
+            if !tree_data.should_spawn {
+                continue;
             }
-        }
-    }
 
-    /// Plant a Spruce tree: conical tree with 1-block trunk and 3-layer foliage
-    fn plant_spruce(tree: &Tree, chunk_coord: &crate::utils::ChunkCoord, x: i32, z: i32, world_y: i32, chunk: &mut super::chunk::Chunk) {
-        use crate::utils::BlockCoord;
-        const CHUNK_SIZE: i32 = 16;
-        
-        let trunk_h = tree.trunk_height;
-        
-        // Place trunk vertically
-        for ty in 0..trunk_h {
-            let wy = world_y + ty;
-            let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-            if cy_local >= 0 && cy_local < CHUNK_SIZE {
-                chunk.set_block(&BlockCoord(x as usize, cy_local as usize, z as usize), Block::SpruceWood, false);
+            if column.height - 1 < 150 && matches!(column.surface_block, Block::Grass | Block::Moss) {
+                anchors.push(tree_data.anchor(x, column.height, z));
             }
-        }
-        
-        // Place foliage: 3 layers in conical shape (2x2, 2x2, 1x1)
-        let leaves_base = world_y + trunk_h - 2;
-        for ly in 0..3 {
-            let wy = leaves_base + ly;
-            let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-            if cy_local < 0 || cy_local >= CHUNK_SIZE as i32 { continue; }
-            
-            // Radius shrinks for upper layers (cone shape)
-            let radius = match ly {
-                0 => 2,      // Bottom: wide
-                1 => 1,      // Middle: medium
-                _ => 1,      // Top: narrow
-            };
-            
-            for lx in -radius..=radius {
-                for lz in -radius..=radius {
-                    let nx = x + lx;
-                    let nz = z + lz;
-                    if nx < 0 || nz < 0 || nx >= CHUNK_SIZE as i32 || nz >= CHUNK_SIZE as i32 { continue; }
-                    
-                    let dist_sq = lx * lx + lz * lz;
-                    // Create circular foliage (not square)
-                    if dist_sq <= (radius * radius + 1) {
-                        chunk.set_block(&BlockCoord(nx as usize, cy_local as usize, nz as usize), Block::SpruceLeaves, false);
-                    }
-                }
+            let sky_top = self.sky_island_shape(wx, wz)
+                .and_then(|shape| self.sky_island_grass(wx, wz, &shape, column.height));
+            if let Some(top) = sky_top {
+                anchors.push(tree_data.anchor(x, top + 1, z));
             }
         }
+        anchors
     }
 
-    /// Plant a Birch tree: tall thin tree with 1-block trunk and 2-layer foliage
-    fn plant_birch(tree: &Tree, chunk_coord: &crate::utils::ChunkCoord, x: i32, z: i32, world_y: i32, chunk: &mut super::chunk::Chunk) {
-        use crate::utils::BlockCoord;
-        const CHUNK_SIZE: i32 = 16;
-        const BIRCH_LEAF_RADIUS: i32 = 1;
-        
-        let trunk_h = tree.trunk_height;
-        
-        // Place trunk vertically
-        for ty in 0..trunk_h {
-            let wy = world_y + ty;
-            let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-            if cy_local >= 0 && cy_local < CHUNK_SIZE {
-                chunk.set_block(&BlockCoord(x as usize, cy_local as usize, z as usize), Block::BirchWood, false);
-            }
-        }
-        
-        // Place foliage: 2 layers with compact spherical shape
-        let leaves_base = world_y + trunk_h - 2;
-        for ly in 0..2 {
-            let wy = leaves_base + ly;
-            let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-            if cy_local < 0 || cy_local >= CHUNK_SIZE as i32 { continue; }
-            
-            for lx in -BIRCH_LEAF_RADIUS..=BIRCH_LEAF_RADIUS {
-                for lz in -BIRCH_LEAF_RADIUS..=BIRCH_LEAF_RADIUS {
-                    let nx = x + lx;
-                    let nz = z + lz;
-                    if nx < 0 || nz < 0 || nx >= CHUNK_SIZE as i32 || nz >= CHUNK_SIZE as i32 { continue; }
-                    
-                    // Place leaves in 3x3 area
-                    chunk.set_block(&BlockCoord(nx as usize, cy_local as usize, nz as usize), Block::BirchLeaves, false);
-                }
-            }
+    /// Sample biome, terrain height and surface block of a column for the simple generator
+    ///
+    /// `blender` must cover the column. Used by populate_chunk_simple and by tools
+    /// that need the terrain without generating chunks (e.g. the terrain preview).
+    pub fn sample_column(&self, blender: &BiomeBlender, wx: f32, wz: f32) -> ColumnSample {
+        // Surface materials use one jittered biome, height and tint use the blend
+        let weights = blender.weights_at(wx as i32, wz as i32);
+        let biome = self.surface_biome(&weights, wx, wz);
+        let tint = self.biome_tint(&weights);
+
+        let mut height = self.column_height(&weights, wx, wz);
+        let mut erosion = 0.0;
+        if self.config.erosion {
+            erosion = self.erosion_cache.delta(self, wx, wz);
+            height = (height as f32 + erosion).round() as isize;
         }
+        let surface_block = self.get_simple_block(wx, wz, height - 1, biome, true);
+        let surface_block = Self::eroded_surface_block(surface_block, erosion, height - 1);
+
+        ColumnSample { weights, biome, height, surface_block, tint, erosion }
     }
 
-    /// Plant an Acacia tree: dry climate tree with 1-block trunk and wide foliage
-    fn plant_acacia(tree: &Tree, chunk_coord: &crate::utils::ChunkCoord, x: i32, z: i32, world_y: i32, chunk: &mut super::chunk::Chunk) {
-        use crate::utils::BlockCoord;
-        const CHUNK_SIZE: i32 = 16;
-        
-        let trunk_h = tree.trunk_height;
-        
-        // Place trunk vertically
-        for ty in 0..trunk_h {
-            let wy = world_y + ty;
-            let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-            if cy_local >= 0 && cy_local < CHUNK_SIZE {
-                chunk.set_block(&BlockCoord(x as usize, cy_local as usize, z as usize), Block::AcaciaWood, false);
-            }
-        }
-        
-        // Acacia: wide, flat foliage - 2 layers with radius 2
-        let leaves_base = world_y + trunk_h - 1;
-        for ly in 0..2 {
-            let wy = leaves_base + ly;
-            let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-            if cy_local < 0 || cy_local >= CHUNK_SIZE as i32 { continue; }
-            
-            let radius = 2;
-            for lx in -radius..=radius {
-                for lz in -radius..=radius {
-                    let nx = x + lx;
-                    let nz = z + lz;
-                    if nx < 0 || nz < 0 || nx >= CHUNK_SIZE as i32 || nz >= CHUNK_SIZE as i32 { continue; }
-                    
-                    // Create circular foliage shape
-                    let dist_sq = lx * lx + lz * lz;
-                    if dist_sq <= 5 {
-                        chunk.set_block(&BlockCoord(nx as usize, cy_local as usize, nz as usize), Block::AcaciaLeaves, false);
-                    }
-                }
+    /// Cheap column sample for far terrain (horizon.rs), no lattice or erosion needed
+    ///
+    /// Blends only five biome samples (the column and four points at half the
+    /// blend radius), which is close to sample_column seen from far away.
+    pub fn coarse_column(&self, wx: f32, wz: f32) -> ColumnSample {
+        let r = self.config.biome_blend_radius.max(0.0) * 0.5;
+        let mut weights = BiomeWeights::default();
+        weights.add(self.get_biome_type(wx, wz, 30.0), 2.0);
+        if r > 0.0 {
+            for (dx, dz) in [(r, 0.0), (-r, 0.0), (0.0, r), (0.0, -r)] {
+                weights.add(self.get_biome_type(wx + dx, wz + dz, 30.0), 1.0);
             }
         }
+        weights.normalize();
+
+        let biome = weights.dominant();
+        let height = self.column_height(&weights, wx, wz);
+        let surface_block = self.get_simple_block(wx, wz, height - 1, biome, true);
+        ColumnSample { tint: self.biome_tint(&weights), weights, biome, height, surface_block, erosion: 0.0 }
     }
 
-    /// Plant a Dark Oak tree: large tree with 2-block trunk and dense foliage
-    fn plant_darkoak(tree: &Tree, chunk_coord: &crate::utils::ChunkCoord, x: i32, z: i32, world_y: i32, chunk: &mut super::chunk::Chunk) {
-        use crate::utils::BlockCoord;
-        const CHUNK_SIZE: i32 = 16;
-        
-        let trunk_h = tree.trunk_height;
-        
-        // Dark Oak: 2x2 trunk base
-        for tx in 0..2 {
-            for tz in 0..2 {
-                for ty in 0..trunk_h {
-                    let wy = world_y + ty;
-                    let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-                    if cy_local >= 0 && cy_local < CHUNK_SIZE {
-                        let nx = x + tx;
-                        let nz = z + tz;
-                        if nx < 0 || nz < 0 || nx >= CHUNK_SIZE as i32 || nz >= CHUNK_SIZE as i32 { continue; }
-                        chunk.set_block(&BlockCoord(nx as usize, cy_local as usize, nz as usize), Block::DarkOakWood, false);
-                    }
-                }
-            }
+    /// Column height before erosion (the input of the erosion simulation)
+    pub fn uneroded_height(&self, blender: &BiomeBlender, wx: f32, wz: f32) -> isize {
+        self.column_height(&blender.weights_at(wx as i32, wz as i32), wx, wz)
+    }
+
+    fn column_height(&self, weights: &BiomeWeights, wx: f32, wz: f32) -> isize {
+        // Use higher frequency (0.08 by default) for more terrain variation and detail
+        // More octaves (6) for realistic mountain/valley transitions
+        let freq = self.config.height_freq;
+        let height_noise = self.fbm(wx * freq, wz * freq, freq, 0.55, 6);
+        let land_height = self.blended_terrain_height(height_noise, weights);
+        self.coastal_height(wx, wz, land_height)
+    }
+
+    /// Texture erosion features: gravel in carved gullies, sediment fans of dirt
+    /// (sand near the sea) where droplets dropped their load
+    fn eroded_surface_block(block: Block, erosion: f32, world_y: isize) -> Block {
+        let is_soil = matches!(block, Block::Grass | Block::Moss | Block::Dirt);
+        if !is_soil || world_y < SEA_LEVEL {
+            block
+        } else if erosion < -2.5 {
+            Block::Gravel
+        } else if erosion > 1.5 {
+            if world_y <= SEA_LEVEL + 3 { Block::Sand } else { Block::Dirt }
+        } else {
+            block
         }
-        
-        // Dark Oak: Dense foliage - 3 layers, large radius
-        let leaves_base = world_y + trunk_h - 3;
-        for ly in 0..3 {
-            let wy = leaves_base + ly;
-            let cy_local = wy - chunk_coord.1 as i32 * CHUNK_SIZE;
-            if cy_local < 0 || cy_local >= CHUNK_SIZE as i32 { continue; }
-            
-            let radius = match ly {
-                0 => 3,     // Bottom: very wide
-                1 => 2,     // Middle: medium
-                _ => 1,     // Top: narrow
-            };
-            
-            for lx in -radius..=radius {
-                for lz in -radius..=radius {
-                    let nx = x + lx;
-                    let nz = z + lz;
-                    if nx < 0 || nz < 0 || nx >= CHUNK_SIZE as i32 || nz >= CHUNK_SIZE as i32 { continue; }
-                    
-                    chunk.set_block(&BlockCoord(nx as usize, cy_local as usize, nz as usize), Block::DarkOakLeaves, false);
-                }
-            }
+    }
+
+    /// Height-based block selection of the simple generator for a solid position
+    fn get_simple_block(&self, wx: f32, wz: f32, world_y: isize, biome: BiomeId, is_surface: bool) -> Block {
+        if world_y >= 200 {
+            // Above y=200: Snow
+            Block::Snow
+        } else if world_y >= 100 {
+            // Above y=100: Bare stone (no grass)
+            Block::Stone
+        } else if is_surface {
+            // Below y=100: Grass and biome-specific blocks
+            self.get_surface_block_for_biome(wx, wz, world_y as f32, biome)
+        } else {
+            self.get_subsurface_block(wx, wz, world_y as f32, biome)
         }
     }
 }
@@ -1015,6 +1460,8 @@ impl VoxelDensityGenerator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::ChunkCoord;
+    use std::collections::HashSet;
 
     #[test]
     fn test_density_gradient() {
@@ -1038,4 +1485,307 @@ mod tests {
         let _ = generator.get_biome_type(1000.0, 1000.0, 100.0);
         let _ = generator.get_biome_type(-1000.0, -1000.0, 50.0);
     }
+
+    #[test]
+    fn test_sky_islands_stay_below_clouds() {
+        // band reaching into the cloud layer is clamped
+        let config = TerrainConfig { sky_islands: true, sky_island_max: 300.0, ..TerrainConfig::default() };
+        let generator = VoxelDensityGenerator::with_config(config);
+
+        let mut islands = 0;
+        for i in 0..64 {
+            for j in 0..64 {
+                let (x, z) = (i as f32 * 8.0, j as f32 * 8.0);
+                let Some(shape) = generator.sky_island_shape(x, z) else { continue };
+                islands += 1;
+                let cloud_height = config.cloud_height as f32;
+                assert!(shape.top + 4.0 < cloud_height);
+                assert!(generator.calculate_density(x, cloud_height, z) <= 0.0);
+            }
+        }
+        assert!(islands > 0, "no sky islands generated");
+    }
+
+    #[test]
+    fn test_kelp_and_lake_water() {
+        use crate::model::world::Chunk;
+        use crate::utils::{BlockCoord, ChunkCoord};
+
+        let generator = VoxelDensityGenerator::new();
+        let count = |chunk: &Chunk, block: Block| {
+            (0..CHUNK_SIZE as usize * CHUNK_SIZE as usize * CHUNK_SIZE as usize)
+                .filter(|i| chunk.get_block(&BlockCoord(i % 16, i / 16 % 16, i / 256)) == block)
+                .count()
+        };
+
+        // the sea around the origin grows kelp in the density generator too
+        let mut kelp = 0;
+        for (cx, cz) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            for cy in [-2, -1] {
+                let mut chunk = Chunk::new_empty();
+                generator.populate_chunk(&mut chunk, &ChunkCoord(cx, cy, cz));
+                kelp += count(&chunk, Block::Kelp);
+            }
+        }
+        assert!(kelp > 0, "no kelp in the density generator's sea");
+
+        // a column of an inland biome below sea level is flooded with lake water
+        let (wx, wz, biome) = (0..4096)
+            .map(|i| ((i % 64) as f32 * 24.0, (i / 64) as f32 * 24.0))
+            .find_map(|(wx, wz)| {
+                let blender = BiomeBlender::new(&generator, wx as i32, wz as i32, 1);
+                let column = generator.sample_column(&blender, wx, wz);
+                (column.height <= SEA_LEVEL && generator.biomes.get(column.biome).water() == Block::LakeWater)
+                    .then_some((wx as isize, wz as isize, column.biome))
+            })
+            .expect("no inland water found");
+        assert_eq!(generator.biomes.get(generator.biomes.id("lake").unwrap()).water(), Block::LakeWater);
+        assert_eq!(generator.biomes.get(generator.biomes.id("ocean").unwrap()).water(), Block::Water);
+
+        let coord = ChunkCoord(wx.div_euclid(CHUNK_SIZE), 0, wz.div_euclid(CHUNK_SIZE));
+        let mut chunk = Chunk::new_empty();
+        generator.populate_chunk_simple(&mut chunk, &coord);
+        let top = chunk.get_block(&BlockCoord(wx.rem_euclid(CHUNK_SIZE) as usize, SEA_LEVEL as usize, wz.rem_euclid(CHUNK_SIZE) as usize));
+        assert!(
+            matches!(top, Block::LakeWater | Block::Ice),
+            "{} column at sea level is {top:?}",
+            generator.biomes.get(biome).name
+        );
+    }
+
+    #[test]
+    fn test_density_trees_cross_chunks() {
+        use crate::model::world::Chunk;
+        use crate::utils::{BlockCoord, ChunkCoord};
+
+        let generator = VoxelDensityGenerator::new();
+        let anchors = |coord: &ChunkCoord| {
+            let (x, z) = (coord.0 as i32 * CHUNK_SIZE as i32, coord.2 as i32 * CHUNK_SIZE as i32);
+            let blender = BiomeBlender::new(&generator, x - TREE_REACH, z - TREE_REACH, CHUNK_SIZE as i32 + 2 * TREE_REACH);
+            generator.density_tree_anchors(&blender, coord)
+        };
+
+        // wooded hills around a tree near x = z = -640
+        let coords: Vec<_> = (-41..-38)
+            .flat_map(|cx| (1..4).flat_map(move |cy| (-41..-38).map(move |cz| ChunkCoord(cx, cy, cz))))
+            .collect();
+        let found: Vec<_> = coords.iter().map(anchors).collect();
+        let mut trees = 0;
+        for (coord, list) in coords.iter().zip(&found) {
+            let mut chunk = Chunk::new_empty();
+            generator.populate_chunk(&mut chunk, coord);
+            for anchor in list {
+                let (x, y, z) = anchor.pos;
+                let min = |c: isize| c as i32 * CHUNK_SIZE as i32;
+                let (lx, ly, lz) = (x - min(coord.0), y - 1 - min(coord.1), z - min(coord.2));
+                if [lx, ly, lz].iter().all(|l| (0..CHUNK_SIZE as i32).contains(l)) {
+                    // rooted in this chunk: on the grass or moss it generated (or the tree's roots)
+                    let ground = chunk.get_block(&BlockCoord(lx as usize, ly as usize, lz as usize));
+                    assert!(
+                        matches!(ground, Block::Grass | Block::Moss) || ground == anchor.tree_type.wood(),
+                        "tree at {:?} grows on {ground:?}",
+                        anchor.pos
+                    );
+                    trees += 1;
+                }
+                // every chunk the tree may reach into knows it, so it isn't cut at the border
+                for (other, other_list) in coords.iter().zip(&found) {
+                    let near = |a: i32, c: isize| a >= min(c) - TREE_REACH && a < min(c) + CHUNK_SIZE as i32 + TREE_REACH;
+                    if near(x, other.0) && near(z, other.2) && trees::reaches_layer(y, other.1) {
+                        assert!(other_list.contains(anchor), "{other:?} misses the tree at {:?}", anchor.pos);
+                    }
+                }
+            }
+        }
+        assert!(trees > 0, "no trees in the test area");
+    }
+
+    #[test]
+    fn test_cave_biome_surfaces() {
+        let generator = VoxelDensityGenerator::new();
+        // solid below y=10, a cave from 10 to 12, solid from 13 up
+        let column = generator.cave_column(0, |_| true, |y| (10..13).contains(&y));
+        let mut biomes = HashSet::new();
+
+        for i in 0..200 {
+            let (x, z) = (i as f32 * 37.0, i as f32 * -23.0);
+            let biome = generator.get_cave_biome(x, 9.0, z);
+            biomes.insert(format!("{biome:?}"));
+            let floor = generator.cave_surface_block(x, 9, z, &column);
+            let expected = match biome {
+                CaveBiome::Stone => vec![None],
+                CaveBiome::LushCaves => vec![Some(Block::Moss)],
+                CaveBiome::DripstoneCaves => vec![Some(Block::Dripstone)],
+                CaveBiome::BasaltDeltas => vec![Some(Block::Basalt), Some(Block::Magma)],
+            };
+            assert!(expected.contains(&floor), "{biome:?} floor {floor:?}");
+            // walls away from the cave stay terrain, plants only grow inside it
+            assert_eq!(generator.cave_surface_block(x, 5, z, &column), None);
+            assert_eq!(generator.cave_air_block(x, 20, z, &column), None);
+        }
+        assert!(biomes.len() >= 3, "cave biomes too uniform: {biomes:?}");
+    }
+
+    #[test]
+    fn test_presets_round_trip() {
+        // the default preset file must match TerrainConfig::default()
+        assert_eq!(TerrainConfig::preset("default"), Some(TerrainConfig::default()));
+
+        for (name, _) in TERRAIN_PRESETS {
+            let config = TerrainConfig::preset(name).unwrap();
+            let reloaded = TerrainConfig::from_toml(&config.to_toml()).unwrap();
+            assert_eq!(config, reloaded, "preset '{name}' changed after save and load");
+        }
+    }
+
+    /// Golden chunk hashes, one line per chunk: `<generator> <seed> <cx> <cy> <cz> <fingerprint>`
+    const GOLDEN_FILE: &str = "src/model/world/terrain_goldens.txt";
+    const GOLDENS: &str = include_str!("terrain_goldens.txt");
+
+    /// Chunks covered by the golden test: generator, seed and chunk
+    ///
+    /// Noise chunks sit around the surface (caves, surface and trees), since
+    /// uniform air or stone chunks all hash alike; heightmap chunks are from the
+    /// ramp of `golden_heightmap`, underworld chunks span floor to roof.
+    const GOLDEN_CHUNKS: &[(&str, u32, ChunkCoord)] = &[
+        ("simple", 0, ChunkCoord(0, -5, 0)),
+        ("density", 0, ChunkCoord(0, -5, 0)),
+        ("simple", 0, ChunkCoord(0, -4, 0)),
+        ("density", 0, ChunkCoord(0, -4, 0)),
+        ("simple", 0, ChunkCoord(0, -3, 0)),
+        ("density", 0, ChunkCoord(0, -3, 0)),
+        ("simple", 0, ChunkCoord(0, -2, 0)),
+        ("density", 0, ChunkCoord(0, -2, 0)),
+        ("simple", 0, ChunkCoord(-3, 2, 5)),
+        ("density", 0, ChunkCoord(-3, -1, 5)),
+        ("simple", 0, ChunkCoord(-3, 3, 5)),
+        ("density", 0, ChunkCoord(-3, 0, 5)),
+        ("simple", 0, ChunkCoord(-3, 4, 5)),
+        ("density", 0, ChunkCoord(-3, 1, 5)),
+        ("simple", 0, ChunkCoord(-3, 5, 5)),
+        ("density", 0, ChunkCoord(-3, 2, 5)),
+        ("simple", 0, ChunkCoord(250, -4, -310)),
+        ("density", 0, ChunkCoord(250, -4, -310)),
+        ("simple", 0, ChunkCoord(250, -3, -310)),
+        ("density", 0, ChunkCoord(250, -3, -310)),
+        ("simple", 0, ChunkCoord(250, -2, -310)),
+        ("density", 0, ChunkCoord(250, -2, -310)),
+        ("simple", 0, ChunkCoord(250, -1, -310)),
+        ("density", 0, ChunkCoord(250, -1, -310)),
+        ("simple", 1, ChunkCoord(-40, -1, 17)),
+        ("density", 1, ChunkCoord(-40, -2, 17)),
+        ("simple", 1, ChunkCoord(-40, 0, 17)),
+        ("density", 1, ChunkCoord(-40, -1, 17)),
+        ("simple", 1, ChunkCoord(-40, 1, 17)),
+        ("density", 1, ChunkCoord(-40, 0, 17)),
+        ("simple", 1, ChunkCoord(-40, 2, 17)),
+        ("density", 1, ChunkCoord(-40, 1, 17)),
+        ("simple", 42, ChunkCoord(5, -3, 5)),
+        ("density", 42, ChunkCoord(5, -3, 5)),
+        ("simple", 42, ChunkCoord(5, -2, 5)),
+        ("density", 42, ChunkCoord(5, -2, 5)),
+        ("simple", 42, ChunkCoord(5, -1, 5)),
+        ("density", 42, ChunkCoord(5, -1, 5)),
+        ("simple", 42, ChunkCoord(5, 0, 5)),
+        ("density", 42, ChunkCoord(5, 0, 5)),
+        ("simple", 123456, ChunkCoord(64, -4, -64)),
+        ("density", 123456, ChunkCoord(64, -3, -64)),
+        ("simple", 123456, ChunkCoord(64, -3, -64)),
+        ("density", 123456, ChunkCoord(64, -2, -64)),
+        ("simple", 123456, ChunkCoord(64, -2, -64)),
+        ("density", 123456, ChunkCoord(64, -1, -64)),
+        ("simple", 123456, ChunkCoord(64, -1, -64)),
+        ("density", 123456, ChunkCoord(64, 0, -64)),
+        ("heightmap", 0, ChunkCoord(-2, -3, -2)),
+        ("heightmap", 0, ChunkCoord(-2, -2, -2)),
+        ("heightmap", 0, ChunkCoord(-2, -1, -2)),
+        ("heightmap", 0, ChunkCoord(0, 2, 0)),
+        ("heightmap", 0, ChunkCoord(0, 3, 0)),
+        ("heightmap", 0, ChunkCoord(-1, 2, 1)),
+        ("heightmap", 0, ChunkCoord(1, 4, 1)),
+        ("heightmap", 0, ChunkCoord(1, 5, 1)),
+        ("heightmap", 7, ChunkCoord(0, 2, 0)),
+        ("heightmap", 7, ChunkCoord(1, 4, 1)),
+        ("underworld", 0, ChunkCoord(0, 0, 0)),
+        ("underworld", 0, ChunkCoord(0, 1, 0)),
+        ("underworld", 0, ChunkCoord(0, 6, 0)),
+        ("underworld", 0, ChunkCoord(0, 7, 0)),
+        ("underworld", 0, ChunkCoord(3, 1, -2)),
+        ("underworld", 0, ChunkCoord(3, 2, -2)),
+        ("underworld", 0, ChunkCoord(-5, 2, 9)),
+        ("underworld", 0, ChunkCoord(-5, 6, 9)),
+        ("underworld", 7, ChunkCoord(0, 1, 0)),
+        ("underworld", 7, ChunkCoord(-5, 1, 9)),
+    ];
+
+    /// In-memory heightmap for the golden test: a 64x64 ramp from below the sea up to hills
+    fn golden_heightmap() -> Vec<u8> {
+        use image::{GrayImage, ImageFormat, Luma};
+        let image = GrayImage::from_fn(64, 64, |x, z| Luma([(x * 2 + z * 2) as u8]));
+        let mut bytes = Vec::new();
+        image.write_to(&mut std::io::Cursor::new(&mut bytes), ImageFormat::Png).unwrap();
+        bytes
+    }
+
+    /// Checks generated chunks against committed hashes so terrain changes are never silent
+    ///
+    /// When a change to generated worlds is intended, regenerate the file with
+    ///   WOXEL_UPDATE_GOLDENS=1 cargo test golden
+    /// and commit terrain_goldens.txt together with the change.
+    #[test]
+    fn test_golden_chunk_hashes() {
+        use crate::model::world::heightmap::{HeightmapConfig, HeightmapGenerator};
+        use crate::model::world::underworld::UnderworldGenerator;
+        use crate::model::world::{Chunk, WorldGenerator};
+        use std::collections::HashSet;
+
+        let heightmap = golden_heightmap();
+        let mut actual = String::new();
+        let mut hashes = HashSet::new();
+        for (name, seed, coord) in GOLDEN_CHUNKS {
+            let terrain = VoxelDensityGenerator::with_config(TerrainConfig { seed: *seed, ..TerrainConfig::default() });
+            let mut chunk = Chunk::new_empty();
+            match *name {
+                "simple" => terrain.populate_chunk_simple(&mut chunk, coord),
+                "density" => terrain.populate_chunk(&mut chunk, coord),
+                "heightmap" => WorldGenerator::Heightmap(
+                    HeightmapGenerator::from_memory(&heightmap, None, HeightmapConfig::default(), terrain).unwrap(),
+                )
+                .populate_chunk(&mut chunk, coord),
+                "underworld" => WorldGenerator::Underworld(UnderworldGenerator::new(*seed)).populate_chunk(&mut chunk, coord),
+                _ => unreachable!("unknown generator {name}"),
+            }
+            let ChunkCoord(cx, cy, cz) = coord;
+            actual.push_str(&format!("{name} {seed} {cx} {cy} {cz} {:016x}\n", chunk.fingerprint()));
+            hashes.insert(chunk.fingerprint());
+        }
+
+        // Identical hashes mean the set checks less terrain than it looks like
+        let chunks = actual.lines().count();
+        assert!(
+            hashes.len() * 4 >= chunks * 3,
+            "only {} distinct hashes among {chunks} golden chunks",
+            hashes.len()
+        );
+
+        if std::env::var_os("WOXEL_UPDATE_GOLDENS").is_some() {
+            let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_FILE);
+            std::fs::write(&path, &actual).expect("cannot write golden file");
+            return;
+        }
+
+        let changed: Vec<_> = actual
+            .lines()
+            .zip(GOLDENS.lines())
+            .filter(|(a, g)| a != g)
+            .map(|(a, g)| format!("  expected {g}\n  got      {a}"))
+            .collect();
+        assert!(
+            changed.is_empty() && actual.lines().count() == GOLDENS.lines().count(),
+            "generated terrain changed ({} chunks):\n{}\n\
+             if this is intended, run WOXEL_UPDATE_GOLDENS=1 cargo test golden and commit {GOLDEN_FILE}",
+            changed.len(),
+            changed.join("\n")
+        );
+    }
 }
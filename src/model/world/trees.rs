@@ -0,0 +1,514 @@
+// ============================================================================
+// TREES
+// ============================================================================
+//
+// Parameterized tree generator. Every tree grows from a TreeAnchor (the first
+// block above the ground of its column) with a random generator seeded by the
+// anchor position, so a tree has the same shape in every chunk that sees it:
+//   → trunk: 1x1 or 2x2 (giant dark oak and jungle trees), optionally leaning
+//   → roots: short soil roots or tall buttress roots above the ground
+//   → branches: leave the trunk at random angles and fork once on giant trees
+//   → canopy: leaf blobs, spruce cones or flat acacia disks at every tip
+//
+// Trees may reach up to TREE_REACH blocks sideways, so they cross chunk borders.
+// A chunk collects the anchors of all columns around it that are close enough
+// (anchor_columns) and writes only the blocks inside itself (place_trees).
+// Wood wins over leaves and leaves only grow into air and small plants, so the
+// result does not depend on the order chunks are generated in.
+// ============================================================================
+
+use std::f32::consts::TAU;
+
+use super::block::Block;
+use super::chunk::{Chunk, CHUNK_SIZE};
+use crate::utils::{BlockCoord, ChunkCoord};
+
+/// Farthest any tree block lies from its anchor along x or z
+pub const TREE_REACH: i32 = 14;
+
+/// Highest tree block above the anchor
+const TREE_MAX_HEIGHT: i32 = 44;
+
+/// Deepest root below the anchor
+const TREE_MAX_DEPTH: i32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeType {
+    Oak,
+    Spruce,
+    Birch,
+    Acacia,     // Baum für Trockengebiete/Jungle
+    DarkOak,    // Großer Baum, 2x2 Stamm
+    Jungle,     // Riesiger Dschungelbaum, 2x2 Stamm mit Brettwurzeln
+}
+
+impl TreeType {
+    /// Tree type by its name in biome files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "oak" => Some(TreeType::Oak),
+            "spruce" => Some(TreeType::Spruce),
+            "birch" => Some(TreeType::Birch),
+            "acacia" => Some(TreeType::Acacia),
+            "dark_oak" => Some(TreeType::DarkOak),
+            "jungle" => Some(TreeType::Jungle),
+            _ => None,
+        }
+    }
+
+    /// Wood block of the trunk (also used for fallen logs)
+    pub fn wood(self) -> Block {
+        species(self).wood
+    }
+}
+
+/// A tree rooted at a world position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TreeAnchor {
+    /// World position of the lowest trunk block (one above the ground)
+    pub pos: (i32, i32, i32),
+    pub tree_type: TreeType,
+    /// Trunk height in blocks, branches and canopy scale with it
+    pub height: i32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Canopy {
+    /// Ragged leaf ellipsoid
+    Blob,
+    /// Stacked disks shrinking towards the top
+    Cone,
+    /// Two flat disks
+    Flat,
+}
+
+/// Shape parameters of a tree type
+struct Species {
+    wood: Block,
+    leaves: Block,
+    trunk_width: i32,
+    /// Sideways shift of the trunk top, relative to the height
+    lean: f32,
+    /// Number of branches (min, max)
+    branches: (u32, u32),
+    /// Lowest branch, relative to the height
+    branch_start: f32,
+    /// Branch length relative to the height
+    branch_length: f32,
+    /// Rise of a branch per block outwards
+    branch_rise: f32,
+    /// Branches fork once at their end
+    forks: bool,
+    canopy: Canopy,
+    /// Horizontal radius of the leaves at the trunk top and the branch tips
+    leaf_radius: f32,
+    /// Vertical radius of leaf blobs relative to the horizontal radius
+    leaf_stretch: f32,
+    roots: u32,
+    /// Roots start this high up the trunk (buttress roots)
+    root_height: i32,
+}
+
+fn species(tree_type: TreeType) -> Species {
+    let base = Species {
+        wood: Block::Wood,
+        leaves: Block::OakLeaves,
+        trunk_width: 1,
+        lean: 0.0,
+        branches: (0, 0),
+        branch_start: 0.6,
+        branch_length: 0.3,
+        branch_rise: 0.6,
+        forks: false,
+        canopy: Canopy::Blob,
+        leaf_radius: 2.5,
+        leaf_stretch: 0.8,
+        roots: 0,
+        root_height: 0,
+    };
+    match tree_type {
+        TreeType::Oak => Species { branches: (1, 3), roots: 2, ..base },
+        TreeType::Birch => Species {
+            wood: Block::BirchWood,
+            leaves: Block::BirchLeaves,
+            branches: (0, 1),
+            branch_start: 0.7,
+            branch_length: 0.2,
+            leaf_radius: 2.0,
+            leaf_stretch: 1.4,
+            ..base
+        },
+        TreeType::Spruce => Species {
+            wood: Block::SpruceWood,
+            leaves: Block::SpruceLeaves,
+            canopy: Canopy::Cone,
+            leaf_radius: 3.0,
+            roots: 2,
+            ..base
+        },
+        TreeType::Acacia => Species {
+            wood: Block::AcaciaWood,
+            leaves: Block::AcaciaLeaves,
+            lean: 0.25,
+            branches: (1, 2),
+            branch_start: 0.55,
+            branch_length: 0.45,
+            branch_rise: 0.5,
+            canopy: Canopy::Flat,
+            leaf_radius: 3.0,
+            ..base
+        },
+        TreeType::DarkOak => Species {
+            wood: Block::DarkOakWood,
+            leaves: Block::DarkOakLeaves,
+            trunk_width: 2,
+            branches: (3, 5),
+            branch_start: 0.5,
+            branch_length: 0.35,
+            branch_rise: 0.4,
+            leaf_radius: 3.5,
+            leaf_stretch: 0.6,
+            roots: 4,
+            root_height: 1,
+            ..base
+        },
+        TreeType::Jungle => Species {
+            wood: Block::JungleWood,
+            leaves: Block::JungleLeaves,
+            trunk_width: 2,
+            branches: (3, 6),
+            branch_start: 0.6,
+            branch_length: 0.25,
+            branch_rise: 0.5,
+            forks: true,
+            leaf_radius: 3.5,
+            leaf_stretch: 0.6,
+            roots: 6,
+            root_height: 3,
+            ..base
+        },
+    }
+}
+
+/// Deterministic random numbers for one tree (splitmix64)
+struct TreeRng(u64);
+
+impl TreeRng {
+    fn new(x: i32, z: i32, seed: u32) -> Self {
+        Self(((x as u32 as u64) << 32 | z as u32 as u64) ^ (seed as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in [0, 1)
+    fn next(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next()
+    }
+
+    fn count(&mut self, (min, max): (u32, u32)) -> u32 {
+        min + (self.next() * (max - min + 1) as f32) as u32 % (max - min + 1)
+    }
+}
+
+/// Which part of a tree a block belongs to (decides what it may replace)
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Part {
+    Wood,
+    Leaves,
+    Root,
+}
+
+fn is_leaves(block: Block) -> bool {
+    matches!(
+        block,
+        Block::OakLeaves | Block::SpruceLeaves | Block::BirchLeaves | Block::AcaciaLeaves |
+        Block::DarkOakLeaves | Block::JungleLeaves
+    )
+}
+
+impl Part {
+    fn replaces(self, block: Block) -> bool {
+        match self {
+            Part::Leaves => block.is_open(),
+            Part::Wood => block.is_open() || is_leaves(block),
+            Part::Root => {
+                block.is_open() || is_leaves(block) ||
+                matches!(block, Block::Dirt | Block::Grass | Block::Moss | Block::Sand | Block::Gravel | Block::Clay | Block::Snow)
+            }
+        }
+    }
+}
+
+/// Columns whose trees may reach into a chunk, as world (x, z)
+pub fn anchor_columns(chunk_coord: &ChunkCoord) -> impl Iterator<Item = (i32, i32)> {
+    let size = CHUNK_SIZE as i32;
+    let (min_x, min_z) = (chunk_coord.0 as i32 * size - TREE_REACH, chunk_coord.2 as i32 * size - TREE_REACH);
+    let extent = size + 2 * TREE_REACH;
+    (0..extent).flat_map(move |dz| (0..extent).map(move |dx| (min_x + dx, min_z + dz)))
+}
+
+/// Anchor heights (world y) of trees that could reach into the chunk layer `chunk_y`
+pub fn anchor_heights(chunk_y: isize) -> std::ops::Range<i32> {
+    let min = chunk_y as i32 * CHUNK_SIZE as i32;
+    min - TREE_MAX_HEIGHT..min + CHUNK_SIZE as i32 + TREE_MAX_DEPTH
+}
+
+/// Whether a tree anchored at world y could reach into the chunk layer `chunk_y`
+pub fn reaches_layer(anchor_y: i32, chunk_y: isize) -> bool {
+    anchor_heights(chunk_y).contains(&anchor_y)
+}
+
+/// Write the parts of trees that fall into a chunk
+///
+/// `anchors` may contain trees from anywhere around the chunk (see anchor_columns);
+/// they are sorted first, so overlapping trees resolve the same way in every chunk.
+pub fn place_trees(chunk: &mut Chunk, chunk_coord: &ChunkCoord, anchors: &mut [TreeAnchor], seed: u32) {
+    let size = CHUNK_SIZE as i32;
+    let min = (chunk_coord.0 as i32 * size, chunk_coord.1 as i32 * size, chunk_coord.2 as i32 * size);
+    anchors.sort_by_key(|anchor| (anchor.pos.2, anchor.pos.0, anchor.pos.1));
+
+    for anchor in anchors.iter() {
+        let (ax, ay, az) = anchor.pos;
+        let outside = |a: i32, min: i32| a < min - TREE_REACH || a >= min + size + TREE_REACH;
+        if outside(ax, min.0) || outside(az, min.2) || !reaches_layer(ay, chunk_coord.1) {
+            continue;
+        }
+
+        grow_tree(anchor, seed, &mut |x, y, z, block, part| {
+            if (x - ax).abs() > TREE_REACH || (z - az).abs() > TREE_REACH {
+                return; // keep every tree inside the area its neighbors look at
+            }
+            let (lx, ly, lz) = (x - min.0, y - min.1, z - min.2);
+            if lx < 0 || ly < 0 || lz < 0 || lx >= size || ly >= size || lz >= size {
+                return;
+            }
+            let coord = BlockCoord(lx as usize, ly as usize, lz as usize);
+            if part.replaces(chunk.get_block(&coord)) {
+                chunk.set_block(&coord, block, true);
+            }
+        });
+    }
+}
+
+/// Generate all blocks of a tree in world coordinates
+///
+/// Blocks may be emitted more than once (e.g. leaves where a branch passes);
+/// the writer decides with Part::replaces what survives.
+fn grow_tree(anchor: &TreeAnchor, seed: u32, put: &mut dyn FnMut(i32, i32, i32, Block, Part)) {
+    let species = species(anchor.tree_type);
+    let (ax, ay, az) = anchor.pos;
+    let mut rng = TreeRng::new(ax, az, seed);
+    let height = anchor.height.clamp(3, TREE_MAX_HEIGHT - 6) as f32;
+    let width = species.trunk_width;
+    // 2x2 trunks are centered between their four columns
+    let center = (ax as f32 + (width - 1) as f32 * 0.5, az as f32 + (width - 1) as f32 * 0.5);
+
+    // Trunk, bending towards its lean direction
+    let lean_angle = rng.range(0.0, TAU);
+    let lean = species.lean * height;
+    let trunk_at = |t: f32| {
+        let shift = lean * t * t;
+        (center.0 + lean_angle.cos() * shift, ay as f32 + t * height, center.1 + lean_angle.sin() * shift)
+    };
+    for ty in 0..height as i32 {
+        let (cx, _, cz) = trunk_at(ty as f32 / height);
+        let (x0, z0) = ((cx - (width - 1) as f32 * 0.5).round() as i32, (cz - (width - 1) as f32 * 0.5).round() as i32);
+        for dx in 0..width {
+            for dz in 0..width {
+                put(x0 + dx, ay + ty, z0 + dz, species.wood, Part::Wood);
+            }
+        }
+    }
+
+    // Roots: spread out from the trunk foot and dive into the ground
+    for i in 0..species.roots {
+        let angle = i as f32 * TAU / species.roots as f32 + rng.range(-0.4, 0.4);
+        let length = rng.range(2.0, 3.0) + species.root_height as f32;
+        let start = (center.0, ay as f32 + species.root_height as f32, center.1);
+        let end = (
+            center.0 + angle.cos() * (length + width as f32 * 0.5),
+            ay as f32 - rng.range(1.0, TREE_MAX_DEPTH as f32),
+            center.1 + angle.sin() * (length + width as f32 * 0.5),
+        );
+        line(start, end, &mut |x, y, z| put(x, y, z, species.wood, Part::Root));
+    }
+
+    // Branches with leaves at their tips
+    let mut tips = vec![(trunk_at(1.0), species.leaf_radius)];
+    let branches = rng.count(species.branches);
+    let first_angle = rng.range(0.0, TAU);
+    for i in 0..branches {
+        // golden angle spacing keeps branches spread around the trunk
+        let angle = first_angle + i as f32 * 2.4 + rng.range(-0.3, 0.3);
+        let start = trunk_at(rng.range(species.branch_start, 0.95));
+        let length = species.branch_length * height * rng.range(0.7, 1.0);
+        let end = branch(start, angle, length, species.branch_rise, species.wood, put);
+
+        if species.forks {
+            for side in [-0.7, 0.7] {
+                let fork = branch(end, angle + side, length * 0.4, species.branch_rise, species.wood, put);
+                tips.push((fork, species.leaf_radius * 0.8));
+            }
+        } else {
+            tips.push((end, species.leaf_radius * 0.8));
+        }
+    }
+
+    // Canopy
+    let top = tips[0].0;
+    match species.canopy {
+        Canopy::Blob => {
+            for &(tip, radius) in &tips {
+                leaf_blob(tip, radius, radius * species.leaf_stretch, species.leaves, put);
+            }
+        }
+        Canopy::Cone => {
+            // radius shrinks towards the top, every second layer is pulled in for tiers
+            let bottom = ay as f32 + height * 0.3;
+            let peak = top.1 + 2.0;
+            let radius = (species.leaf_radius * height / 10.0).clamp(2.0, 6.0);
+            for y in bottom as i32..=peak as i32 {
+                let t = (y as f32 - bottom) / (peak - bottom);
+                let r = radius * (1.0 - t) + 0.5 - if y % 2 == 0 { 0.8 } else { 0.0 };
+                disk((top.0, y as f32, top.2), r.max(0.5), species.leaves, put);
+            }
+            put(top.0.round() as i32, peak as i32 + 1, top.2.round() as i32, species.leaves, Part::Leaves);
+        }
+        Canopy::Flat => {
+            for &(tip, radius) in &tips {
+                disk(tip, radius, species.leaves, put);
+                disk((tip.0, tip.1 + 1.0, tip.2), radius - 1.0, species.leaves, put);
+            }
+        }
+    }
+}
+
+/// Draw a branch leaving `start` at a horizontal angle, returns its tip
+fn branch(
+    start: (f32, f32, f32),
+    angle: f32,
+    length: f32,
+    rise: f32,
+    wood: Block,
+    put: &mut dyn FnMut(i32, i32, i32, Block, Part),
+) -> (f32, f32, f32) {
+    let end = (start.0 + angle.cos() * length, start.1 + length * rise, start.2 + angle.sin() * length);
+    line(start, end, &mut |x, y, z| put(x, y, z, wood, Part::Wood));
+    end
+}
+
+/// Visit the blocks along a line (steps of half a block, no gaps)
+fn line(from: (f32, f32, f32), to: (f32, f32, f32), visit: &mut dyn FnMut(i32, i32, i32)) {
+    let delta = (to.0 - from.0, to.1 - from.1, to.2 - from.2);
+    let steps = (delta.0.abs().max(delta.1.abs()).max(delta.2.abs()) * 2.0).ceil().max(1.0) as i32;
+    for i in 0..=steps {
+        let t = i as f32 / steps as f32;
+        visit(
+            (from.0 + delta.0 * t).round() as i32,
+            (from.1 + delta.1 * t).round() as i32,
+            (from.2 + delta.2 * t).round() as i32,
+        );
+    }
+}
+
+/// Leaf ellipsoid with a ragged outer shell
+fn leaf_blob(center: (f32, f32, f32), radius: f32, vertical: f32, leaves: Block, put: &mut dyn FnMut(i32, i32, i32, Block, Part)) {
+    let (cx, cy, cz) = (center.0.round() as i32, center.1.round() as i32, center.2.round() as i32);
+    let (r, v) = (radius.ceil() as i32, vertical.ceil() as i32);
+    for dy in -v..=v {
+        for dz in -r..=r {
+            for dx in -r..=r {
+                let d = (dx * dx + dz * dz) as f32 / (radius * radius) + (dy * dy) as f32 / (vertical * vertical);
+                // the outer shell is thinned out by a position hash, the same in every chunk
+                if d > 1.0 || (d > 0.6 && shell_hash(cx + dx, cy + dy, cz + dz) < 0.3) {
+                    continue;
+                }
+                put(cx + dx, cy + dy, cz + dz, leaves, Part::Leaves);
+            }
+        }
+    }
+}
+
+/// Horizontal leaf disk
+fn disk(center: (f32, f32, f32), radius: f32, leaves: Block, put: &mut dyn FnMut(i32, i32, i32, Block, Part)) {
+    let (cx, cy, cz) = (center.0.round() as i32, center.1.round() as i32, center.2.round() as i32);
+    let r = radius.ceil() as i32;
+    for dz in -r..=r {
+        for dx in -r..=r {
+            if (dx * dx + dz * dz) as f32 <= radius * radius + 0.5 {
+                put(cx + dx, cy, cz + dz, leaves, Part::Leaves);
+            }
+        }
+    }
+}
+
+/// Position hash in [0, 1) for ragged leaf edges
+fn shell_hash(x: i32, y: i32, z: i32) -> f32 {
+    let h = (x as u32).wrapping_mul(0x8DA6_B343) ^ (y as u32).wrapping_mul(0xD816_3841) ^ (z as u32).wrapping_mul(0xCB1A_B31F);
+    let h = (h ^ (h >> 15)).wrapping_mul(0x2C1B_3C6D);
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_trees_cross_chunks_seamlessly() {
+        // giant jungle tree next to a chunk corner
+        let anchor = TreeAnchor { pos: (15, 4, 14), tree_type: TreeType::Jungle, height: 28 };
+
+        // the whole tree with the same precedence rules as in a chunk
+        let mut expected: HashMap<(i32, i32, i32), Block> = HashMap::new();
+        grow_tree(&anchor, 7, &mut |x, y, z, block, part| {
+            if (x - 15).abs() > TREE_REACH || (z - 14).abs() > TREE_REACH {
+                return;
+            }
+            let current = expected.get(&(x, y, z)).copied().unwrap_or(Block::Empty);
+            if part.replaces(current) {
+                expected.insert((x, y, z), block);
+            }
+        });
+
+        let mut found = 0;
+        let mut chunks = 0;
+        for cx in -1..=1 {
+            for cy in 0..=3 {
+                for cz in -1..=1 {
+                    let coord = ChunkCoord(cx, cy, cz);
+                    let mut chunk = Chunk::new_empty();
+                    place_trees(&mut chunk, &coord, &mut [anchor], 7);
+                    let min = (cx as i32 * 16, cy as i32 * 16, cz as i32 * 16);
+                    let mut in_chunk = 0;
+                    for (&(x, y, z), &block) in &expected {
+                        let (lx, ly, lz) = (x - min.0, y - min.1, z - min.2);
+                        if (0..16).contains(&lx) && (0..16).contains(&ly) && (0..16).contains(&lz) {
+                            let coord = BlockCoord(lx as usize, ly as usize, lz as usize);
+                            assert_eq!(chunk.get_block(&coord), block, "block {x} {y} {z}");
+                            in_chunk += 1;
+                        }
+                    }
+                    let placed = (0..16 * 16 * 16)
+                        .filter(|i| !chunk.get_block(&BlockCoord(i % 16, i / 256, i / 16 % 16)).is_empty())
+                        .count();
+                    assert_eq!(in_chunk, placed, "stray blocks in {coord:?}");
+                    found += in_chunk;
+                    chunks += (in_chunk > 0) as usize;
+                }
+            }
+        }
+        assert_eq!(found, expected.len());
+        assert!(chunks >= 6, "giant tree should span several chunks, got {chunks}");
+    }
+}
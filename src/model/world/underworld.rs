@@ -0,0 +1,179 @@
+// ============================================================================
+// UNDERWORLD
+// ============================================================================
+//
+// A closed cavern world between a bedrock floor and a bedrock roof:
+//   → floor and ceiling heights come from 2D noise, so the open space between
+//     them is a single huge cave
+//   → the floor takes its blocks from the underworld biomes (biomes/underworld/,
+//     basalt and blackstone), the ceiling is blackstone
+//   → 3D noise stretched along y grows basalt pillars and overhangs
+//   → every open position at or below LAVA_LEVEL is a lava sea
+//   → the top `bedrock_layers` layers mirror the ragged bedrock floor
+//
+// The terrain config provides the seed, the climate noise for the biomes and the
+// vertical bounds (0..128 by default, see UnderworldGenerator::default_config).
+// ============================================================================
+
+use std::rc::Rc;
+
+use super::biome::BiomeWeights;
+use super::biome_def::BiomeRegistry;
+use super::block::{Block, Tint};
+use super::chunk::{Chunk, CHUNK_SIZE};
+use super::terrain::{ColumnSample, TerrainConfig, VoxelDensityGenerator};
+use crate::utils::{BlockCoord, ChunkCoord};
+
+/// Underworld biome definitions, in id order (the first one is the fallback)
+pub const UNDERWORLD_BIOMES: &[&str] = &[
+    include_str!("biomes/underworld/blackstone_wastes.toml"),
+    include_str!("biomes/underworld/basalt_deltas.toml"),
+];
+
+/// Highest world y of the lava sea
+pub const LAVA_LEVEL: isize = 30;
+
+/// Average floor height and how far the floor noise moves it up or down
+const FLOOR_HEIGHT: f32 = 30.0;
+const FLOOR_RELIEF: f32 = 60.0;
+
+/// Average distance of the ceiling below the build limit, and its relief
+const CEILING_DEPTH: f32 = 24.0;
+const CEILING_RELIEF: f32 = 28.0;
+
+/// Pillar noise above this value is solid basalt
+const PILLAR_THRESHOLD: f32 = 0.1;
+
+pub struct UnderworldGenerator {
+    /// Seeded noise, climate and underworld biomes
+    pub terrain: VoxelDensityGenerator,
+}
+
+impl UnderworldGenerator {
+    pub fn new(seed: u32) -> Self {
+        let biomes = BiomeRegistry::from_toml(UNDERWORLD_BIOMES).expect("underworld biome definitions are valid");
+        let config = TerrainConfig { seed, ..Self::default_config() };
+        Self { terrain: VoxelDensityGenerator::with_biomes(config, Rc::new(biomes)) }
+    }
+
+    /// Terrain config of the underworld: a 128 block tall world without oceans
+    pub fn default_config() -> TerrainConfig {
+        TerrainConfig {
+            min_y: 0,
+            max_y: 128,
+            bedrock_layers: 4,
+            // every column is land, there is no sea to carve a coast for
+            ocean_threshold: -10.0,
+            deep_ocean_threshold: -10.0,
+            ..TerrainConfig::default()
+        }
+    }
+
+    /// First world y above the cavern floor
+    fn floor_height(&self, wx: f32, wz: f32) -> isize {
+        // value noise is centered around -0.5
+        let noise = self.terrain.fbm(wx, wz, 0.015, 0.5, 4) + 0.5;
+        (FLOOR_HEIGHT + noise * FLOOR_RELIEF) as isize
+    }
+
+    /// Lowest world y of the ceiling rock
+    fn ceiling_height(&self, wx: f32, wz: f32) -> isize {
+        let noise = self.terrain.fbm(wx - 4000.0, wz + 4000.0, 0.02, 0.5, 3) + 0.5;
+        let max_y = self.terrain.config.max_y as f32;
+        (max_y - CEILING_DEPTH + noise * CEILING_RELIEF) as isize
+    }
+
+    fn is_pillar(&self, wx: f32, y: f32, wz: f32) -> bool {
+        // squashed y turns the noise blobs into tall columns
+        self.terrain.fbm_3d(wx, y * 0.3, wz, 0.045, 0.5, 3) + 0.5 > PILLAR_THRESHOLD
+    }
+
+    /// Top of a column without pillars: the floor, or the lava sea covering it
+    pub fn sample_column(&self, wx: f32, wz: f32) -> ColumnSample {
+        let floor = self.floor_height(wx, wz);
+        let biome = self.terrain.get_biome_type(wx, wz, floor as f32);
+        let (height, surface_block) = if floor <= LAVA_LEVEL {
+            (LAVA_LEVEL + 1, Block::Lava)
+        } else {
+            (floor, self.terrain.get_surface_block_for_biome(wx, wz, (floor - 1) as f32, biome))
+        };
+        ColumnSample { weights: BiomeWeights::single(biome), biome, height, surface_block, tint: Tint::NEUTRAL, erosion: 0.0 }
+    }
+
+    pub fn populate_chunk(&self, chunk: &mut Chunk, chunk_coord: &ChunkCoord) {
+        let world_coord = chunk_coord.to_world_coord();
+        let bounds = self.terrain.config.bounds();
+
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let wx = (world_coord.0 + x) as f32;
+                let wz = (world_coord.2 + z) as f32;
+                let floor = self.floor_height(wx, wz);
+                let ceiling = self.ceiling_height(wx, wz);
+                let biome = self.terrain.get_biome_type(wx, wz, floor as f32);
+
+                for y in 0..CHUNK_SIZE {
+                    let world_y = world_coord.1 + y;
+                    let block = if world_y >= ceiling {
+                        // the roof is the bedrock floor upside down
+                        let mirrored = bounds.min_y + bounds.max_y - 1 - world_y;
+                        if bounds.is_bedrock(world_coord.0 + x, mirrored, world_coord.2 + z) {
+                            Block::Bedrock
+                        } else {
+                            Block::BlackStone
+                        }
+                    } else if world_y == floor - 1 {
+                        self.terrain.get_surface_block_for_biome(wx, wz, world_y as f32, biome)
+                    } else if world_y < floor {
+                        self.terrain.get_subsurface_block(wx, wz, world_y as f32, biome)
+                    } else if self.is_pillar(wx, world_y as f32, wz) {
+                        Block::Basalt
+                    } else if world_y <= LAVA_LEVEL {
+                        Block::Lava
+                    } else {
+                        Block::Empty
+                    };
+                    chunk.set_block(&BlockCoord(x as usize, y as usize, z as usize), block, false);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_underworld_is_a_closed_cavern() {
+        let generator = UnderworldGenerator::new(7);
+        let bounds = generator.terrain.config.bounds();
+        let (mut open, mut rock) = (0, 0);
+
+        for cx in 0..4 {
+            let mut column = Vec::new();
+            for cy in 0..(bounds.max_y / CHUNK_SIZE) {
+                let mut chunk = Chunk::new_empty();
+                generator.populate_chunk(&mut chunk, &ChunkCoord(cx, cy, 0));
+                for y in 0..CHUNK_SIZE as usize {
+                    column.push(chunk.get_block(&BlockCoord(5, y, 9)));
+                    for i in 0..CHUNK_SIZE as usize {
+                        match chunk.get_block(&BlockCoord(i, y, 3)) {
+                            Block::Empty => open += 1,
+                            Block::Basalt | Block::BlackStone => rock += 1,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            // bedrock at both ends of the world, nothing but the underworld blocks in between
+            assert_eq!(column[0], Block::Bedrock);
+            assert_eq!(column[bounds.max_y as usize - 1], Block::Bedrock);
+            assert!(column.iter().all(|b| matches!(
+                b,
+                Block::Bedrock | Block::Basalt | Block::BlackStone | Block::Gravel | Block::Lava | Block::Empty
+            )));
+        }
+        assert!(open > 0 && rock > 0, "open {open}, rock {rock}");
+    }
+}
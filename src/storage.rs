@@ -0,0 +1,48 @@
+// ============================================================================
+// STORAGE: small text records that survive a page reload or a restart
+// ============================================================================
+//
+// In the browser every record is a localStorage item, natively a file
+// `saves/<key>.txt` in the working directory. Used for the block edits of
+// each dimension (see Scene::save_edits).
+// ============================================================================
+
+/// Directory the records are written to natively
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_DIR: &str = "saves";
+
+/// Text stored under `key`, None if nothing was saved yet
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    storage.get_item(key).ok()?
+}
+
+/// Store `text` under `key`, replacing what was there
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, text: &str) -> Result<(), String> {
+    let storage = web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or("no local storage")?;
+    storage.set_item(key, text).map_err(|e| format!("{key}: {e:?}"))
+}
+
+/// Text stored under `key`, None if nothing was saved yet
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)).ok()
+}
+
+/// Store `text` under `key`, replacing what was there
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, text: &str) -> Result<(), String> {
+    let path = path(key);
+    std::fs::create_dir_all(SAVE_DIR)
+        .and_then(|_| std::fs::write(&path, text))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(key: &str) -> std::path::PathBuf {
+    std::path::Path::new(SAVE_DIR).join(format!("{key}.txt"))
+}
@@ -4,9 +4,13 @@ use std::cell::RefCell;
 use crate::model::Camera;
 use crate::model::CHUNK_SIZE;
 use crate::controller::GameState;
+use crate::controller::camera_controller::MAX_HEALTH;
 use crate::controller::InputState;
 use crate::model::Scene;
 use crate::model::Block;
+use crate::model::FogMode;
+use crate::model::world::terrain::{TerrainConfig, TERRAIN_PRESETS};
+use crate::view::shadows::{ShadowSettings, MAX_CASCADES};
 
 /// Build the complete UI and return egui output
 pub fn build_ui(
@@ -30,8 +34,12 @@ pub fn build_ui(
     egui_ctx.run(raw_input, |ctx| {
         draw_crosshair(ctx);
         draw_debug_window(ctx, cam, game_state, core, dt);
-        draw_settings_window(ctx, cam, canvas_width);
+        draw_settings_window(ctx, cam, input_state, canvas_width);
+        draw_new_world_window(ctx, input_state);
+        draw_terrain_window(ctx, core, canvas_width);
+        draw_time_window(ctx, game_state, canvas_width);
         draw_hotbar(ctx, input_state, canvas_height);
+        draw_health(ctx, game_state);
     })
 }
 
@@ -73,8 +81,13 @@ fn draw_debug_window(ctx: &Context, cam: &Rc<RefCell<Camera>>, game_state: &Rc<R
             );
             ui.label(egui::RichText::new(format!("Pos: x: {:.0} y: {:.0} z: {:.0}", player_pos.x, player_pos.y, player_pos.z)).small());
             ui.label(egui::RichText::new(format!("Chunk: x: {} y: {} z: {}", chunk_x, chunk_y, chunk_z)).small());
+            ui.label(egui::RichText::new(format!("Dimension: {}", core.borrow().dimension.name)).small());
             ui.label(egui::RichText::new(format!("Yaw: {:.2} Pitch: {:.2}", cam.borrow().yaw.to_degrees(), cam.borrow().pitch.to_degrees())).small());
             ui.label(egui::RichText::new(format!("Chunks: 64x64x64 (fixed)")).small());
+            let (used, capacity) = core.borrow().arena().usage();
+            ui.label(egui::RichText::new(format!("Mesh arena: {:.1} / {:.0} MB", used as f64 / 1e6, capacity as f64 / 1e6)).small());
+            let stats = core.borrow().cull_stats;
+            ui.label(egui::RichText::new(format!("Chunks drawn: {}  occluded: {}  off-screen: {}", stats.drawn, stats.occluded, stats.outside_frustum)).small());
             ui.separator();
             ui.label(egui::RichText::new("Controls:").small());
             ui.label(egui::RichText::new("WASD - Move").small());
@@ -86,7 +99,7 @@ fn draw_debug_window(ctx: &Context, cam: &Rc<RefCell<Camera>>, game_state: &Rc<R
         });
 }
 
-fn draw_settings_window(ctx: &Context, cam: &Rc<RefCell<Camera>>, canvas_width: u32) {
+fn draw_settings_window(ctx: &Context, cam: &Rc<RefCell<Camera>>, input_state: &Rc<RefCell<InputState>>, canvas_width: u32) {
     egui::Window::new("Settings")
         .default_pos([canvas_width as f32 - 140.0, 8.0])
         .default_size([130.0, 100.0])
@@ -96,7 +109,207 @@ fn draw_settings_window(ctx: &Context, cam: &Rc<RefCell<Camera>>, canvas_width:
             if ui.add(egui::Slider::new(&mut fov_deg, 30.0..=120.0).step_by(5.0)).changed() {
                 cam.borrow_mut().fov_y = fov_deg.to_radians();
             }
+
+            ui.separator();
+            {
+                let fog = &mut input_state.borrow_mut().fog;
+                egui::ComboBox::from_id_salt("fog_mode")
+                    .selected_text(format!("Fog: {}", fog.name()))
+                    .show_ui(ui, |ui| {
+                        for mode in FogMode::ALL {
+                            ui.selectable_value(fog, mode, mode.name());
+                        }
+                    });
+            }
+
+            ui.separator();
+            let shadows = &mut input_state.borrow_mut().shadows;
+            ui.checkbox(&mut shadows.enabled, egui::RichText::new("Shadows").small());
+            ui.add_enabled_ui(shadows.enabled, |ui| {
+                ui.label(egui::RichText::new("Cascades").small());
+                ui.add(egui::Slider::new(&mut shadows.cascades, 1..=MAX_CASCADES));
+                ui.label(egui::RichText::new("Distance").small());
+                ui.add(egui::Slider::new(&mut shadows.distance, 64.0..=512.0).step_by(32.0));
+                egui::ComboBox::from_id_salt("shadow_resolution")
+                    .selected_text(format!("{} px", shadows.resolution))
+                    .show_ui(ui, |ui| {
+                        for resolution in ShadowSettings::RESOLUTIONS {
+                            ui.selectable_value(&mut shadows.resolution, resolution, format!("{resolution} px"));
+                        }
+                    });
+            });
+        });
+}
+
+fn draw_time_window(ctx: &Context, game_state: &Rc<RefCell<GameState>>, canvas_width: u32) {
+    egui::Window::new("Time")
+        .default_pos([canvas_width as f32 - 280.0, 80.0])
+        .default_open(false)
+        .show(ctx, |ui| {
+            let clock = &mut game_state.borrow_mut().clock;
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(clock.clock_text()).monospace());
+                let label = if clock.paused { "Resume" } else { "Pause" };
+                if ui.button(label).clicked() {
+                    clock.paused = !clock.paused;
+                }
+            });
+
+            // hours 0..24 on the slider, the clock keeps 0..1
+            let mut hours = clock.time * 24.0;
+            if ui.add(egui::Slider::new(&mut hours, 0.0..=24.0).text("h")).changed() {
+                clock.time = (hours / 24.0).rem_euclid(1.0);
+            }
+            ui.horizontal(|ui| {
+                for (name, time) in [("Dawn", 0.25), ("Noon", 0.5), ("Dusk", 0.75), ("Night", 0.0)] {
+                    if ui.small_button(name).clicked() {
+                        clock.time = time;
+                    }
+                }
+            });
+
+            let mut minutes = clock.day_length / 60.0;
+            ui.label(egui::RichText::new("Day length (minutes)").small());
+            if ui.add(egui::DragValue::new(&mut minutes).speed(0.5).range(1.0..=120.0)).changed() {
+                clock.day_length = minutes * 60.0;
+            }
+        });
+}
+
+/// New World window state kept in egui memory between frames
+#[derive(Clone)]
+struct NewWorldPanel {
+    preset: &'static str,
+    seed: u32,
+}
+
+/// Start a new world from a preset and seed (the world shown until then is the
+/// default preset, or the one picked in the page URL)
+fn draw_new_world_window(ctx: &Context, input_state: &Rc<RefCell<InputState>>) {
+    let id = egui::Id::new("new_world_panel");
+    let mut panel = ctx.data_mut(|d| d.get_temp::<NewWorldPanel>(id)).unwrap_or(NewWorldPanel { preset: "default", seed: 0 });
+
+    egui::Window::new("New World")
+        .default_pos([10.0, 320.0])
+        .default_open(true)
+        .show(ctx, |ui| {
+            egui::Grid::new("new_world_fields").num_columns(2).show(ui, |ui| {
+                ui.label("Preset");
+                egui::ComboBox::from_id_salt("new_world_preset")
+                    .selected_text(panel.preset)
+                    .show_ui(ui, |ui| {
+                        for (name, _) in TERRAIN_PRESETS {
+                            ui.selectable_value(&mut panel.preset, name, *name);
+                        }
+                    });
+                ui.end_row();
+                ui.label("Seed");
+                ui.add(egui::DragValue::new(&mut panel.seed));
+                ui.end_row();
+            });
+            if ui.button("Create world").clicked() {
+                let preset = TerrainConfig::preset(panel.preset).unwrap_or_default();
+                input_state.borrow_mut().new_world = Some(TerrainConfig { seed: panel.seed, ..preset });
+            }
         });
+
+    ctx.data_mut(|d| d.insert_temp(id, panel));
+}
+
+/// Terrain panel state kept in egui memory between frames
+#[derive(Clone)]
+struct TerrainPanel {
+    draft: TerrainConfig,
+    /// Config of the scene the draft started from, a new world resets the draft
+    base: TerrainConfig,
+    preset: &'static str,
+    live: bool,
+}
+
+fn draw_terrain_window(ctx: &Context, core: &Rc<RefCell<Scene>>, canvas_width: u32) {
+    // one panel per dimension, so a draft never lands in another world
+    let id = egui::Id::new("terrain_panel").with(core.borrow().dimension.name);
+    let current = *core.borrow().terrain_config();
+    let mut panel = ctx.data_mut(|d| d.get_temp::<TerrainPanel>(id)).unwrap_or(TerrainPanel {
+        draft: current,
+        base: current,
+        preset: "default",
+        live: false,
+    });
+    if panel.base != current {
+        panel.draft = current;
+        panel.base = current;
+    }
+
+    egui::Window::new("Terrain")
+        .default_pos([canvas_width as f32 - 280.0, 120.0])
+        .default_open(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("terrain_preset")
+                    .selected_text(panel.preset)
+                    .show_ui(ui, |ui| {
+                        for (name, _) in TERRAIN_PRESETS {
+                            if ui.selectable_label(panel.preset == *name, *name).clicked() {
+                                panel.preset = name;
+                                panel.draft = TerrainConfig::preset(name).unwrap_or_default();
+                            }
+                        }
+                    });
+                ui.checkbox(&mut panel.live, "Live");
+            });
+
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                egui::Grid::new("terrain_fields").num_columns(2).show(ui, |ui| {
+                    for (name, value) in panel.draft.fields() {
+                        ui.label(egui::RichText::new(&name).small());
+                        let edited = match value {
+                            toml::Value::Boolean(mut on) => ui.checkbox(&mut on, "").changed().then_some(toml::Value::Boolean(on)),
+                            toml::Value::Integer(mut v) => {
+                                ui.add(egui::DragValue::new(&mut v).speed(0.1)).changed().then_some(toml::Value::Integer(v))
+                            }
+                            toml::Value::Float(mut v) => {
+                                let speed = (v.abs() * 0.01).max(0.001);
+                                ui.add(egui::DragValue::new(&mut v).speed(speed)).changed().then_some(toml::Value::Float(v))
+                            }
+                            other => {
+                                ui.label(other.to_string());
+                                None
+                            }
+                        };
+                        if let Some(value) = edited {
+                            // out of range values (a negative seed) are dropped
+                            let _ = panel.draft.set_value(&name, value);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let dirty = panel.draft != *core.borrow().terrain_config();
+                if ui.add_enabled(dirty && !panel.live, egui::Button::new("Regenerate")).clicked() {
+                    core.borrow_mut().set_terrain_config(panel.draft);
+                    panel.base = panel.draft;
+                }
+                if ui.button("Copy TOML").clicked() {
+                    ui.ctx().copy_text(panel.draft.to_toml());
+                }
+                if ui.button("Save TOML").clicked() {
+                    if let Err(e) = save_file(&format!("{}.toml", panel.preset), &panel.draft.to_toml()) {
+                        tracing::warn!("can't save terrain preset: {e}");
+                    }
+                }
+            });
+        });
+
+    // live mode regenerates once a drag is released, not on every frame of it
+    if panel.live && !ctx.is_using_pointer() && panel.draft != *core.borrow().terrain_config() {
+        core.borrow_mut().set_terrain_config(panel.draft);
+        panel.base = panel.draft;
+    }
+
+    ctx.data_mut(|d| d.insert_temp(id, panel));
 }
 
 fn draw_hotbar(ctx: &Context, input_state: &Rc<RefCell<InputState>>, canvas_height: u32) {
@@ -116,6 +329,12 @@ fn draw_hotbar(ctx: &Context, input_state: &Rc<RefCell<InputState>>, canvas_heig
                     (Block::Wood, "9"),
                     (Block::Water, "0"),
                     (Block::Cloud, "-"),
+                    (Block::Portal, "="),
+                    (Block::SkyPortal, "]"),
+                    (Block::Torch, "t"),
+                    (Block::Lantern, "y"),
+                    (Block::Glowstone, "u"),
+                    (Block::Lava, "i"),
                 ];
                 let current = input_state.borrow().selected_block;
                 for (block, key) in blocks.iter() {
@@ -146,3 +365,51 @@ fn draw_hotbar(ctx: &Context, input_state: &Rc<RefCell<InputState>>, canvas_heig
             });
         });
 }
+
+/// Health bar above the hotbar, while playing
+fn draw_health(ctx: &Context, game_state: &Rc<RefCell<GameState>>) {
+    let game = game_state.borrow();
+    if !game.player_active {
+        return;
+    }
+    egui::Area::new(egui::Id::new("health"))
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -60.0])
+        .show(ctx, |ui| {
+            ui.add(
+                egui::ProgressBar::new(game.health / MAX_HEALTH)
+                    .desired_width(200.0)
+                    .fill(egui::Color32::from_rgb(200, 40, 40))
+                    .text(format!("{:.0} / {:.0}", game.health.ceil(), MAX_HEALTH)),
+            );
+        });
+}
+
+/// Save a text file: a download in the browser, a file in the working directory natively
+#[cfg(target_arch = "wasm32")]
+fn save_file(name: &str, text: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let js_error = |e: wasm_bindgen::JsValue| format!("{e:?}");
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("application/toml");
+    let parts = js_sys::Array::of1(&text.into());
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options).map_err(js_error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+    let document = web_sys::window().and_then(|window| window.document()).ok_or("no document")?;
+    let link: web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(|_| "not an anchor element")?;
+    link.set_href(&url);
+    link.set_download(name);
+    link.click();
+    web_sys::Url::revoke_object_url(&url).map_err(js_error)
+}
+
+/// Save a text file: a download in the browser, a file in the working directory natively
+#[cfg(not(target_arch = "wasm32"))]
+fn save_file(name: &str, text: &str) -> Result<(), String> {
+    std::fs::write(name, text).map_err(|e| format!("{name}: {e}"))
+}
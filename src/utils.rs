@@ -1,4 +1,6 @@
 use crate::model::{CHUNK_SIZE, Chunk};
+use crate::model::light::Light;
+use crate::model::world::{Block, Tint};
 use wgpu::util::DeviceExt;
 use bytemuck::{NoUninit};
 
@@ -11,6 +13,64 @@ pub struct Vertex {
     pub uv: [f32; 2],
 }
 
+/// Chunk mesh vertex packed into three words (12 bytes instead of 48 for `Vertex`)
+///
+/// Positions are relative to the chunk origin, which is passed per draw as
+/// instance data, so a chunk mesh does not depend on where the chunk lies.
+///
+/// `geometry` bits: x 0..5, y 5..10, z 10..15 (0..=CHUNK_SIZE), face direction
+/// 15..18, u 18..23 and v 23..28 (quad corner in blocks, for tiling textures).
+/// `material` bits: block id 0..8 (palette index with the face), biome tint
+/// r/g/b 8..32.
+/// `light` bits: sky light 0..4, block light 4..8 and the id of its source block
+/// 8..16 (of the cell the face looks into, see model/light.rs).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, NoUninit)]
+pub struct ChunkVertex {
+    pub geometry: u32,
+    pub material: u32,
+    pub light: u32,
+}
+
+impl ChunkVertex {
+    pub fn new(pos: [u8; 3], face_dir: u8, uv: [u8; 2], block: Block, tint: Tint, light: Light) -> Self {
+        debug_assert!(pos.iter().chain(&uv).all(|&c| c as isize <= CHUNK_SIZE) && face_dir < 6);
+        let geometry = pos[0] as u32
+            | (pos[1] as u32) << 5
+            | (pos[2] as u32) << 10
+            | (face_dir as u32) << 15
+            | (uv[0] as u32) << 18
+            | (uv[1] as u32) << 23;
+        let material = block as u32 | (tint.0[0] as u32) << 8 | (tint.0[1] as u32) << 16 | (tint.0[2] as u32) << 24;
+        Self { geometry, material, light: light.0 as u32 }
+    }
+
+    /// Position inside the chunk
+    pub fn pos(&self) -> [u8; 3] {
+        [(self.geometry & 31) as u8, (self.geometry >> 5 & 31) as u8, (self.geometry >> 10 & 31) as u8]
+    }
+
+    pub fn face_dir(&self) -> u8 {
+        (self.geometry >> 15 & 7) as u8
+    }
+
+    pub fn uv(&self) -> [u8; 2] {
+        [(self.geometry >> 18 & 31) as u8, (self.geometry >> 23 & 31) as u8]
+    }
+
+    pub fn block(&self) -> Block {
+        Block::from_u8(self.material as u8)
+    }
+
+    pub fn tint(&self) -> Tint {
+        Tint([(self.material >> 8) as u8, (self.material >> 16) as u8, (self.material >> 24) as u8])
+    }
+
+    pub fn light(&self) -> Light {
+        Light(self.light as u16)
+    }
+}
+
 pub struct MeshBuffer {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
@@ -18,12 +78,15 @@ pub struct MeshBuffer {
 }
 
 #[derive(Debug, Clone)]
-pub struct Mesh {
-    pub vertices: Vec<Vertex>,
+pub struct Mesh<V = Vertex> {
+    pub vertices: Vec<V>,
     pub indices: Vec<u32>,
 }
 
-impl Mesh {
+/// Chunk-local mesh of packed vertices
+pub type ChunkMesh = Mesh<ChunkVertex>;
+
+impl<V: NoUninit> Mesh<V> {
     pub fn empty() -> Self {
         Self {
             vertices: Vec::new(),
@@ -35,17 +98,6 @@ impl Mesh {
         self.vertices.is_empty() && self.indices.is_empty()
     }
 
-    // scale and offset vertices 
-    pub fn offset_vertices_by(&mut self, coord: &ChunkCoord) {
-
-        let coord = coord.to_world_coord();
-        for v in self.vertices.iter_mut() {
-            v.pos[0] = coord.0 as f32 + v.pos[0];
-            v.pos[1] = coord.1 as f32 + v.pos[1];
-            v.pos[2] = coord.2 as f32 + v.pos[2];
-        }
-    }
-
     pub fn upload(&self, device: &wgpu::Device) -> MeshBuffer {
 
         let vertices = bytemuck::cast_slice(&self.vertices);
@@ -72,6 +124,7 @@ impl Mesh {
 
 
 
+
 /// Create outline mesh for block targeting (unit cube at origin)
 pub fn create_outline_mesh() -> Mesh {
     let verts = vec![
@@ -137,18 +190,18 @@ impl WorldCoord {
     /// Convert to chunk index
     pub fn to_chunk_coord(&self) -> ChunkCoord {
         ChunkCoord(
-            self.0 / CHUNK_SIZE as isize,
-            self.1 / CHUNK_SIZE as isize,
-            self.2 / CHUNK_SIZE as isize,
+            self.0.div_euclid(CHUNK_SIZE),
+            self.1.div_euclid(CHUNK_SIZE),
+            self.2.div_euclid(CHUNK_SIZE),
         )
     }
 
     /// Convert to chunk-local coordinates
     pub fn to_block_coord(&self) -> BlockCoord {
         BlockCoord(
-            (self.0.rem_euclid(CHUNK_SIZE as isize)) as usize,
-            (self.1.rem_euclid(CHUNK_SIZE as isize)) as usize,
-            (self.2.rem_euclid(CHUNK_SIZE as isize)) as usize,
+            (self.0.rem_euclid(CHUNK_SIZE)) as usize,
+            (self.1.rem_euclid(CHUNK_SIZE)) as usize,
+            (self.2.rem_euclid(CHUNK_SIZE)) as usize,
         )
     }
 }
@@ -162,9 +215,9 @@ impl ChunkCoord {
     /// Convert to chunk world key (the corner position in world block coordinates)
     pub fn to_world_coord(&self) -> WorldCoord {
         WorldCoord(
-            self.0 * CHUNK_SIZE as isize,
-            self.1 * CHUNK_SIZE as isize,
-            self.2 * CHUNK_SIZE as isize,
+            self.0 * CHUNK_SIZE,
+            self.1 * CHUNK_SIZE,
+            self.2 * CHUNK_SIZE,
         )
     }
 }
@@ -180,3 +233,25 @@ impl BlockCoord {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_vertex_packing() {
+        let tint = Tint([200, 128, 31]);
+        let light = Light::emitted(9, 14, Block::Torch);
+        let v = ChunkVertex::new([16, 0, 7], 5, [16, 3], Block::SkyPortal, tint, light);
+        assert_eq!(std::mem::size_of::<ChunkVertex>(), 12);
+        assert_eq!((v.pos(), v.face_dir(), v.uv()), ([16, 0, 7], 5, [16, 3]));
+        assert_eq!((v.block(), v.tint(), v.light()), (Block::SkyPortal, tint, light));
+
+        // chunk meshes stay chunk-local wherever the chunk lies
+        let mut chunk = Chunk::new_empty();
+        chunk.set_block(&BlockCoord(15, 15, 15), Block::Stone, true);
+        let mesh = chunk.get_mesh(0, None);
+        assert_eq!(mesh.vertices.len(), 24);
+        assert!(mesh.vertices.iter().all(|v| v.pos().iter().all(|&c| (15..=16).contains(&c))));
+    }
+}
@@ -3,6 +3,8 @@
 use wgpu::Device;
 use std::sync::Arc;
 
+use super::render::ChunkDrawList;
+
 /// GPU context - unified for both WASM and native
 pub struct GpuContext {
     pub device: Arc<Device>,
@@ -10,6 +12,10 @@ pub struct GpuContext {
     pub surface: wgpu::Surface<'static>,
     pub format: wgpu::TextureFormat,
     pub config: wgpu::SurfaceConfiguration,
+    /// Chunks can be drawn with one multi_draw_indexed_indirect (see ChunkDrawList)
+    pub indirect_draw: bool,
+    /// Running on WebGL2, where extra render passes are expensive
+    pub webgl: bool,
 }
 
 /// Shared GPU initialization helper
@@ -20,6 +26,8 @@ async fn init_device_and_queue(
 ) -> (Arc<Device>, Arc<wgpu::Queue>) {
     let adapter_limits = adapter.limits();
     let limits = wgpu::Limits::downlevel_defaults().using_resolution(adapter_limits);
+    // optional: lets indirect chunk draws pick their chunk origin by instance
+    let features = features | (adapter.features() & wgpu::Features::INDIRECT_FIRST_INSTANCE);
 
     let (device, queue) = adapter
         .request_device(
@@ -96,6 +104,8 @@ impl GpuContext {
 
         let (device, queue) = init_device_and_queue(&adapter, wgpu::Features::empty()).await;
         let (format, config) = configure_surface(&device, &adapter, &surface, width, height);
+        let indirect_draw = ChunkDrawList::supports_indirect(&adapter, &device);
+        let webgl = adapter.get_info().backend == wgpu::Backend::Gl;
 
         Ok(GpuContext {
             device,
@@ -103,6 +113,8 @@ impl GpuContext {
             surface,
             format,
             config,
+            indirect_draw,
+            webgl,
         })
     }
 }
@@ -135,6 +147,8 @@ impl GpuContext {
         ).await;
         
         let (format, config) = configure_surface(&device, &adapter, &surface, width, height);
+        let indirect_draw = ChunkDrawList::supports_indirect(&adapter, &device);
+        let webgl = adapter.get_info().backend == wgpu::Backend::Gl;
 
         GpuContext {
             device,
@@ -142,5 +156,7 @@ impl GpuContext {
             surface,
             format,
             config,
+            indirect_draw,
+            webgl,
         }
     }}
\ No newline at end of file
@@ -1,6 +1,10 @@
 // VIEW: Rendering and graphics
 pub mod render;
 pub mod gpu_init;
+pub mod shadows;
+pub mod sky;
 
 pub use render::{RenderState, CameraResources, PipelineResources, OutlineResources};
 pub use gpu_init::GpuContext;
+pub use shadows::{ShadowResources, ShadowSettings};
+pub use sky::SkyResources;
@@ -1,22 +1,30 @@
-use std::rc::Rc;
 
 use wgpu::*;
-use wgpu::util::DeviceExt;
-use crate::model::Chunk;
-use crate::utils::{MeshBuffer, Vertex, create_outline_mesh};
+use wgpu::util::{DeviceExt, DrawIndexedIndirectArgs};
+use crate::model::Scene;
+use crate::model::Block;
+use crate::model::mesh_arena::ArenaMesh;
+use super::shadows::ShadowResources;
+use super::sky::SkyResources;
+use crate::utils::{ChunkVertex, MeshBuffer, Vertex, create_outline_mesh};
 use glam::Vec3;
 
 // Shared graphics setup used by native and web
 pub struct CameraResources {
     pub camera_buffer: wgpu::Buffer,
     pub lighting_buffer: wgpu::Buffer,
+    /// Block face colors for packed chunk vertices, written once
+    pub palette_buffer: wgpu::Buffer,
     pub bind_group_layout: wgpu::BindGroupLayout,
     pub camera_bind_group: wgpu::BindGroup,
 }
 
 pub struct PipelineResources {
+    /// Chunks (packed vertices)
     pub pipeline: wgpu::RenderPipeline,
     pub wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    /// Far terrain (full vertices in world space)
+    pub horizon_pipeline: wgpu::RenderPipeline,
 }
 
 pub struct OutlineResources {
@@ -26,6 +34,124 @@ pub struct OutlineResources {
     pub outline_bind_group: wgpu::BindGroup,
 }
 
+/// Draw list of the chunks in the mesh arena, rebuilt every frame
+///
+/// Draw i takes its chunk origin from instance i of `origin_buffer`, so all
+/// chunks can go out in one multi_draw_indexed_indirect. Devices without
+/// indirect drawing (WebGL) issue the same draws one by one.
+pub struct ChunkDrawList {
+    pub indirect_buffer: wgpu::Buffer,
+    pub origin_buffer: wgpu::Buffer,
+    /// Draws the buffers have room for
+    capacity: u32,
+    /// Draws written for the current frame
+    pub draws: Vec<DrawIndexedIndirectArgs>,
+    /// Use multi_draw_indexed_indirect (needs indirect execution with first instance)
+    pub indirect: bool,
+}
+
+impl ChunkDrawList {
+    pub fn new(device: &wgpu::Device, indirect: bool) -> Self {
+        let capacity = 4096;
+        let (indirect_buffer, origin_buffer) = create_draw_buffers(device, capacity);
+        Self { indirect_buffer, origin_buffer, capacity, draws: Vec::new(), indirect }
+    }
+
+    /// Whether a device can draw the chunks with one indirect draw
+    pub fn supports_indirect(adapter: &wgpu::Adapter, device: &wgpu::Device) -> bool {
+        adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::INDIRECT_EXECUTION)
+            && device.features().contains(wgpu::Features::INDIRECT_FIRST_INSTANCE)
+    }
+
+    /// Collect a draw for every non-empty chunk mesh and upload the list
+    pub fn update<'a>(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, meshes: impl Iterator<Item = &'a ArenaMesh>) {
+        self.draws.clear();
+        let mut origins: Vec<[i32; 3]> = Vec::new();
+        for mesh in meshes {
+            if mesh.index_count() == 0 {
+                continue; // Skip empty meshes (and all the shared air chunks)
+            }
+            self.draws.push(DrawIndexedIndirectArgs {
+                index_count: mesh.index_count(),
+                instance_count: 1,
+                first_index: mesh.indices.start,
+                base_vertex: 0,
+                first_instance: origins.len() as u32,
+            });
+            origins.push(mesh.origin);
+        }
+
+        if self.draws.len() as u32 > self.capacity {
+            self.capacity = (self.draws.len() as u32).next_power_of_two();
+            (self.indirect_buffer, self.origin_buffer) = create_draw_buffers(device, self.capacity);
+        }
+        if !self.draws.is_empty() {
+            let args: Vec<u8> = self.draws.iter().flat_map(|draw| draw.as_bytes().iter().copied()).collect();
+            queue.write_buffer(&self.indirect_buffer, 0, &args);
+            queue.write_buffer(&self.origin_buffer, 0, bytemuck::cast_slice(&origins));
+        }
+    }
+}
+
+/// Draw a list of chunks from the scene's mesh arena, draw i at origin i
+fn draw_chunk_list(rp: &mut RenderPass, scene: &Scene, list: &ChunkDrawList) {
+    let draw_count = list.draws.len() as u32;
+    if draw_count == 0 {
+        return;
+    }
+    rp.set_vertex_buffer(0, scene.arena().vertex_buffer.slice(..));
+    rp.set_vertex_buffer(1, list.origin_buffer.slice(..));
+    rp.set_index_buffer(scene.arena().index_buffer.slice(..), IndexFormat::Uint32);
+    if list.indirect {
+        rp.multi_draw_indexed_indirect(&list.indirect_buffer, 0, draw_count);
+    } else {
+        for (i, draw) in list.draws.iter().enumerate() {
+            let first_instance = i as u32;
+            rp.draw_indexed(draw.first_index..draw.first_index + draw.index_count, 0, first_instance..first_instance + 1);
+        }
+    }
+}
+
+/// Vertex buffers of chunk pipelines: packed vertices plus the chunk origin per instance
+pub fn chunk_vertex_buffers() -> [wgpu::VertexBufferLayout<'static>; 2] {
+    [
+        // the mesh arena
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ChunkVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute { offset: 0, shader_location: 0, format: wgpu::VertexFormat::Uint32 },
+                wgpu::VertexAttribute { offset: 4, shader_location: 1, format: wgpu::VertexFormat::Uint32 },
+                wgpu::VertexAttribute { offset: 8, shader_location: 3, format: wgpu::VertexFormat::Uint32 },
+            ],
+        },
+        // ChunkDrawList::origin_buffer
+        wgpu::VertexBufferLayout {
+            array_stride: 12,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute { offset: 0, shader_location: 2, format: wgpu::VertexFormat::Sint32x3 },
+            ],
+        },
+    ]
+}
+
+fn create_draw_buffers(device: &wgpu::Device, capacity: u32) -> (wgpu::Buffer, wgpu::Buffer) {
+    let indirect_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("chunk_indirect_buffer"),
+        size: capacity as u64 * std::mem::size_of::<DrawIndexedIndirectArgs>() as u64,
+        usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let origin_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("chunk_origin_buffer"),
+        size: capacity as u64 * 12,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    (indirect_buffer, origin_buffer)
+}
+
 pub fn create_depth_texture(device: &wgpu::Device, width: u32, height: u32) -> (wgpu::Texture, wgpu::TextureView) {
     let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
         label: Some("depth_texture"),
@@ -50,10 +176,15 @@ pub fn create_camera_resources(device: &wgpu::Device) -> CameraResources {
     });
     let lighting_buffer = device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("lighting_buffer"),
-        size: 32,
+        size: 80,
         usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         mapped_at_creation: false,
     });
+    let palette_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("palette_buffer"),
+        contents: bytemuck::cast_slice(&Block::palette()),
+        usage: wgpu::BufferUsages::UNIFORM,
+    });
 
     let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         label: Some("camera_bind_group_layout"),
@@ -78,6 +209,16 @@ pub fn create_camera_resources(device: &wgpu::Device) -> CameraResources {
                 },
                 count: None,
             },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
         ],
     });
 
@@ -87,16 +228,18 @@ pub fn create_camera_resources(device: &wgpu::Device) -> CameraResources {
         entries: &[
             wgpu::BindGroupEntry { binding: 0, resource: camera_buffer.as_entire_binding() },
             wgpu::BindGroupEntry { binding: 1, resource: lighting_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 2, resource: palette_buffer.as_entire_binding() },
         ],
     });
 
-    CameraResources { camera_buffer, lighting_buffer, bind_group_layout, camera_bind_group }
+    CameraResources { camera_buffer, lighting_buffer, palette_buffer, bind_group_layout, camera_bind_group }
 }
 
 pub fn create_chunk_pipelines(
     device: &wgpu::Device,
     format: wgpu::TextureFormat,
     bind_group_layout: &wgpu::BindGroupLayout,
+    shadow_bind_group_layout: &wgpu::BindGroupLayout,
     depth_format: wgpu::TextureFormat,
 ) -> PipelineResources {
     let shader_src = include_str!("shaders/chunk.wgsl");
@@ -107,72 +250,19 @@ pub fn create_chunk_pipelines(
 
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("pipeline_layout"),
-        bind_group_layouts: &[bind_group_layout],
+        bind_group_layouts: &[bind_group_layout, shadow_bind_group_layout],
         push_constant_ranges: &[],
     });
 
-    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("render_pipeline"),
-        layout: Some(&pipeline_layout),
-        vertex: wgpu::VertexState {
-            module: &shader,
-            entry_point: Some("vs_main"),
-            buffers: &[wgpu::VertexBufferLayout {
-                array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-                step_mode: wgpu::VertexStepMode::Vertex,
-                attributes: &[
-                    wgpu::VertexAttribute { offset: 0, shader_location: 0, format: wgpu::VertexFormat::Float32x3 },
-                    wgpu::VertexAttribute { offset: 12, shader_location: 1, format: wgpu::VertexFormat::Float32x3 },
-                    wgpu::VertexAttribute { offset: 24, shader_location: 2, format: wgpu::VertexFormat::Float32x4 },
-                    wgpu::VertexAttribute { offset: 40, shader_location: 3, format: wgpu::VertexFormat::Float32x2 },
-                ],
-            }],
-            compilation_options: Default::default(),
-        },
-        fragment: Some(wgpu::FragmentState {
-            module: &shader,
-            entry_point: Some("fs_main"),
-            targets: &[Some(wgpu::ColorTargetState { format, blend: Some(wgpu::BlendState::ALPHA_BLENDING), write_mask: wgpu::ColorWrites::ALL })],
-            compilation_options: Default::default(),
-        }),
-        primitive: wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleList,
-            strip_index_format: None,
-            front_face: wgpu::FrontFace::Ccw,
-            cull_mode: Some(wgpu::Face::Back),
-            polygon_mode: wgpu::PolygonMode::Fill,
-            unclipped_depth: false,
-            conservative: false,
-        },
-        depth_stencil: Some(wgpu::DepthStencilState {
-            format: depth_format,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
-        }),
-        multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
-        multiview: None,
-        cache: None,
-    });
-
-    let wireframe_pipeline = if device.features().contains(wgpu::Features::POLYGON_MODE_LINE) {
-        Some(device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("wireframe_pipeline"),
+    // chunks and far terrain share the shader and bind group but not the vertex format
+    let create_pipeline = |label: &str, entry_point: &str, buffers: &[wgpu::VertexBufferLayout], polygon_mode: wgpu::PolygonMode| {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    attributes: &[
-                        wgpu::VertexAttribute { offset: 0, shader_location: 0, format: wgpu::VertexFormat::Float32x3 },
-                        wgpu::VertexAttribute { offset: 12, shader_location: 1, format: wgpu::VertexFormat::Float32x3 },
-                        wgpu::VertexAttribute { offset: 24, shader_location: 2, format: wgpu::VertexFormat::Float32x4 },
-                        wgpu::VertexAttribute { offset: 40, shader_location: 3, format: wgpu::VertexFormat::Float32x2 },
-                    ],
-                }],
+                entry_point: Some(entry_point),
+                buffers,
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
@@ -186,24 +276,43 @@ pub fn create_chunk_pipelines(
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Line,
+                polygon_mode,
                 unclipped_depth: false,
                 conservative: false,
             },
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: depth_format,
                 depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
+                // reversed depth, see Camera::view_proj
+                depth_compare: wgpu::CompareFunction::Greater,
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
             multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
             multiview: None,
             cache: None,
-        }))
+        })
+    };
+
+    let chunk_buffers = chunk_vertex_buffers();
+    let horizon_buffers = [wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[
+            wgpu::VertexAttribute { offset: 0, shader_location: 0, format: wgpu::VertexFormat::Float32x3 },
+            wgpu::VertexAttribute { offset: 12, shader_location: 1, format: wgpu::VertexFormat::Float32x3 },
+            wgpu::VertexAttribute { offset: 24, shader_location: 2, format: wgpu::VertexFormat::Float32x4 },
+            wgpu::VertexAttribute { offset: 40, shader_location: 3, format: wgpu::VertexFormat::Float32x2 },
+        ],
+    }];
+
+    let pipeline = create_pipeline("render_pipeline", "vs_chunk", &chunk_buffers, wgpu::PolygonMode::Fill);
+    let horizon_pipeline = create_pipeline("horizon_pipeline", "vs_main", &horizon_buffers, wgpu::PolygonMode::Fill);
+    let wireframe_pipeline = if device.features().contains(wgpu::Features::POLYGON_MODE_LINE) {
+        Some(create_pipeline("wireframe_pipeline", "vs_chunk", &chunk_buffers, wgpu::PolygonMode::Line))
     } else { None };
 
-    PipelineResources { pipeline, wireframe_pipeline }
+    PipelineResources { pipeline, wireframe_pipeline, horizon_pipeline }
 }
 
 pub fn create_outline_resources(
@@ -295,7 +404,7 @@ pub fn create_outline_resources(
         depth_stencil: Some(wgpu::DepthStencilState {
             format: depth_format,
             depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
+            depth_compare: wgpu::CompareFunction::Greater,
             stencil: wgpu::StencilState::default(),
             bias: wgpu::DepthBiasState::default(),
         }),
@@ -320,6 +429,7 @@ pub struct RenderState {
     // Pipelines
     pub pipeline: RenderPipeline,
     pub wireframe_pipeline: Option<RenderPipeline>,
+    pub horizon_pipeline: RenderPipeline,
     pub outline_pipeline: RenderPipeline,
     
     // Meshes
@@ -327,6 +437,13 @@ pub struct RenderState {
     pub show_outline: bool,
     pub chunk_border_mesh: MeshBuffer,
     pub show_chunk_borders: bool,
+    pub chunk_draws: ChunkDrawList,
+
+    // Sky and shadows
+    pub sky: SkyResources,
+    pub shadows: ShadowResources,
+    /// Chunks around the camera that cast shadows, also those it doesn't see
+    pub shadow_draws: ChunkDrawList,
     
     // Camera state
     pub player_pos: Vec3,
@@ -343,6 +460,9 @@ pub struct RenderState {
     pub egui_full_output: Option<egui::FullOutput>,
     pub egui_dpr: f32,
     pub wireframe_mode: bool,
+
+    /// Clear color behind the terrain (time of day, see model/daylight.rs)
+    pub sky_color: [f32; 3],
 }
 
 impl RenderState {
@@ -351,7 +471,7 @@ impl RenderState {
         device: &Device,
         queue: &Queue,
         surface: &Surface,
-        scene_chunks: &Vec<Option<Rc<(Chunk, (u8, MeshBuffer))>>>,
+        scene: &Scene,
         depth_view: &TextureView,
         cam_bg: &BindGroup,
         outline_bg: &BindGroup,
@@ -394,6 +514,34 @@ impl RenderState {
             label: Some("encoder"),
         });
 
+        self.chunk_draws.update(device, queue, scene.visible_meshes());
+
+        // SHADOW PASSES (depth from the sun, one per cascade)
+        let cascades = self.shadows.active_cascades();
+        if cascades > 0 {
+            self.shadow_draws.update(device, queue, scene.meshes_near(self.shadows.caster_center, self.shadows.caster_radius));
+        }
+        for (layer_view, cascade_bg) in self.shadows.layer_views.iter().zip(&self.shadows.cascade_bind_groups).take(cascades) {
+            let mut rp = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("shadow_pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                    view: layer_view,
+                    depth_ops: Some(Operations {
+                        load: LoadOp::Clear(1.0),
+                        store: StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            rp.set_pipeline(&self.shadows.pipeline);
+            rp.set_bind_group(0, cascade_bg, &[]);
+            draw_chunk_list(&mut rp, scene, &self.shadow_draws);
+        }
+
+        let sky = self.sky_color;
         {
             let mut rp = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("render_pass"),
@@ -402,9 +550,9 @@ impl RenderState {
                     resolve_target: None,
                     ops: Operations {
                         load: LoadOp::Clear(Color {
-                            r: 0.5,
-                            g: 0.8,
-                            b: 1.0,
+                            r: sky[0] as f64,
+                            g: sky[1] as f64,
+                            b: sky[2] as f64,
                             a: 1.0,
                         }),
                         store: StoreOp::Store,
@@ -414,7 +562,7 @@ impl RenderState {
                 depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
                     view: depth_view,
                     depth_ops: Some(Operations {
-                        load: LoadOp::Clear(1.0),
+                        load: LoadOp::Clear(0.0), // reversed depth: 0 is the far plane
                         store: StoreOp::Store,
                     }),
                     stencil_ops: None,
@@ -423,28 +571,30 @@ impl RenderState {
                 occlusion_query_set: None,
             });
 
-            let active_pipeline = if self.wireframe_mode && self.wireframe_pipeline.is_some() {
-                self.wireframe_pipeline.as_ref().unwrap()
-            } else {
-                &self.pipeline
-            };
+            // DRAW SKY (behind everything)
+            rp.set_pipeline(&self.sky.pipeline);
+            rp.set_bind_group(0, &self.sky.bind_group, &[]);
+            rp.draw(0..3, 0..1);
 
-            rp.set_pipeline(active_pipeline);
             rp.set_bind_group(0, cam_bg, &[]);
+            rp.set_bind_group(1, &self.shadows.bind_group, &[]);
+
+            // DRAW FAR TERRAIN (lowered below the chunks where both exist)
+            rp.set_pipeline(&self.horizon_pipeline);
+            for mesh_buffer in scene.horizon.meshes() {
+                rp.set_vertex_buffer(0, mesh_buffer.vertex_buffer.slice(..));
+                rp.set_index_buffer(mesh_buffer.index_buffer.slice(..), IndexFormat::Uint32);
+                rp.draw_indexed(0..mesh_buffer.index_count, 0, 0..1);
+            }
 
+            let active_pipeline = match (self.wireframe_mode, &self.wireframe_pipeline) {
+                (true, Some(wireframe_pipeline)) => wireframe_pipeline,
+                _ => &self.pipeline,
+            };
+            rp.set_pipeline(active_pipeline);
 
-            // DRAW CHUNKS
-            for entry in scene_chunks.iter() {
-                // Render mesh if this chunk has one
-                if let Some((_, (_, mesh_buffer))) = entry.as_deref() {
-                    if mesh_buffer.index_count == 0 {
-                        continue; // Skip empty meshes
-                    }
-                    rp.set_vertex_buffer(0, mesh_buffer.vertex_buffer.slice(..));
-                    rp.set_index_buffer(mesh_buffer.index_buffer.slice(..), IndexFormat::Uint32);
-                    rp.draw_indexed(0..mesh_buffer.index_count, 0, 0..1);
-                }
-            }
+            // DRAW CHUNKS (all from the mesh arena)
+            draw_chunk_list(&mut rp, scene, &self.chunk_draws);
 
             // Render block outline
             if self.show_outline {
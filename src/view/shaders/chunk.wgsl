@@ -8,15 +8,52 @@ struct Lighting {
     sun_dir_z: f32,
     sun_intensity: f32,
     ambient: f32,
-    _pad1: f32,
-    _pad2: f32,
-    _pad3: f32,
+    // distance fog (model/fog.rs): 0 linear, 1 exp, 2 exp²
+    fog_mode: u32,
+    fog_start: f32,
+    fog_end: f32,
+    fog_color_r: f32,
+    fog_color_g: f32,
+    fog_color_b: f32,
+    fog_density: f32,
+    eye_x: f32,
+    eye_y: f32,
+    eye_z: f32,
+    // light left where no sky light reaches (DimensionSettings::cave_light)
+    cave_light: f32,
+    // seconds since start, animates lava
+    seconds: f32,
 };
 
 @group(0) @binding(0)
 var<uniform> camera: Camera;
 @group(0) @binding(1)
 var<uniform> lighting: Lighting;
+// face colors by block id * 6 + face direction, then the light color and
+// emission of every block at GLOW + block id (Block::palette)
+@group(0) @binding(2)
+var<uniform> palette: array<vec4<f32>, 448>;
+const GLOW: u32 = 384u;
+// Block::Lava, drawn with a flowing glow
+const LAVA: u32 = 53u;
+
+// Cascaded shadow maps (view/shadows.rs)
+struct Shadows {
+    light_view_proj: array<mat4x4<f32>, 4>,
+    // world size of a shadow map texel per cascade
+    texel_world: vec4<f32>,
+    cascade_count: u32,
+    enabled: u32,
+    texel_size: f32,
+    _pad: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> shadows: Shadows;
+@group(1) @binding(1)
+var shadow_maps: texture_depth_2d_array;
+@group(1) @binding(2)
+var shadow_sampler: sampler_comparison;
 
 struct VsIn {
     @location(0) pos: vec3<f32>,
@@ -30,6 +67,10 @@ struct VsOut {
     @location(0) color: vec4<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) world_pos: vec3<f32>,
+    // sky and block light 0..1 (model/light.rs)
+    @location(3) light: vec2<f32>,
+    // block id and the id of the block the block light comes from
+    @location(4) @interpolate(flat) blocks: vec2<u32>,
 };
 
 @vertex
@@ -39,9 +80,94 @@ fn vs_main(in: VsIn) -> VsOut {
     out.color = in.color;
     out.normal = in.normal;
     out.world_pos = in.pos;
+    // far terrain lies under the open sky
+    out.light = vec2<f32>(1.0, 0.0);
+    out.blocks = vec2<u32>(0u);
+    return out;
+}
+
+// Packed chunk vertex (utils::ChunkVertex), drawn as one instance at the chunk origin
+struct ChunkVsIn {
+    @location(0) geometry: u32,
+    @location(1) material: u32,
+    @location(2) origin: vec3<i32>,
+    @location(3) light: u32,
+};
+
+@vertex
+fn vs_chunk(in: ChunkVsIn) -> VsOut {
+    let local = vec3<u32>(in.geometry & 31u, (in.geometry >> 5u) & 31u, (in.geometry >> 10u) & 31u);
+    let face = (in.geometry >> 15u) & 7u;
+    let block = in.material & 255u;
+    // biome tint: factor * 128 per channel
+    let tint = vec3<f32>(
+        f32((in.material >> 8u) & 255u),
+        f32((in.material >> 16u) & 255u),
+        f32(in.material >> 24u),
+    ) / 128.0;
+
+    let base = palette[block * 6u + face];
+    let world_pos = vec3<f32>(in.origin) + vec3<f32>(local);
+
+    var out: VsOut;
+    out.pos = camera.view_proj * vec4<f32>(world_pos, 1.0);
+    out.color = vec4<f32>(min(base.rgb * tint, vec3<f32>(1.0)), base.a);
+    // faces come in +/- pairs per axis: 0/1 = x, 2/3 = y, 4/5 = z
+    var normal = vec3<f32>(0.0);
+    normal[face / 2u] = select(1.0, -1.0, (face & 1u) == 1u);
+    out.normal = normal;
+    out.world_pos = world_pos;
+    out.light = vec2<f32>(f32(in.light & 15u), f32((in.light >> 4u) & 15u)) / 15.0;
+    out.blocks = vec2<u32>(block, (in.light >> 8u) & 255u);
     return out;
 }
 
+// Fraction of sunlight reaching a point: 3x3 PCF in the first cascade that covers it
+fn sun_visibility(world_pos: vec3<f32>, normal: vec3<f32>) -> f32 {
+    if shadows.enabled == 0u {
+        return 1.0;
+    }
+    for (var i = 0u; i < shadows.cascade_count; i = i + 1u) {
+        // push the lookup off the surface by a texel against shadow acne
+        let offset_pos = world_pos + normal * shadows.texel_world[i] * 1.5;
+        let light = shadows.light_view_proj[i] * vec4<f32>(offset_pos, 1.0);
+        let uv = light.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5);
+        if all(uv > vec2<f32>(0.0)) && all(uv < vec2<f32>(1.0)) && light.z > 0.0 && light.z < 1.0 {
+            var lit = 0.0;
+            for (var x = -1; x <= 1; x = x + 1) {
+                for (var y = -1; y <= 1; y = y + 1) {
+                    let texel = vec2<f32>(f32(x), f32(y)) * shadows.texel_size;
+                    lit = lit + textureSampleCompareLevel(shadow_maps, shadow_sampler, uv + texel, i, light.z);
+                }
+            }
+            return lit / 9.0;
+        }
+    }
+    // beyond the shadow distance
+    return 1.0;
+}
+
+// Brightness of a light level 0..1, each level 80% of the one above
+fn light_curve(level: f32) -> f32 {
+    return pow(0.8, (1.0 - level) * 15.0);
+}
+
+// Fraction of the fog color at a distance from the camera (Fog::amount)
+fn fog_amount(distance: f32) -> f32 {
+    switch lighting.fog_mode {
+        case 1u: {
+            return 1.0 - exp(-distance * lighting.fog_density);
+        }
+        case 2u: {
+            let d = distance * lighting.fog_density;
+            return 1.0 - exp(-d * d);
+        }
+        default: {
+            return clamp((distance - lighting.fog_start) / (lighting.fog_end - lighting.fog_start), 0.0, 1.0);
+        }
+    }
+}
+
 @fragment
 fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     // Reconstruct sun direction from individual components
@@ -54,10 +180,34 @@ fn fs_main(in: VsOut) -> @location(0) vec4<f32> {
     let normal = normalize(in.normal);
     // Use smoothstep for softer transitions instead of max(dot, 0)
     let sun_dot = dot(normal, sun_dir);
-    let sun_light = smoothstep(-0.2, 0.8, sun_dot) * lighting.sun_intensity;
+    let sun_light = smoothstep(-0.2, 0.8, sun_dot) * lighting.sun_intensity * sun_visibility(in.world_pos, normal);
     light_amount = light_amount + sun_light;
-    
+
+    // Sun and ambient light only reach as far as the sky light, block light shines on its own
+    // in the color of its source
+    let sky = max(light_curve(in.light.x), lighting.cave_light);
+    let block_light = light_curve(in.light.y) * step(0.01, in.light.y) * palette[GLOW + in.blocks.y].rgb;
+    var light_color = max(vec3<f32>(light_amount * sky), block_light);
+
+    var color = vec3<f32>(in.color.x, in.color.y, in.color.z);
+    if in.blocks.x == LAVA {
+        // slow bright waves drifting over the surface
+        let p = in.world_pos;
+        let t = lighting.seconds;
+        let wave = sin(p.x * 0.9 + t * 0.7 + sin(p.z * 0.6 + t * 0.5) * 2.0) * sin(p.z * 0.8 - t * 0.6 + p.y * 0.5);
+        color = mix(color, vec3<f32>(1.0, 0.8, 0.25), 0.5 + 0.5 * wave);
+    }
+    // Glowing blocks are not lit, they shine
+    if palette[GLOW + in.blocks.x].w > 0.0 {
+        light_color = vec3<f32>(1.0);
+    }
+
     // Apply lighting to color (preserve alpha)
-    let lit_color = vec3<f32>(in.color.x, in.color.y, in.color.z) * light_amount;
-    return vec4<f32>(lit_color, in.color.w);
+    let lit_color = color * light_color;
+
+    // Fade into the fog with the distance from the camera
+    let eye = vec3<f32>(lighting.eye_x, lighting.eye_y, lighting.eye_z);
+    let fog_color = vec3<f32>(lighting.fog_color_r, lighting.fog_color_g, lighting.fog_color_b);
+    let fogged = mix(lit_color, fog_color, fog_amount(distance(in.world_pos, eye)));
+    return vec4<f32>(fogged, in.color.w);
 }
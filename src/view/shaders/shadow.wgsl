@@ -0,0 +1,29 @@
+// Depth of the chunks as seen from the sun, one pass per shadow cascade (view/shadows.rs)
+
+@group(0) @binding(0)
+var<uniform> light_view_proj: mat4x4<f32>;
+// face colors by block id * 6 + face direction, then light colors (Block::palette)
+@group(0) @binding(1)
+var<uniform> palette: array<vec4<f32>, 448>;
+
+// Packed chunk vertex (utils::ChunkVertex), drawn as one instance at the chunk origin
+struct ChunkVsIn {
+    @location(0) geometry: u32,
+    @location(1) material: u32,
+    @location(2) origin: vec3<i32>,
+};
+
+@vertex
+fn vs_shadow(in: ChunkVsIn) -> @builtin(position) vec4<f32> {
+    let local = vec3<u32>(in.geometry & 31u, (in.geometry >> 5u) & 31u, (in.geometry >> 10u) & 31u);
+    let face = (in.geometry >> 15u) & 7u;
+    let block = in.material & 255u;
+
+    // see-through blocks (water, ice, clouds) cast no shadow: collapse their
+    // triangles to a point outside the clip volume
+    if palette[block * 6u + face].a < 1.0 {
+        return vec4<f32>(2.0, 2.0, 2.0, 1.0);
+    }
+    let world_pos = vec3<f32>(in.origin) + vec3<f32>(local);
+    return light_view_proj * vec4<f32>(world_pos, 1.0);
+}
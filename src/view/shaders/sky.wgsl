@@ -0,0 +1,112 @@
+// Sky dome behind the terrain: gradient, sun, moon, stars and clouds (view/sky.rs)
+
+struct Sky {
+    inv_view_proj: mat4x4<f32>,
+    // xyz = camera position, w = seconds (cloud drift, twinkling)
+    eye: vec4<f32>,
+    // xyz = towards the sun, w = world y of the cloud layer
+    sun_dir: vec4<f32>,
+    // rgb = sky overhead, w = 1 with sun, moon, stars and clouds
+    zenith: vec4<f32>,
+    // rgb = sky at the horizon, w = daylight (0 at night, 1 by day)
+    horizon: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> sky: Sky;
+
+struct VsOut {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+// one triangle covering the screen
+@vertex
+fn vs_sky(@builtin(vertex_index) index: u32) -> VsOut {
+    let ndc = vec2<f32>(f32(index & 1u) * 4.0 - 1.0, f32(index >> 1u) * 4.0 - 1.0);
+    var out: VsOut;
+    out.pos = vec4<f32>(ndc, 0.0, 1.0);
+    out.ndc = ndc;
+    return out;
+}
+
+fn hash2(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+fn hash3(p: vec3<f32>) -> f32 {
+    return fract(sin(dot(p, vec3<f32>(127.1, 311.7, 74.7))) * 43758.5453);
+}
+
+fn value_noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let a = hash2(i);
+    let b = hash2(i + vec2<f32>(1.0, 0.0));
+    let c = hash2(i + vec2<f32>(0.0, 1.0));
+    let d = hash2(i + vec2<f32>(1.0, 1.0));
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+fn fbm(p: vec2<f32>) -> f32 {
+    var sum = 0.0;
+    var amplitude = 0.5;
+    var q = p;
+    for (var i = 0; i < 4; i = i + 1) {
+        sum = sum + value_noise(q) * amplitude;
+        q = q * 2.03;
+        amplitude = amplitude * 0.5;
+    }
+    return sum;
+}
+
+@fragment
+fn fs_sky(in: VsOut) -> @location(0) vec4<f32> {
+    let far = sky.inv_view_proj * vec4<f32>(in.ndc, 0.5, 1.0);
+    let dir = normalize(far.xyz / far.w - sky.eye.xyz);
+
+    // gradient from the horizon haze to the sky overhead
+    let up = max(dir.y, 0.0);
+    var color = mix(sky.zenith.rgb, sky.horizon.rgb, pow(1.0 - up, 3.0));
+    if sky.zenith.w == 0.0 {
+        return vec4<f32>(color, 1.0);
+    }
+
+    let day = sky.horizon.w;
+    let sun = normalize(sky.sun_dir.xyz);
+    let above_horizon = smoothstep(-0.02, 0.02, dir.y);
+
+    // stars, fixed to the sky, fading in at dusk
+    let cell = floor(dir * 300.0);
+    let star = hash3(cell);
+    if star > 0.997 {
+        let twinkle = 0.75 + 0.25 * sin(sky.eye.w * 3.0 + star * 1000.0);
+        color = color + vec3<f32>((star - 0.997) / 0.003 * twinkle * (1.0 - day) * above_horizon);
+    }
+
+    // sun disc with a glow, orange when low
+    let sun_dot = dot(dir, sun);
+    let sun_color = mix(vec3<f32>(1.0, 0.5, 0.2), vec3<f32>(1.0, 0.97, 0.85), smoothstep(0.0, 0.3, sun.y));
+    let sun_disc = smoothstep(0.9994, 0.9997, sun_dot);
+    let sun_glow = pow(max(sun_dot, 0.0), 64.0) * 0.35;
+    color = color + sun_color * (sun_disc + sun_glow) * above_horizon;
+
+    // moon disc opposite the sun
+    let moon_disc = smoothstep(0.9996, 0.9998, dot(dir, -sun));
+    color = mix(color, vec3<f32>(0.85, 0.87, 0.95), moon_disc * above_horizon);
+
+    // cloud layer: where the view ray meets the plane y = cloud height
+    let t = (sky.sun_dir.w - sky.eye.y) / dir.y;
+    if t > 0.0 {
+        let wind = vec2<f32>(3.0, 1.0) * sky.eye.w;
+        let p = (sky.eye.xz + dir.xz * t + wind) * 0.006;
+        let cover = smoothstep(0.5, 0.75, fbm(p));
+        // thin out towards the horizon and far away
+        let fade = smoothstep(0.0, 0.15, abs(dir.y)) * exp(-t * 0.0003);
+        let lit = mix(vec3<f32>(0.1, 0.11, 0.16), vec3<f32>(1.0), day);
+        let cloud = mix(lit, sky.horizon.rgb, 0.3);
+        color = mix(color, cloud, cover * fade * 0.9);
+    }
+    return vec4<f32>(color, 1.0);
+}
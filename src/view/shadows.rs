@@ -0,0 +1,416 @@
+use glam::{Mat4, Vec3, Vec4Swizzles};
+
+use crate::model::Camera;
+use super::render::chunk_vertex_buffers;
+
+// ============================================================================
+// SHADOWS: cascaded shadow maps for sunlight
+// ============================================================================
+//
+// The view frustum up to the shadow distance is split into 1-4 cascades,
+// near ones small and sharp, far ones large and coarse:
+//   → each cascade gets an orthographic projection along the sun direction
+//     around the bounding sphere of its slice of the view frustum, so its size
+//     doesn't change when the camera turns, and it moves in whole shadow map
+//     texels, so shadow edges don't shimmer when the camera moves
+//   → the projection reaches CASTER_REACH blocks further towards the sun to
+//     catch mountains outside the view that still cast into it
+//   → the chunks are drawn into one layer of a depth texture array per
+//     cascade (shaders/shadow.wgsl), see-through blocks cast no shadow
+//   → chunk.wgsl picks the first cascade a fragment falls into and filters
+//     3x3 shadow map samples (PCF)
+//
+// Shadows can be turned off for weak (WebGL) devices, no passes are drawn then.
+// ============================================================================
+
+pub const MAX_CASCADES: usize = 4;
+
+/// How far behind a cascade (towards the sun) shadow casters are drawn
+const CASTER_REACH: f32 = 128.0;
+
+/// Blend between logarithmic (1.0) and even (0.0) cascade splits
+const SPLIT_LAMBDA: f32 = 0.7;
+
+/// Shadow quality, edited in the settings window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowSettings {
+    pub enabled: bool,
+    /// 1..=MAX_CASCADES
+    pub cascades: usize,
+    /// Width and height of each cascade's shadow map
+    pub resolution: u32,
+    /// Shadows end this far from the camera
+    pub distance: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self { enabled: true, cascades: 3, resolution: 2048, distance: 256.0 }
+    }
+}
+
+impl ShadowSettings {
+    pub const RESOLUTIONS: [u32; 3] = [1024, 2048, 4096];
+
+    /// Defaults for a device: off on WebGL, where the extra passes cost too much
+    pub fn for_device(webgl: bool) -> Self {
+        Self { enabled: !webgl, ..Self::default() }
+    }
+}
+
+/// Shadow uniform of chunk.wgsl (group 1, binding 0)
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShadowUniform {
+    pub light_view_proj: [[[f32; 4]; 4]; MAX_CASCADES],
+    /// World size of a shadow map texel per cascade (normal offset of the lookup)
+    pub texel_world: [f32; MAX_CASCADES],
+    pub cascade_count: u32,
+    pub enabled: u32,
+    pub texel_size: f32,
+    pub _pad: u32,
+}
+
+/// Light projection of one cascade
+#[derive(Clone, Copy, Debug)]
+pub struct Cascade {
+    pub light_view_proj: Mat4,
+    /// View distance where the cascade ends
+    pub split: f32,
+    /// World size of a shadow map texel
+    pub texel_world: f32,
+}
+
+/// Fit the cascades to the camera frustum (see the header comment)
+pub fn fit_cascades(camera: &Camera, sun_dir: Vec3, settings: &ShadowSettings) -> Vec<Cascade> {
+    let count = settings.cascades.clamp(1, MAX_CASCADES);
+    let (near, far) = (camera.z_near, settings.distance.min(camera.z_far));
+
+    let forward = camera.forward();
+    let right = forward.cross(Vec3::Y).normalize();
+    let up = right.cross(forward);
+    let (tan_y, tan_x) = ((camera.fov_y / 2.0).tan(), (camera.fov_y / 2.0).tan() * camera.aspect);
+
+    // orientation of the light, the same for every cascade
+    let light_dir = sun_dir.normalize();
+    let light_up = if light_dir.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+    let light_view = Mat4::look_at_rh(Vec3::ZERO, -light_dir, light_up);
+
+    let mut cascades = Vec::with_capacity(count);
+    let mut start = near;
+    for i in 1..=count {
+        let t = i as f32 / count as f32;
+        let end = SPLIT_LAMBDA * near * (far / near).powf(t) + (1.0 - SPLIT_LAMBDA) * (near + (far - near) * t);
+
+        // bounding sphere of the slice (start..end) of the view frustum
+        let corners: Vec<Vec3> = [start, end]
+            .iter()
+            .flat_map(|&d| {
+                [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+                    .map(|(sx, sy)| camera.eye + forward * d + right * (sx * d * tan_x) + up * (sy * d * tan_y))
+            })
+            .collect();
+        let center = corners.iter().copied().sum::<Vec3>() / corners.len() as f32;
+        let radius = corners.iter().map(|c| c.distance(center)).fold(0.0, f32::max).ceil();
+
+        // move the projection in whole texels
+        let texel_world = 2.0 * radius / settings.resolution as f32;
+        let c = (light_view * center.extend(1.0)).xyz();
+        let (cx, cy) = ((c.x / texel_world).floor() * texel_world, (c.y / texel_world).floor() * texel_world);
+        // the light looks down -z: the near plane lies towards the sun
+        let proj = Mat4::orthographic_rh(cx - radius, cx + radius, cy - radius, cy + radius, -c.z - radius - CASTER_REACH, -c.z + radius);
+
+        cascades.push(Cascade { light_view_proj: proj * light_view, split: end, texel_world });
+        start = end;
+    }
+    cascades
+}
+
+/// GPU side of the shadows: shadow maps, uniforms and the depth pipeline
+pub struct ShadowResources {
+    /// Settings the shadow maps were last updated with
+    pub settings: ShadowSettings,
+    /// Where the shadow casters of the current frame are gathered around
+    pub caster_center: Vec3,
+    pub caster_radius: f32,
+
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    /// Shadow maps and uniform for chunk.wgsl (group 1)
+    pub bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    /// One depth layer per cascade to draw into
+    pub layer_views: Vec<wgpu::TextureView>,
+    array_view: wgpu::TextureView,
+
+    pub pipeline: wgpu::RenderPipeline,
+    cascade_buffers: Vec<wgpu::Buffer>,
+    /// Light matrix and palette per cascade for shadow.wgsl
+    pub cascade_bind_groups: Vec<wgpu::BindGroup>,
+}
+
+impl ShadowResources {
+    pub fn new(device: &wgpu::Device, palette_buffer: &wgpu::Buffer, settings: ShadowSettings) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("shadow_uniform_buffer"),
+            size: std::mem::size_of::<ShadowUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("shadow_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("shadow_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+        });
+
+        let (layer_views, array_view) = create_shadow_maps(device, settings.resolution);
+        let bind_group = create_bind_group(device, &bind_group_layout, &uniform_buffer, &array_view, &sampler);
+
+        // depth pass: one light matrix per cascade
+        let cascade_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("shadow_cascade_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                    count: None,
+                },
+            ],
+        });
+        let cascade_buffers: Vec<wgpu::Buffer> = (0..MAX_CASCADES)
+            .map(|_| device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("shadow_cascade_buffer"),
+                size: 64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }))
+            .collect();
+        let cascade_bind_groups = cascade_buffers
+            .iter()
+            .map(|buffer| device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("shadow_cascade_bind_group"),
+                layout: &cascade_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: palette_buffer.as_entire_binding() },
+                ],
+            }))
+            .collect();
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shadow_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/shadow.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("shadow_pipeline_layout"),
+            bind_group_layouts: &[&cascade_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("shadow_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_shadow"),
+                buffers: &chunk_vertex_buffers(),
+                compilation_options: Default::default(),
+            },
+            fragment: None,
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                // both sides: chunk borders leave open surfaces
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                // against shadow acne on surfaces at a grazing sun angle
+                bias: wgpu::DepthBiasState { constant: 2, slope_scale: 2.0, clamp: 0.0 },
+            }),
+            multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            settings,
+            caster_center: Vec3::ZERO,
+            caster_radius: 0.0,
+            bind_group_layout,
+            bind_group,
+            uniform_buffer,
+            sampler,
+            layer_views,
+            array_view,
+            pipeline,
+            cascade_buffers,
+            cascade_bind_groups,
+        }
+    }
+
+    /// Fit the cascades to the camera and write the uniforms, recreating the
+    /// shadow maps if the resolution changed
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, camera: &Camera, sun_dir: Vec3, settings: ShadowSettings) {
+        if settings.resolution != self.settings.resolution {
+            (self.layer_views, self.array_view) = create_shadow_maps(device, settings.resolution);
+            self.bind_group = create_bind_group(device, &self.bind_group_layout, &self.uniform_buffer, &self.array_view, &self.sampler);
+        }
+        self.settings = settings;
+
+        let cascades = fit_cascades(camera, sun_dir, &settings);
+        let mut uniform = ShadowUniform {
+            light_view_proj: [Mat4::IDENTITY.to_cols_array_2d(); MAX_CASCADES],
+            texel_world: [0.0; MAX_CASCADES],
+            cascade_count: cascades.len() as u32,
+            enabled: settings.enabled as u32,
+            texel_size: 1.0 / settings.resolution as f32,
+            _pad: 0,
+        };
+        for (i, cascade) in cascades.iter().enumerate() {
+            uniform.light_view_proj[i] = cascade.light_view_proj.to_cols_array_2d();
+            uniform.texel_world[i] = cascade.texel_world;
+            queue.write_buffer(&self.cascade_buffers[i], 0, bytemuck::cast_slice(&uniform.light_view_proj[i]));
+        }
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+
+        self.caster_center = camera.eye;
+        self.caster_radius = settings.distance + CASTER_REACH;
+    }
+
+    /// Cascades to draw this frame (none when shadows are off)
+    pub fn active_cascades(&self) -> usize {
+        if self.settings.enabled { self.settings.cascades.clamp(1, MAX_CASCADES) } else { 0 }
+    }
+}
+
+fn create_shadow_maps(device: &wgpu::Device, resolution: u32) -> (Vec<wgpu::TextureView>, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("shadow_maps"),
+        size: wgpu::Extent3d { width: resolution, height: resolution, depth_or_array_layers: MAX_CASCADES as u32 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Depth32Float,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let layer_views = (0..MAX_CASCADES as u32)
+        .map(|layer| texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("shadow_map_layer"),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..Default::default()
+        }))
+        .collect();
+    let array_view = texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("shadow_maps_view"),
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        ..Default::default()
+    });
+    (layer_views, array_view)
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    uniform_buffer: &wgpu::Buffer,
+    array_view: &wgpu::TextureView,
+    sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("shadow_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: uniform_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(array_view) },
+            wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(sampler) },
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cascades_cover_their_frustum_slice() {
+        let mut camera = Camera::new(1600, 900);
+        camera.eye = Vec3::new(100.3, 70.0, -40.7);
+        camera.yaw = 0.8;
+        camera.pitch = -0.3;
+        let settings = ShadowSettings { cascades: 4, ..ShadowSettings::default() };
+        let sun_dir = Vec3::new(0.5, 1.0, 0.3);
+        let cascades = fit_cascades(&camera, sun_dir, &settings);
+
+        assert_eq!(cascades.len(), 4);
+        assert!(cascades.windows(2).all(|w| w[0].split < w[1].split && w[0].texel_world < w[1].texel_world));
+        assert!((cascades[3].split - settings.distance).abs() < 1e-3);
+
+        // points along the view direction land inside the cascade that covers them,
+        // and so does a caster up to CASTER_REACH towards the sun
+        let mut start = camera.z_near;
+        for cascade in &cascades {
+            for d in [start, (start + cascade.split) / 2.0, cascade.split] {
+                let p = camera.eye + camera.forward() * d;
+                for q in [p, p + sun_dir.normalize() * (CASTER_REACH - 1.0)] {
+                    let ndc = cascade.light_view_proj.project_point3(q);
+                    assert!(ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0, "{ndc:?}");
+                    assert!((0.0..=1.0).contains(&ndc.z), "{ndc:?}");
+                }
+            }
+            start = cascade.split;
+        }
+
+        // a small camera move shifts the projection by whole texels
+        camera.eye.x += 0.37;
+        let moved = fit_cascades(&camera, sun_dir, &settings);
+        let shift = moved[0].light_view_proj.col(3).x - cascades[0].light_view_proj.col(3).x;
+        let texels = shift / 2.0 * settings.resolution as f32;
+        assert!((texels - texels.round()).abs() < 1e-2, "{texels}");
+    }
+}
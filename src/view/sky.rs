@@ -0,0 +1,120 @@
+use crate::model::{Camera, Daylight};
+
+// ============================================================================
+// SKY: the sky dome drawn behind everything
+// ============================================================================
+//
+// One screen-filling triangle at the start of the main pass (shaders/sky.wgsl):
+//   → a gradient from the horizon haze (Daylight::fog_color) up to the sky
+//     color overhead (Daylight::sky_color)
+//   → sun and moon discs along the directions the lighting uses
+//   → a star field that fades in at night
+//   → a cloud layer at the world's cloud height, drifting with the wind;
+//     clouds are no blocks (nothing to generate, store or mesh)
+//
+// Dimensions under a roof get the plain gradient without sun, moon, stars or
+// clouds.
+// ============================================================================
+
+/// Sky uniform of sky.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkyUniform {
+    pub inv_view_proj: [[f32; 4]; 4],
+    /// Camera position, w = seconds
+    pub eye: [f32; 4],
+    /// Towards the sun, w = cloud height
+    pub sun_dir: [f32; 4],
+    /// Sky overhead, w = 1 for an open sky
+    pub zenith: [f32; 4],
+    /// Sky at the horizon, w = daylight
+    pub horizon: [f32; 4],
+}
+
+pub struct SkyResources {
+    pub pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl SkyResources {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, depth_format: wgpu::TextureFormat) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sky_uniform_buffer"),
+            size: std::mem::size_of::<SkyUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("sky_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sky_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: uniform_buffer.as_entire_binding() }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sky_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/sky.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("sky_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("sky_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_sky"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_sky"),
+                targets: &[Some(wgpu::ColorTargetState { format, blend: Some(wgpu::BlendState::REPLACE), write_mask: wgpu::ColorWrites::ALL })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // drawn first and behind everything: no depth test, no depth written
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
+            multiview: None,
+            cache: None,
+        });
+
+        Self { pipeline, uniform_buffer, bind_group }
+    }
+
+    /// Write the sky of this frame, `clouds` is the cloud height of an open sky
+    /// (None under a roof)
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera, daylight: &Daylight, seconds: f32, clouds: Option<f32>) {
+        let [sr, sg, sb] = daylight.sky_color;
+        let [fr, fg, fb] = daylight.fog_color;
+        let eye = camera.eye;
+        let sun = daylight.sun_dir;
+        let uniform = SkyUniform {
+            inv_view_proj: camera.view_proj().inverse().to_cols_array_2d(),
+            eye: [eye.x, eye.y, eye.z, seconds],
+            sun_dir: [sun.x, sun.y, sun.z, clouds.unwrap_or(0.0)],
+            zenith: [sr, sg, sb, clouds.is_some() as u32 as f32],
+            horizon: [fr, fg, fb, daylight.day],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+}